@@ -0,0 +1,103 @@
+//! renders of a long document with `on_click`,
+//! with a listener per element and with the clicks delegated to the root
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::future::Future;
+use std::pin::Pin;
+use std::rc::Rc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use criterion::{criterion_group, criterion_main, Criterion};
+use dioxus::prelude::*;
+use dioxus::html::prelude::{EvalError, EvalProvider, Evaluator};
+use dioxus_markdown::*;
+
+/// counts the allocations, to compare the renders beyond their duration
+struct CountingAlloc;
+
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for CountingAlloc {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAlloc = CountingAlloc;
+
+/// an evaluator that accepts any script and never answers,
+/// so that the renderer can delegate the clicks as on web
+struct SilentEval;
+
+impl EvalProvider for SilentEval {
+    fn new_evaluator(&self, _js: String) -> Result<Rc<dyn Evaluator>, EvalError> {
+        Ok(Rc::new(SilentEval))
+    }
+}
+
+impl Evaluator for SilentEval {
+    fn send(&self, _data: serde_json::Value) -> Result<(), EvalError> {
+        Ok(())
+    }
+
+    fn recv(&self) -> Pin<Box<dyn Future<Output = Result<serde_json::Value, EvalError>>>> {
+        Box::pin(std::future::pending())
+    }
+
+    fn join(&self) -> Pin<Box<dyn Future<Output = Result<serde_json::Value, EvalError>>>> {
+        Box::pin(std::future::pending())
+    }
+}
+
+/// about 3000 elements
+fn document() -> &'static str {
+    let section = "## Section\n\nSome *emphasis*, some **strong** text and `code`.\n\n- one\n- two\n- three\n\n> a quote\n\n";
+    Box::leak(section.repeat(250).into_boxed_str())
+}
+
+fn per_element(cx: Scope) -> Element {
+    let src = *cx.use_hook(document);
+    render!{
+        Markdown { src: src, on_click: move |_: MarkdownMouseEvent| () }
+    }
+}
+
+fn delegated(cx: Scope) -> Element {
+    cx.use_hook(|| cx.provide_context(Rc::new(SilentEval) as Rc<dyn EvalProvider>));
+    let src = *cx.use_hook(document);
+    render!{
+        Markdown { src: src, on_click: move |_: MarkdownMouseEvent| (), delegate_clicks: true }
+    }
+}
+
+fn mount(app: fn(Scope) -> Element) -> VirtualDom {
+    let mut vdom = VirtualDom::new(app);
+    let _ = vdom.rebuild();
+    vdom
+}
+
+fn rerender(vdom: &mut VirtualDom) {
+    vdom.mark_dirty(ScopeId(0));
+    let _ = vdom.render_immediate();
+}
+
+fn renders(c: &mut Criterion) {
+    let apps: [(&str, fn(Scope) -> Element); 2] = [("per element", per_element), ("delegated", delegated)];
+    for (name, app) in apps {
+        let mut vdom = mount(app);
+        let before = ALLOCATIONS.load(Ordering::Relaxed);
+        rerender(&mut vdom);
+        let allocations = ALLOCATIONS.load(Ordering::Relaxed) - before;
+        println!("click listeners {name}: {allocations} allocations per render");
+
+        c.bench_function(&format!("render, click listeners {name}"), |b| b.iter(|| rerender(&mut vdom)));
+    }
+}
+
+criterion_group!(benches, renders);
+criterion_main!(benches);
@@ -0,0 +1,74 @@
+#![allow(non_snake_case)]
+use dioxus::prelude::*;
+
+use dioxus_markdown::*;
+
+static MARKDOWN_SOURCE: &str = r#"
+## Repositories
+
+<GithubCard repo="rambip/dioxus-markdown"/>
+
+<GithubCard repo="rambip/rust-web-markdown"/>
+
+<GithubCard/>
+"#;
+
+struct Repository {
+    name: String,
+    stars: u32,
+}
+
+// pretend to ask the github api
+async fn fetch_repository(repo: String) -> Result<Repository, ComponentCreationError> {
+    gloo_timers::future::TimeoutFuture::new(1000).await;
+    Ok(Repository {
+        stars: repo.len() as u32 * 7,
+        name: repo,
+    })
+}
+
+fn App(cx: Scope) -> Element {
+    let src = use_state(cx, || MARKDOWN_SOURCE.to_string());
+
+    let mut components = CustomComponents::new();
+
+    components.register_async(
+        "GithubCard",
+        |attributes| async move {
+            match attributes.get("repo") {
+                Some(repo) => fetch_repository(repo.clone()).await,
+                None => Err("please provide the attribute `repo`".to_string().into()),
+            }
+        },
+        |cx, repository, _| Ok(render!{
+            div {
+                style: "border: 1px solid gray; padding: 4px; margin: 4px",
+                b { "{repository.name}" }
+                " ★ {repository.stars}"
+            }
+        })
+    );
+
+    components.set_async_placeholder(|cx| render!{
+        div { "loading..." }
+    });
+
+    cx.render(rsx! {
+        // editing the source only loads the cards that changed
+        textarea {
+            rows: 10,
+            cols: 60,
+            value: "{src}",
+            oninput: move |e| src.set(e.value.clone()),
+        }
+        Markdown {
+            src: src,
+            components: components
+        }
+    })
+}
+
+fn main() {
+    // launch the web app
+    dioxus_web::launch(App);
+}
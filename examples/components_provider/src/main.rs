@@ -0,0 +1,66 @@
+#![allow(non_snake_case)]
+use dioxus::prelude::*;
+
+use dioxus_markdown::*;
+
+static MARKDOWN_SOURCE: &str = r#"
+Here is a badge: <Badge label="beta"/>
+"#;
+
+#[component]
+fn Badge(cx: Scope, label: String, color: &'static str) -> Element {
+    cx.render(rsx!{
+        span {
+            style: "background: {color}; color: white; border-radius: 4px; padding: 0 4px",
+            "{label}"
+        }
+    })
+}
+
+fn App(cx: Scope) -> Element {
+    // every `Markdown` below this component can use these
+    use_markdown_components(cx, || {
+        let registry = ComponentRegistry::new();
+        registry.register("Badge", |cx, props| Ok(render!{
+            Badge {
+                label: props.attributes.get("label").cloned().unwrap_or_default(),
+                color: "blue",
+            }
+        }));
+        registry
+    });
+
+    // the components given as props take precedence
+    let mut components = CustomComponents::new();
+    components.register("Badge", |cx, props| Ok(render!{
+        Badge {
+            label: props.attributes.get("label").cloned().unwrap_or_default(),
+            color: "red",
+        }
+    }));
+
+    cx.render(rsx! {
+        h1 {"Provided components"}
+        Section {}
+
+        h1 {"Local override"}
+        Markdown {
+            src: MARKDOWN_SOURCE,
+            components: components
+        }
+    })
+}
+
+// a component far from the root, still using the provided components
+fn Section(cx: Scope) -> Element {
+    cx.render(rsx!{
+        Markdown {
+            src: MARKDOWN_SOURCE,
+        }
+    })
+}
+
+fn main() {
+    // launch the web app
+    dioxus_web::launch(App);
+}
@@ -0,0 +1,66 @@
+#![allow(non_snake_case)]
+
+use dioxus::prelude::*;
+
+use dioxus_markdown::*;
+
+static MARKDOWN_SOURCE: &str = r#"
+# Quoting a selection
+
+Select some text on this page, then click **quote**:
+the markdown of the selected elements appears below, as a quote.
+
+- the selection is mapped to the source element by element
+- so the quote may start before the first selected character
+"#;
+
+// the source ranges of the elements containing the ends of the selection
+static SELECTION_SCRIPT: &str = r#"
+const selection = window.getSelection();
+const range = node => {
+    const element = node && (node.nodeType === 1 ? node : node.parentElement);
+    const source = element && element.closest("[data-md-start]");
+    return source ? [Number(source.dataset.mdStart), Number(source.dataset.mdEnd)] : null;
+};
+return [range(selection.anchorNode), range(selection.focusNode)];
+"#;
+
+fn source_range(value: &serde_json::Value) -> Option<core::ops::Range<usize>> {
+    let start = value.get(0)?.as_u64()? as usize;
+    let end = value.get(1)?.as_u64()? as usize;
+    Some(start..end)
+}
+
+fn App(cx: Scope) -> Element {
+    let eval = use_eval(cx);
+    let quote = use_state(cx, String::new);
+
+    let on_quote = move |_| {
+        let eval = eval.clone();
+        let quote = quote.clone();
+        cx.spawn(async move {
+            let Ok(selection) = eval(SELECTION_SCRIPT) else { return };
+            let Ok(ends) = selection.join().await else { return };
+            if let (Some(anchor), Some(focus)) = (source_range(&ends[0]), source_range(&ends[1])) {
+                let range = selection_to_source_range(anchor, focus);
+                let quoted = MARKDOWN_SOURCE[range].lines()
+                    .map(|line| format!("> {line}\n"))
+                    .collect();
+                quote.set(quoted)
+            }
+        })
+    };
+
+    render!{
+        div {
+            Markdown {src: MARKDOWN_SOURCE, source_attributes: true}
+            button {onclick: on_quote, "quote"}
+            hr {}
+            pre {"{quote}"}
+        }
+    }
+}
+
+fn main() {
+    dioxus_web::launch(App)
+}
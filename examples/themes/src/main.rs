@@ -0,0 +1,56 @@
+#![allow(non_snake_case)]
+
+use dioxus::prelude::*;
+
+use dioxus_markdown::*;
+
+static MARKDOWN_SOURCE: &str = r#"
+```rust
+/// a comment
+fn main() {
+    let greeting = "hello";
+    println!("{greeting}, world");
+}
+```
+"#;
+
+fn App(cx: Scope) -> Element {
+    // hiding the dark one unmounts its stylesheet, the light one keeps its own
+    let dark = use_state(cx, || true);
+
+    render!{
+        button {
+            onclick: move |_| dark.set(!dark),
+            if **dark { "hide the dark theme" } else { "show the dark theme" }
+        }
+        div {
+            style: "display: flex; gap: 1em",
+            div {
+                style: "flex: 1",
+                Markdown {
+                    src: MARKDOWN_SOURCE,
+                    theme: "InspiredGitHub".to_string(),
+                    highlight_classes: true,
+                    unmount_unused: true,
+                }
+            }
+            if **dark {
+                rsx!{
+                    div {
+                        style: "flex: 1",
+                        Markdown {
+                            src: MARKDOWN_SOURCE,
+                            theme: "base16-eighties.dark".to_string(),
+                            highlight_classes: true,
+                            unmount_unused: true,
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+fn main() {
+    dioxus_web::launch(App)
+}
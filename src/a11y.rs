@@ -0,0 +1,23 @@
+/// accessibility settings of the rendered markdown.
+///
+/// The labels are read by screen readers, translate them
+/// if your app is not in english.
+#[derive(Clone, PartialEq, Debug)]
+pub struct A11yConfig {
+    /// label of math expressions, which are only rendered visually
+    pub math_label: String,
+    /// label of the section listing the footnotes at the end of the document
+    pub footnotes_label: String,
+    /// label of the table of contents rendered by `<Toc/>`
+    pub toc_label: String,
+}
+
+impl Default for A11yConfig {
+    fn default() -> Self {
+        Self {
+            math_label: "math expression".into(),
+            footnotes_label: "Footnotes".into(),
+            toc_label: "Table of contents".into(),
+        }
+    }
+}
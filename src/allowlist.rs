@@ -0,0 +1,162 @@
+use crate::ElementTag;
+
+/// the kinds of markdown elements a [`Markdown`](crate::Markdown) component may render.
+///
+/// Disallowed elements degrade to their content:
+/// - headings render as bold text
+/// - images render as their alt text
+/// - block quotes render their content without the quote
+/// - tables render as text, see [`TableFallback`]
+/// - horizontal rules are skipped
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct ElementAllowlist {
+    pub headings: bool,
+    pub images: bool,
+    pub block_quotes: bool,
+    pub tables: bool,
+    pub rules: bool,
+}
+
+impl ElementAllowlist {
+    /// every element is allowed
+    pub fn all() -> Self {
+        Self {
+            headings: true,
+            images: true,
+            block_quotes: true,
+            tables: true,
+            rules: true,
+        }
+    }
+
+    /// only inline formatting and paragraphs, for small surfaces like tooltips
+    pub fn none() -> Self {
+        Self {
+            headings: false,
+            images: false,
+            block_quotes: false,
+            tables: false,
+            rules: false,
+        }
+    }
+}
+
+impl Default for ElementAllowlist {
+    fn default() -> Self {
+        Self::all()
+    }
+}
+
+/// how tables are rendered where they are not allowed,
+/// or in inline mode
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum TableFallback {
+    /// a line per row, like `Header: value; Header: value`.
+    /// The cells of tables without headers are separated by commas
+    #[default]
+    Lines,
+    /// a definition list per row, with the headers as terms.
+    /// Lines are used in inline mode and for tables without headers
+    DefinitionList,
+    /// nothing is rendered, with a message in the debug info
+    Hidden,
+}
+
+/// the kinds of elements whose clicks are reported to `on_click`.
+/// Clicks on other elements propagate to their parent as if they had no handler
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct ElementFilter {
+    pub headings: bool,
+    pub paragraphs: bool,
+    pub block_quotes: bool,
+    pub lists: bool,
+    pub list_items: bool,
+    /// code blocks and inline code
+    pub code: bool,
+    /// tables, their head and their rows
+    pub tables: bool,
+    pub table_cells: bool,
+    /// definition lists, their terms and their definitions
+    pub definition_lists: bool,
+    /// emphasis, strong, strikethrough and highlighted text
+    pub formatting: bool,
+    /// checkboxes of task list items
+    pub checkboxes: bool,
+    pub rules: bool,
+    pub links: bool,
+    pub images: bool,
+    /// raw html and math
+    pub html: bool,
+    pub other: bool,
+}
+
+impl ElementFilter {
+    pub fn all() -> Self {
+        Self {
+            headings: true,
+            paragraphs: true,
+            block_quotes: true,
+            lists: true,
+            list_items: true,
+            code: true,
+            tables: true,
+            table_cells: true,
+            definition_lists: true,
+            formatting: true,
+            checkboxes: true,
+            rules: true,
+            links: true,
+            images: true,
+            html: true,
+            other: true,
+        }
+    }
+
+    pub fn none() -> Self {
+        Self {
+            headings: false,
+            paragraphs: false,
+            block_quotes: false,
+            lists: false,
+            list_items: false,
+            code: false,
+            tables: false,
+            table_cells: false,
+            definition_lists: false,
+            formatting: false,
+            checkboxes: false,
+            rules: false,
+            links: false,
+            images: false,
+            html: false,
+            other: false,
+        }
+    }
+
+    pub fn matches(&self, tag: &ElementTag) -> bool {
+        match tag {
+            ElementTag::Heading { .. } => self.headings,
+            ElementTag::Paragraph => self.paragraphs,
+            ElementTag::BlockQuote => self.block_quotes,
+            ElementTag::List { .. } => self.lists,
+            ElementTag::ListItem => self.list_items,
+            ElementTag::CodeBlock | ElementTag::Code => self.code,
+            ElementTag::Table | ElementTag::TableHead | ElementTag::TableRow => self.tables,
+            ElementTag::TableCell => self.table_cells,
+            ElementTag::DefinitionList | ElementTag::DefinitionTerm | ElementTag::DefinitionDetails => self.definition_lists,
+            ElementTag::Emphasis | ElementTag::Strong | ElementTag::Strikethrough | ElementTag::Mark => self.formatting,
+            ElementTag::Checkbox { .. } => self.checkboxes,
+            ElementTag::Rule => self.rules,
+            ElementTag::Link { .. } => self.links,
+            ElementTag::Image { .. } => self.images,
+            ElementTag::Html => self.html,
+            ElementTag::Other => self.other,
+        }
+    }
+}
+
+impl Default for ElementFilter {
+    fn default() -> Self {
+        Self::all()
+    }
+}
@@ -0,0 +1,189 @@
+use core::ops::Range;
+
+use pulldown_cmark_wikilink::Options;
+
+use crate::rewrite::{Protected, Rewriter};
+
+/// rewrite the bare urls (`https://example.com`, `www.example.com`)
+/// and email addresses of `src` as markdown links, like GFM autolinks.
+/// Trailing punctuation is not part of the links,
+/// and they are not looked for in code, raw html or existing links,
+/// found by parsing `src` with `options`.
+///
+/// Returns the new source, and where the length of the source changed:
+/// offsets in the new source with the total change of length before them.
+/// `None` if there is no bare link
+pub(crate) fn rewrite_autolinks(src: &str, options: Options, wikilinks: bool) -> Option<(String, Vec<(usize, isize)>)> {
+    if !src.contains("://") && !src.contains("www.") && !src.contains('@') {
+        return None
+    }
+    let protected = Protected::new(src, options, wikilinks, true);
+    let mut rewriter = Rewriter::default();
+    let mut line_start = 0;
+
+    for line in src.split_inclusive('\n') {
+        let skip = |i: usize| protected.end_at(line_start + i).map(|end| (end - line_start).min(line.len()));
+        let mut kept = 0;
+        for (range, kind) in bare_links(line, skip) {
+            rewriter.keep(&line[kept..range.start]);
+            let link = &line[range.clone()];
+            match kind {
+                LinkKind::Www => {
+                    let text: String = link.chars()
+                        .flat_map(|c| c.is_ascii_punctuation().then_some('\\').into_iter().chain([c]))
+                        .collect();
+                    rewriter.replace(link.len(), &format!("[{text}](<http://{link}>)"))
+                }
+                LinkKind::Url | LinkKind::Email => {
+                    rewriter.insert("<");
+                    rewriter.keep(link);
+                    rewriter.insert(">");
+                }
+            }
+            kept = range.end;
+        }
+        rewriter.keep(&line[kept..]);
+        line_start += line.len();
+    }
+
+    rewriter.finish()
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+enum LinkKind {
+    /// starting with a scheme, written as is in `<>`
+    Url,
+    /// starting with `www.`, which needs a scheme
+    Www,
+    Email,
+}
+
+/// the ranges of the bare links of `line`.
+/// `skip` gives the end of the protected part at an offset of the line, if any
+fn bare_links(line: &str, skip: impl Fn(usize) -> Option<usize>) -> Vec<(Range<usize>, LinkKind)> {
+    let bytes = line.as_bytes();
+    let mut links = Vec::new();
+
+    let mut i = 0;
+    while i < bytes.len() {
+        if let Some(end) = skip(i) {
+            i = end;
+            continue
+        }
+        if bytes[i] == b'\\' {
+            i += 1 + line[i + 1..].chars().next().map_or(0, char::len_utf8);
+            continue
+        }
+
+        // GFM only starts autolinks after whitespace and some delimiters,
+        // which also keeps out the middle of words
+        let starts_word = match line[..i].chars().next_back() {
+            None => true,
+            Some(c) => c.is_whitespace() || matches!(c, '(' | '*' | '_' | '~'),
+        };
+        let found = match starts_word {
+            true => link_at(&line[i..]),
+            false => None,
+        };
+        match found {
+            Some((len, kind)) => {
+                // a link ends before the protected parts following it, like `<b>`
+                let len = (i + 1..i + len).find(|j| skip(*j).is_some()).map_or(len, |end| end - i);
+                links.push((i..i + len, kind));
+                i += len
+            }
+            None => i += line[i..].chars().next().map_or(1, char::len_utf8),
+        }
+    }
+    links
+}
+
+/// the length and kind of the bare link `s` starts with, if any
+fn link_at(s: &str) -> Option<(usize, LinkKind)> {
+    let (domain_start, kind) = if s.starts_with("https://") {
+        (8, LinkKind::Url)
+    } else if s.starts_with("http://") {
+        (7, LinkKind::Url)
+    } else if s.starts_with("www.") {
+        (0, LinkKind::Www)
+    } else {
+        return email_at(s).map(|len| (len, LinkKind::Email))
+    };
+
+    let end = s.find(|c: char| c.is_whitespace() || c == '<').unwrap_or(s.len());
+    let link = trim_trailing(&s[..end]);
+    let domain = &link[domain_start..];
+    let domain = &domain[..domain.find(['/', '?', '#']).unwrap_or(domain.len())];
+    valid_domain(domain).then_some((link.len(), kind))
+}
+
+/// `link` without the trailing punctuation GFM leaves out of autolinks
+fn trim_trailing(mut link: &str) -> &str {
+    loop {
+        let Some(last) = link.chars().next_back() else { return link };
+        let trimmed = match last {
+            '?' | '!' | '.' | ',' | ':' | '*' | '_' | '~' | '\'' | '"' => &link[..link.len() - 1],
+            // only the closing parentheses without opening one
+            ')' if link.matches(')').count() > link.matches('(').count() => &link[..link.len() - 1],
+            // what looks like an entity reference
+            ';' => match link[..link.len() - 1].rfind('&') {
+                Some(amp) if link[amp + 1..link.len() - 1].chars().all(|c| c.is_ascii_alphanumeric()) => &link[..amp],
+                _ => return link,
+            },
+            _ => return link,
+        };
+        link = trimmed;
+    }
+}
+
+/// whether `domain` has at least two segments of letters, digits, `-` and `_`,
+/// without `_` in the last two, like GFM requires
+fn valid_domain(domain: &str) -> bool {
+    let segments: Vec<&str> = domain.split('.').collect();
+    segments.len() >= 2
+        && segments.iter().all(|s| !s.is_empty() && s.chars().all(|c| c.is_alphanumeric() || c == '-' || c == '_'))
+        && segments[segments.len() - 2..].iter().all(|s| !s.contains('_'))
+}
+
+/// the length of the email address `s` starts with, if any
+fn email_at(s: &str) -> Option<usize> {
+    let local = s.find(|c: char| !(c.is_ascii_alphanumeric() || matches!(c, '.' | '-' | '_' | '+')))?;
+    if local == 0 || !s[local..].starts_with('@') {
+        return None
+    }
+    let rest = &s[local + 1..];
+    let domain = rest.find(|c: char| !(c.is_ascii_alphanumeric() || matches!(c, '.' | '-' | '_'))).unwrap_or(rest.len());
+    let domain = rest[..domain].trim_end_matches('.');
+    let valid = valid_domain(domain) && !domain.ends_with(['-', '_']);
+    valid.then_some(local + 1 + domain.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rewrite(src: &str) -> String {
+        rewrite_autolinks(src, Options::all(), false).map_or(src.to_string(), |(rewritten, _)| rewritten)
+    }
+
+    #[test]
+    fn links_between_inline_html_are_found() {
+        assert_eq!(rewrite("<b>see https://x.y</b>"), "<b>see <https://x.y></b>");
+    }
+
+    #[test]
+    fn nested_list_content_is_linked() {
+        assert_eq!(rewrite("- a\n    - see www.x.y\n"), "- a\n    - see [www\\.x\\.y](<http://www.x.y>)\n");
+    }
+
+    #[test]
+    fn code_links_and_html_blocks_are_left_as_is() {
+        let src = "`https://x.y`\n\n    https://x.y\n\n[https://x.y](https://z.w) <https://x.y>\n\n<div>\nhttps://x.y\n</div>\n";
+        assert_eq!(rewrite(src), src);
+    }
+
+    #[test]
+    fn emails_and_trailing_punctuation() {
+        assert_eq!(rewrite("mail a.b@x.y, or https://x.y/z."), "mail <a.b@x.y>, or <https://x.y/z>.");
+    }
+}
@@ -0,0 +1,57 @@
+use dioxus::prelude::*;
+
+use crate::components::current_call;
+use crate::{ComponentCreationError, ComponentPropsExt, MdComponentProps, OutlineHeading};
+
+/// the `<Toc/>` component, see [`CustomComponents::with_builtins`](crate::CustomComponents::with_builtins)
+pub(crate) fn toc<'a>(cx: &'a ScopeState, props: MdComponentProps<'a>) -> Result<Element<'a>, ComponentCreationError> {
+    let attributes = props.reader();
+    let min = attributes.get_parsed_optional("min")?.unwrap_or(1);
+    let max = attributes.get_parsed_optional("max")?.unwrap_or(6);
+    if let Some(unknown) = attributes.unused_attributes().first() {
+        return Err(format!("<Toc> has no attribute `{unknown}`").into())
+    }
+
+    let Some(call) = current_call() else {
+        return Err("<Toc> can only be used inside markdown".to_string().into())
+    };
+    let headings: Vec<_> = call.outline.iter()
+        .filter(|heading| (min..=max).contains(&heading.level))
+        .collect();
+
+    let label = call.toc_label.as_deref().map(|label| &*cx.bump().alloc_str(label));
+    Ok(cx.render(rsx!{
+        nav {
+            class: "md-toc",
+            aria_label: label,
+            toc_list(cx, &headings)
+        }
+    }))
+}
+
+/// a list of `headings`, each heading containing the deeper ones following it
+fn toc_list<'a>(cx: &'a ScopeState, headings: &[&OutlineHeading]) -> Element<'a> {
+    if headings.is_empty() {
+        return None
+    }
+
+    let mut items = Vec::new();
+    let mut rest = headings;
+    while let Some((heading, tail)) = rest.split_first() {
+        let deeper = tail.iter().take_while(|h| h.level > heading.level).count();
+        items.push((*heading, &tail[..deeper]));
+        rest = &tail[deeper..];
+    }
+
+    cx.render(rsx!{
+        ul {
+            for (heading, nested) in items {
+                li {
+                    key: "{heading.id}",
+                    a { href: "#{heading.id}", "{heading.title}" }
+                    toc_list(cx, nested)
+                }
+            }
+        }
+    })
+}
@@ -0,0 +1,129 @@
+use std::cell::RefCell;
+use std::collections::VecDeque;
+use std::rc::Rc;
+
+/// identifies the result of an expensive sub-render.
+/// The key contains the content itself, so that two contents never share a result;
+/// persistent caches can store its [`content_hash`] along with it
+#[derive(Clone, PartialEq, Eq, Hash, Debug)]
+#[non_exhaustive]
+pub enum CacheKey {
+    /// highlighted html of a code block or an annotated inline code span
+    Highlight {
+        code: String,
+        language: Option<String>,
+        theme: Option<String>,
+        /// highlighted with css classes instead of inline styles
+        classes: bool,
+    },
+    /// html of a math expression rendered with `math`
+    Math {
+        /// the TeX source, without delimiters
+        tex: String,
+        /// whether it is display math rather than inline math
+        display: bool,
+    },
+}
+
+/// storage for the results of expensive sub-renders
+/// like syntax highlighting and math.
+///
+/// Implement it to back the cache with persistent storage,
+/// or use [`LruRenderCache`] for an in-memory one.
+pub trait RenderCache {
+    fn get(&self, key: &CacheKey) -> Option<Rc<str>>;
+
+    fn put(&self, key: CacheKey, value: Rc<str>);
+}
+
+/// in-memory [`RenderCache`] keeping the `capacity` most recently used entries
+pub struct LruRenderCache {
+    capacity: usize,
+    // most recently used entries are at the back
+    entries: RefCell<VecDeque<(CacheKey, Rc<str>)>>,
+}
+
+impl LruRenderCache {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            entries: RefCell::new(VecDeque::with_capacity(capacity)),
+        }
+    }
+}
+
+impl Default for LruRenderCache {
+    fn default() -> Self {
+        Self::new(256)
+    }
+}
+
+impl RenderCache for LruRenderCache {
+    fn get(&self, key: &CacheKey) -> Option<Rc<str>> {
+        let mut entries = self.entries.borrow_mut();
+        let i = entries.iter().position(|(k, _)| k == key)?;
+        let entry = entries.remove(i)?;
+        let value = entry.1.clone();
+        entries.push_back(entry);
+        Some(value)
+    }
+
+    fn put(&self, key: CacheKey, value: Rc<str>) {
+        if self.capacity == 0 {
+            return
+        }
+        let mut entries = self.entries.borrow_mut();
+        entries.retain(|(k, _)| k != &key);
+        if entries.len() == self.capacity {
+            entries.pop_front();
+        }
+        entries.push_back((key, value));
+    }
+}
+
+/// hash of some content, stable across processes and platforms
+pub fn content_hash(content: &str) -> u64 {
+    let mut hasher = StableHasher::new();
+    hasher.write_str(content);
+    hasher.finish()
+}
+
+/// FNV-1a hasher, whose output only depends on the bytes written to it
+pub(crate) struct StableHasher(u64);
+
+impl StableHasher {
+    pub fn new() -> Self {
+        Self(0xcbf29ce484222325)
+    }
+
+    pub fn write(&mut self, bytes: &[u8]) {
+        for byte in bytes {
+            self.0 = (self.0 ^ *byte as u64).wrapping_mul(0x100000001b3);
+        }
+    }
+
+    /// write a string, prefixed by its length so that
+    /// consecutive strings can not be confused
+    pub fn write_str(&mut self, s: &str) {
+        self.write_u64(s.len() as u64);
+        self.write(s.as_bytes());
+    }
+
+    pub fn write_u64(&mut self, n: u64) {
+        self.write(&n.to_le_bytes())
+    }
+
+    pub fn write_bool(&mut self, b: bool) {
+        self.write(&[b as u8])
+    }
+
+    pub fn finish(&self) -> u64 {
+        self.0
+    }
+}
+
+impl Default for StableHasher {
+    fn default() -> Self {
+        Self::new()
+    }
+}
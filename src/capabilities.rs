@@ -0,0 +1,80 @@
+use std::cell::{Cell, RefCell};
+use std::rc::{Rc, Weak};
+
+use dioxus::html::prelude::EvalProvider;
+
+use crate::RenderEnv;
+
+/// the facilities of the renderer that some features rely on
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub struct Capabilities {
+    /// javascript can be evaluated in a live document
+    pub eval: bool,
+}
+
+/// a feature that was disabled because the renderer lacks a capability
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct DisabledFeature {
+    pub feature: &'static str,
+    /// what happens instead
+    pub fallback: &'static str,
+}
+
+/// every feature disabled in the app, see `on_degraded`
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct DegradationReport {
+    pub capabilities: Capabilities,
+    pub disabled: Vec<DisabledFeature>,
+}
+
+/// the features relying on javascript evaluation, and their fallback
+const EVAL_FEATURES: &[DisabledFeature] = &[
+    DisabledFeature {
+        feature: "clipboard",
+        fallback: "copy buttons are hidden, unless a `clipboard` is provided",
+    },
+    DisabledFeature {
+        feature: "stylesheet mounting",
+        fallback: "nothing is mounted, the app has to include the stylesheets",
+    },
+];
+
+thread_local! {
+    // the result of probing each evaluator, while it is alive
+    static EVAL_WORKS: RefCell<Vec<(Weak<dyn EvalProvider>, bool)>> = RefCell::new(Vec::new());
+    static REPORTED: Cell<bool> = Cell::new(false);
+}
+
+/// whether `eval` can evaluate javascript, probing it on its first use
+fn eval_works(eval: &Rc<dyn EvalProvider>) -> bool {
+    EVAL_WORKS.with(|probed| {
+        let mut probed = probed.borrow_mut();
+        probed.retain(|(provider, _)| provider.strong_count() > 0);
+        let known = probed.iter()
+            .find(|(provider, _)| provider.upgrade().is_some_and(|p| Rc::ptr_eq(&p, eval)));
+        if let Some((_, works)) = known {
+            return *works
+        }
+        let works = eval.new_evaluator(String::new()).is_ok();
+        probed.push((Rc::downgrade(eval), works));
+        works
+    })
+}
+
+/// the capabilities of the renderer, given its evaluator and the environment
+pub(crate) fn probe(eval: Option<&Rc<dyn EvalProvider>>, env: RenderEnv) -> Capabilities {
+    Capabilities {
+        eval: env == RenderEnv::Client && eval.is_some_and(eval_works),
+    }
+}
+
+/// call `on_degraded` with the disabled features, if it was not already called in this app
+pub(crate) fn report(capabilities: Capabilities, on_degraded: impl FnOnce(DegradationReport)) {
+    if capabilities.eval || REPORTED.with(|reported| reported.replace(true)) {
+        return
+    }
+    on_degraded(DegradationReport {
+        capabilities,
+        disabled: EVAL_FEATURES.to_vec(),
+    })
+}
@@ -0,0 +1,189 @@
+use core::ops::Range;
+use std::cell::Cell;
+use std::rc::Rc;
+
+use dioxus::prelude::*;
+
+use crate::cache::content_hash;
+use crate::index::DocumentIndex;
+use crate::{MdContext, MdProps, RenderState};
+
+/// how to split the rendering of long documents across several frames
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct ChunkConfig {
+    /// number of top level blocks rendered immediately
+    pub initial_blocks: usize,
+    /// number of top level blocks added by each subsequent render
+    pub blocks_per_chunk: usize,
+    /// show an element with the class `md-loading` after the rendered blocks
+    /// while the rest of the document is pending
+    pub placeholder: bool,
+}
+
+impl Default for ChunkConfig {
+    fn default() -> Self {
+        Self {
+            initial_blocks: 50,
+            blocks_per_chunk: 50,
+            placeholder: false,
+        }
+    }
+}
+
+/// the end of the chunk of about `n` top level blocks starting at the `start`-th one.
+/// Html tags are not split between chunks, and the blocks from the first footnote definition
+/// are rendered together, followed by the footnotes
+fn chunk_end(index: &DocumentIndex, start: usize, n: usize) -> usize {
+    let blocks = index.top_level_blocks();
+    let footnotes = blocks.iter()
+        .position(|block| index.footnote_definition(block).is_some())
+        .unwrap_or(blocks.len());
+    let mut end = match start < footnotes {
+        true => (start + n.max(1)).min(footnotes),
+        false => blocks.len(),
+    };
+    while end < blocks.len() && index.in_tag(blocks[end].start) {
+        end += 1
+    }
+    end
+}
+
+/// the source to render for the top level blocks `chunk` of `src`.
+///
+/// The blocks before the chunk are blanked, so that the offsets stay the ones of the index.
+/// The blocks after it are left out, but the link reference definitions between the blocks are kept,
+/// so that the references of the chunk resolve like in the whole document
+fn chunk_source(src: &str, index: &DocumentIndex, chunk: Range<usize>) -> String {
+    let mut source = String::with_capacity(src.len());
+    // the end of the source already written
+    let mut written = 0;
+    for (i, block) in index.top_level_blocks().iter().enumerate() {
+        source.push_str(&src[written..block.start]);
+        written = block.end;
+        let block = &src[block.clone()];
+        if chunk.contains(&i) {
+            source.push_str(block)
+        }
+        else if i < chunk.start {
+            source.extend(block.bytes().map(|b| if b == b'\n' { '\n' } else { ' ' }))
+        }
+    }
+    source.push_str(&src[written..]);
+    source
+}
+
+impl<'a> MdContext<'a> {
+    /// render the first blocks of the document, and the next chunks in the next frames
+    pub(crate) fn render_chunked(self, config: ChunkConfig) -> Element<'a> {
+        let end = chunk_end(self.index(), 0, config.initial_blocks);
+        let content = self.render_chunk(self.src, content_hash(self.src), 0..end);
+        self.click_root(content)
+    }
+
+    /// render the top level blocks `chunk` of `src`, the whole rendered source,
+    /// followed by the component rendering the next chunk
+    fn render_chunk(self, src: &'a str, version: u64, chunk: Range<usize>) -> Element<'a> {
+        let index = self.index();
+        let chunk_src = &*self.cx.bump().alloc_str(&chunk_source(src, index, chunk.clone()));
+        let content = MdContext { src: chunk_src, ..self }.document_view(chunk_src);
+
+        let blocks = index.top_level_blocks().len();
+        let config = self.cx.props.chunked_render.unwrap_or_default();
+        let next = (chunk.end < blocks).then(|| chunk.end..chunk_end(index, chunk.end, config.blocks_per_chunk));
+        self.cx.render(rsx!{
+            content,
+            next.map(|next| rsx!{
+                MarkdownChunk {
+                    // the chunks of another source start over
+                    key: "{version}",
+                    md: self.cx.props,
+                    parent: self.state,
+                    src: src,
+                    version: version,
+                    blocks: next,
+                }
+            })
+        })
+    }
+}
+
+#[derive(Props)]
+pub(crate) struct ChunkProps<'a> {
+    md: &'a MdProps<'a>,
+    /// the state of the previous chunk, sharing the index of the document
+    parent: &'a RenderState,
+    /// the whole source, as rendered
+    src: &'a str,
+    /// the hash of the source
+    version: u64,
+    blocks: Range<usize>,
+}
+
+/// the top level blocks `blocks` of a document rendered with `chunked_render`,
+/// rendered in the frame after the previous chunk was displayed.
+/// Each chunk renders the next one, so that the chunks already rendered are not rendered again
+#[allow(non_snake_case)]
+pub(crate) fn MarkdownChunk<'a>(cx: Scope<'a, ChunkProps<'a>>) -> Element<'a> {
+    let ready = cx.use_hook(|| Rc::new(Cell::new(false)));
+    let state = cx.use_hook(RenderState::default);
+    if !ready.get() {
+        let ready = ready.clone();
+        let update = cx.schedule_update();
+        cx.spawn(async move {
+            ready.set(true);
+            update()
+        });
+        let placeholder = cx.props.md.chunked_render.is_some_and(|c| c.placeholder);
+        return render!{
+            if placeholder { rsx!{ div { class: "md-loading" } } }
+        }
+    }
+
+    let ChunkProps { md, parent, src, version, blocks } = cx.props;
+    let start = parent.index.get()
+        .and_then(|index| index.top_level_blocks().get(blocks.start))
+        .map_or(0, |block| block.start);
+    state.reset_chunk(parent, start);
+    state.links.unmount_unused.set(md.unmount_unused);
+    let md = cx.bump().alloc(Scoped { scope: cx.scope, props: *md });
+    let context = MdContext::new(md, state, src);
+    context.state.loads.start_render();
+    let view = context.render_chunk(src, *version, blocks.clone());
+    context.state.loads.finish_render(cx);
+    view
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pulldown_cmark_wikilink::Options;
+
+    fn index(src: &str) -> DocumentIndex {
+        DocumentIndex::new(src, Options::all(), false)
+    }
+
+    #[test]
+    fn blocks_before_the_chunk_are_blanked() {
+        let src = "# One\n\ntwo [a]\n\nthree\n\n[a]: /a\n";
+        let chunk = chunk_source(src, &index(src), 1..2);
+        assert_eq!(chunk.find("two"), src.find("two"));
+        assert!(!chunk.contains("One") && !chunk.contains("three"), "{chunk:?}");
+        assert!(chunk.ends_with("[a]: /a\n"), "{chunk:?}");
+    }
+
+    #[test]
+    fn tags_are_not_split() {
+        let src = "<Note>\n\none\n\ntwo\n\n</Note>\n\nthree\n";
+        let index = index(src);
+        let end = chunk_end(&index, 0, 1);
+        assert_eq!(index.top_level_blocks()[end].start, src.find("three").unwrap());
+    }
+
+    #[test]
+    fn footnotes_are_rendered_together() {
+        let src = "a[^a]\n\nb\n\n[^a]: A\n\nc\n\nd\n";
+        let index = index(src);
+        assert_eq!(chunk_end(&index, 0, 5), 2);
+        assert_eq!(chunk_end(&index, 2, 1), index.top_level_blocks().len());
+    }
+}
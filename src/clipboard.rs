@@ -0,0 +1,43 @@
+use std::rc::Rc;
+
+use dioxus::html::prelude::EvalProvider;
+
+/// somewhere to copy text to.
+///
+/// On web, the default implementation uses `navigator.clipboard`.
+/// Implement it to provide clipboard access on other renderers.
+pub trait ClipboardSink {
+    fn copy(&self, text: &str);
+}
+
+/// copies to `navigator.clipboard` by evaluating javascript
+pub(crate) struct EvalClipboard(pub Rc<dyn EvalProvider>);
+
+impl ClipboardSink for EvalClipboard {
+    fn copy(&self, text: &str) {
+        let script = format!("navigator.clipboard.writeText({})", js_string(text));
+        // copying is best effort, there is nothing to do if it fails
+        let _ = self.0.new_evaluator(script);
+    }
+}
+
+/// `s` as a javascript string literal
+pub(crate) fn js_string(s: &str) -> String {
+    let mut literal = String::with_capacity(s.len() + 2);
+    literal.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => literal.push_str("\\\""),
+            '\\' => literal.push_str("\\\\"),
+            '\n' => literal.push_str("\\n"),
+            '\r' => literal.push_str("\\r"),
+            '<' => literal.push_str("\\u003c"),
+            c if (c as u32) < 0x20 || c == '\u{2028}' || c == '\u{2029}' => {
+                literal.push_str(&format!("\\u{:04x}", c as u32))
+            }
+            c => literal.push(c),
+        }
+    }
+    literal.push('"');
+    literal
+}
@@ -0,0 +1,137 @@
+use core::ops::Range;
+
+use pulldown_cmark_wikilink::{Event, Tag, ParserOffsetIter, Options, CodeBlockKind};
+
+use crate::rewrite::{Rewriter, Shifts};
+use crate::tree::code_language;
+
+/// the html comment replacing each code block in the source given to the renderer,
+/// so that code blocks are rendered by this crate instead of being highlighted by the renderer
+pub(crate) const PLACEHOLDER: &str = "<!--md-code-->";
+
+/// a code block of the source, replaced by a placeholder
+pub(crate) struct CodeBlock {
+    /// the range of the placeholder in the rewritten source,
+    /// which is the range of the code block once the rewrite is undone
+    pub placeholder: Range<usize>,
+    pub language: Option<String>,
+    /// whether it is fenced rather than indented
+    pub fenced: bool,
+    /// the code, as written in the source
+    pub code: String,
+}
+
+/// whether `html` is the placeholder of a code block
+pub(crate) fn is_placeholder(html: &str) -> bool {
+    html.trim_end() == PLACEHOLDER
+}
+
+/// replace the code blocks of `src` with placeholders.
+///
+/// Returns the code blocks, in order, and the new source with where its length changed,
+/// `None` if there is no code block
+pub(crate) fn rewrite_code_blocks(src: &str, options: Options, wikilinks: bool) -> (Vec<CodeBlock>, Option<(String, Shifts)>) {
+    // most documents can be skipped without parsing them
+    if !src.contains("```") && !src.contains("~~~") && !src.contains("    ") && !src.contains('\t') {
+        return (Vec::new(), None)
+    }
+
+    let mut blocks = Vec::new();
+    let mut rewriter = Rewriter::default();
+    // the end of the source already written
+    let mut kept = 0;
+    let mut current: Option<(CodeBlock, Range<usize>)> = None;
+
+    for (event, range) in ParserOffsetIter::new_ext(src, options, wikilinks) {
+        match event {
+            Event::Start(Tag::CodeBlock(kind)) => {
+                let block = CodeBlock {
+                    placeholder: 0..0,
+                    language: code_language(&kind),
+                    fenced: matches!(kind, CodeBlockKind::Fenced(_)),
+                    code: String::new(),
+                };
+                current = Some((block, range))
+            }
+            Event::Text(text) => {
+                if let Some((block, _)) = &mut current {
+                    block.code.push_str(&text)
+                }
+            }
+            Event::End(Tag::CodeBlock(_)) => {
+                let Some((mut block, range)) = current.take() else { continue };
+                // the indentation of an indented block is not part of its range,
+                // but would make the placeholder an indented code block too
+                let start = match block.fenced {
+                    true => range.start,
+                    false => indentation_start(src, range.start),
+                };
+                let placeholder = match src[..range.end].ends_with('\n') {
+                    true => format!("{PLACEHOLDER}\n"),
+                    false => PLACEHOLDER.to_string(),
+                };
+                rewriter.keep(&src[kept..start]);
+                let placeholder_start = rewriter.position();
+                rewriter.replace(range.end - start, &placeholder);
+                block.placeholder = placeholder_start..rewriter.position();
+                kept = range.end;
+                blocks.push(block);
+            }
+            _ => (),
+        }
+    }
+
+    rewriter.keep(&src[kept..]);
+    (blocks, rewriter.finish())
+}
+
+/// the start of the 4 columns of indentation before the indented code at `start`
+fn indentation_start(src: &str, start: usize) -> usize {
+    let before = &src.as_bytes()[..start];
+    match before.last() {
+        Some(b'\t') => start - 1,
+        _ => start - before.iter().rev().take(4).take_while(|c| **c == b' ').count(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rewrite(src: &str) -> (Vec<CodeBlock>, String) {
+        let (blocks, rewritten) = rewrite_code_blocks(src, Options::all(), false);
+        (blocks, rewritten.map_or(src.to_string(), |(rewritten, _)| rewritten))
+    }
+
+    #[test]
+    fn fenced_blocks_are_replaced() {
+        let (blocks, rewritten) = rewrite("text\n\n```rust\nfn main() {}\n```\nafter");
+        assert_eq!(rewritten, "text\n\n<!--md-code-->\nafter");
+        assert_eq!(blocks[0].language.as_deref(), Some("rust"));
+        assert_eq!(blocks[0].code, "fn main() {}\n");
+        assert_eq!(&rewritten[blocks[0].placeholder.clone()], PLACEHOLDER);
+    }
+
+    #[test]
+    fn indented_blocks_lose_their_indentation() {
+        let (blocks, rewritten) = rewrite("- item\n\n      code\n      more\n\nafter");
+        assert_eq!(rewritten, "- item\n\n  <!--md-code-->\n\nafter");
+        assert!(!blocks[0].fenced);
+        assert_eq!(blocks[0].code, "code\nmore\n");
+    }
+
+    #[test]
+    fn placeholders_map_to_the_blocks() {
+        let src = "> ```\n> quoted\n> ```\n\n    indented\n";
+        let (blocks, rewritten) = rewrite_code_blocks(src, Options::all(), false);
+        let (_, shifts) = rewritten.unwrap();
+        let original = |range: &Range<usize>| crate::rewrite::unshift(&shifts, range.start)..crate::rewrite::unshift(&shifts, range.end);
+        assert_eq!(&src[original(&blocks[0].placeholder)], "```\n> quoted\n> ```");
+        assert_eq!(&src[original(&blocks[1].placeholder)], "    indented\n");
+    }
+
+    #[test]
+    fn documents_without_code_are_not_rewritten() {
+        assert!(rewrite_code_blocks("a `span`", Options::all(), false).1.is_none());
+    }
+}
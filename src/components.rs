@@ -0,0 +1,613 @@
+use std::borrow::Cow;
+use core::ops::Range;
+use std::cell::RefCell;
+use std::any::Any;
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::Display;
+use std::future::Future;
+use std::rc::Rc;
+use std::str::FromStr;
+use std::sync::Arc;
+
+use dioxus::prelude::*;
+#[cfg(feature="json-props")]
+use serde::de::DeserializeOwned;
+
+use crate::{MdComponentProps, MdRenderer, ComponentCreationError, OutlineHeading};
+use crate::builtins;
+use crate::loading::LoadFn;
+
+pub(crate) type ComponentFn<'a> = dyn Fn(&'a ScopeState, MdComponentProps<'a>, MdRenderer<'a>) -> Result<Element<'a>, ComponentCreationError>;
+
+pub(crate) type FallbackFn<'a> = dyn Fn(&'a ScopeState, &str, MdComponentProps<'a>) -> Result<Element<'a>, ComponentCreationError>;
+
+pub(crate) type PlaceholderFn<'a> = dyn Fn(&'a ScopeState) -> Element<'a>;
+
+pub(crate) struct Component<'a> {
+    /// the name it was registered with
+    pub name: Cow<'static, str>,
+    pub render: Rc<ComponentFn<'a>>,
+    pub meta: Option<Rc<ComponentMeta>>,
+}
+
+impl Clone for Component<'_> {
+    fn clone(&self) -> Self {
+        Self {
+            name: self.name.clone(),
+            render: self.render.clone(),
+            meta: self.meta.clone(),
+        }
+    }
+}
+
+/// component store.
+/// It is called when therer is a `<CustomComponent>` inside the markdown source.
+/// It is basically a hashmap but more efficient for a small number of items
+pub struct CustomComponents<'a> {
+    pub(crate) components: BTreeMap<Cow<'static, str>, Component<'a>>,
+    /// called for unregistered components
+    pub(crate) fallback: Option<Rc<FallbackFn<'a>>>,
+    /// displayed while async components load
+    pub(crate) async_placeholder: Option<Rc<PlaceholderFn<'a>>>,
+    /// whether names are compared ignoring case, with lowercase keys
+    case_insensitive: bool,
+}
+
+impl Default for CustomComponents<'_> {
+    fn default() -> Self {
+        Self {
+            components: Default::default(),
+            fallback: None,
+            async_placeholder: None,
+            case_insensitive: false,
+        }
+    }
+}
+
+impl<'a> CustomComponents<'a> 
+{
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// `self` with the components shipped with this crate:
+    /// - `<Toc/>`: a table of contents, nested lists of links to the headings of the document.
+    ///   The `min` and `max` attributes restrict the levels of the listed headings
+    pub fn with_builtins(self) -> Self {
+        self.with("Toc", builtins::toc)
+    }
+
+    /// compare component names ignoring case, so that `<counter/>` renders `Counter`.
+    /// By default, names must match exactly.
+    /// The components of a [`ComponentRegistry`] are found ignoring case too.
+    ///
+    /// Of the registered components whose names only differ by case, one is kept,
+    /// and the names of the others are returned, like the conflicts of [`merged`](Self::merged)
+    pub fn case_insensitive(mut self, enabled: bool) -> (Self, Vec<Cow<'static, str>>) {
+        self.case_insensitive = enabled;
+        let components = std::mem::take(&mut self.components);
+        let conflicts = components.into_values()
+            .filter_map(|component| self.insert(component))
+            .collect();
+        (self, conflicts)
+    }
+
+    /// whether names are compared ignoring case, see [`case_insensitive`](Self::case_insensitive)
+    pub(crate) fn ignores_case(&self) -> bool {
+        self.case_insensitive
+    }
+
+    /// the key of the component named `name`
+    fn key<'n>(&self, name: &'n str) -> Cow<'n, str> {
+        match self.case_insensitive && name.chars().any(char::is_uppercase) {
+            true => name.to_lowercase().into(),
+            false => name.into(),
+        }
+    }
+
+    /// insert `component`, replacing the one with the same key.
+    /// Returns the name of the replaced component if it is another name,
+    /// which only differs by case
+    fn insert(&mut self, component: Component<'a>) -> Option<Cow<'static, str>> {
+        let name = component.name.clone();
+        let key = match self.key(&name) {
+            Cow::Borrowed(_) => name.clone(),
+            Cow::Owned(key) => key.into(),
+        };
+        let replaced = self.components.insert(key, component)?;
+        (replaced.name != name).then_some(replaced.name)
+    }
+
+    /// register a new component.
+    /// The function `component` takes a context and props of type `MdComponentProps`
+    /// and returns html.
+    /// The name can be a literal or a `String` known at runtime
+    ///
+    /// In case insensitive mode, it replaces a component whose name only differs by case
+    pub fn register<F>(&mut self, name: impl Into<Cow<'static, str>>, component: F)
+        where F: Fn(&'a ScopeState, MdComponentProps<'a>) -> Result<Element<'a>, ComponentCreationError> + 'static
+    {
+        self.insert(Component {
+            name: name.into(),
+            render: Rc::new(move |cx, props, _: MdRenderer<'a>| component(cx, props)),
+            meta: None,
+        });
+    }
+
+    /// register a new component that renders markdown itself, like the `Markdown` calling it:
+    /// with the same components, link renderer and other options. See [`MdRenderer`]
+    pub fn register_with_renderer<F>(&mut self, name: impl Into<Cow<'static, str>>, component: F)
+        where F: Fn(&'a ScopeState, MdComponentProps<'a>, MdRenderer<'a>) -> Result<Element<'a>, ComponentCreationError> + 'static
+    {
+        self.insert(Component {
+            name: name.into(),
+            render: Rc::new(component),
+            meta: None,
+        });
+    }
+
+    /// `self` with a new component, to register several in a single expression
+    pub fn with<F>(mut self, name: impl Into<Cow<'static, str>>, component: F) -> Self
+        where F: Fn(&'a ScopeState, MdComponentProps<'a>) -> Result<Element<'a>, ComponentCreationError> + 'static
+    {
+        self.register(name, component);
+        self
+    }
+
+    /// register a new component, described by `meta`.
+    /// The attributes of the component are checked against `meta` before calling it:
+    /// unknown attributes and missing required ones are reported as errors.
+    pub fn register_with_meta<F>(&mut self, name: impl Into<Cow<'static, str>>, meta: ComponentMeta, component: F)
+        where F: Fn(&'a ScopeState, MdComponentProps<'a>) -> Result<Element<'a>, ComponentCreationError> + 'static
+    {
+        self.insert(Component {
+            name: name.into(),
+            render: Rc::new(move |cx, props, _: MdRenderer<'a>| component(cx, props)),
+            meta: Some(Rc::new(meta)),
+        });
+    }
+
+    /// register a component that loads data before rendering, like `<GithubCard repo="..."/>`.
+    /// `load` is called with the attributes of the tag, and the component is rendered by `render`
+    /// with the value of the future once it is ready.
+    /// Meanwhile, the async placeholder is displayed.
+    ///
+    /// A tag is loaded once, as long as it stays in the document with the same attributes.
+    /// The load of a tag removed from the source is cancelled.
+    pub fn register_async<T, L, Fut, R>(&mut self, name: impl Into<Cow<'static, str>>, load: L, render: R)
+        where T: 'static,
+              L: Fn(BTreeMap<String, String>) -> Fut + 'static,
+              Fut: Future<Output=Result<T, ComponentCreationError>> + 'static,
+              R: Fn(&'a ScopeState, &T, MdComponentProps<'a>) -> Result<Element<'a>, ComponentCreationError> + 'static
+    {
+        let name = name.into();
+        let load: Rc<LoadFn> = Rc::new(move |attributes| {
+            let future = load(attributes);
+            Box::pin(async move {
+                future.await
+                    .map(|value| Rc::new(value) as Rc<dyn Any>)
+                    .map_err(|e| e.to_string())
+            })
+        });
+        let key = name.to_string();
+        self.insert(Component {
+            name,
+            render: Rc::new(move |cx, props, renderer: MdRenderer<'a>| {
+                match renderer.load(&key, &props.attributes, &load) {
+                    None => Ok(renderer.async_placeholder()),
+                    Some(Ok(value)) => {
+                        let value = value.downcast_ref().expect("loaded by this component");
+                        render(cx, value, props)
+                    }
+                    Some(Err(message)) => Err(message.into()),
+                }
+            }),
+            meta: None,
+        });
+    }
+
+    /// set the element displayed while async components load,
+    /// see [`register_async`](Self::register_async).
+    /// By default, it is an empty `span` with the `md-loading` class
+    pub fn set_async_placeholder<F>(&mut self, placeholder: F)
+        where F: Fn(&'a ScopeState) -> Element<'a> + 'static
+    {
+        self.async_placeholder = Some(Rc::new(placeholder));
+    }
+
+    /// set the component rendered for tags that look like components
+    /// (their name starts with an uppercase letter) but are not registered.
+    /// It is called with the name of the tag, for example to show a warning.
+    pub fn set_fallback<F>(&mut self, fallback: F)
+        where F: Fn(&'a ScopeState, &str, MdComponentProps<'a>) -> Result<Element<'a>, ComponentCreationError> + 'static
+    {
+        self.fallback = Some(Rc::new(fallback));
+    }
+
+    /// the component registered with this name
+    pub(crate) fn get(&self, name: &str) -> Option<&Component<'a>> {
+        self.components.get(self.key(name).as_ref())
+    }
+
+    /// whether a component is registered with this name
+    pub fn contains(&self, name: &str) -> bool {
+        self.get(name).is_some()
+    }
+
+    /// whether tags named `name` are rendered as a component:
+    /// a registered one, or the fallback
+    pub fn handles(&self, name: &str) -> bool {
+        self.contains(name)
+            || self.fallback.is_some() && name.starts_with(|c: char| c.is_ascii_uppercase())
+    }
+
+    /// the names of the registered components, in alphabetical order
+    pub fn names(&self) -> impl Iterator<Item=&str> {
+        self.components.values().map(|c| c.name.as_ref())
+    }
+
+    /// the description of the components registered with one
+    pub fn metadata(&self) -> impl Iterator<Item=(&str, &ComponentMeta)> {
+        self.components.values()
+            .filter_map(|c| Some((c.name.as_ref(), c.meta.as_deref()?)))
+    }
+
+    /// a new store with the components of both `self` and `overrides`.
+    /// When both define a component, the one of `overrides` is kept,
+    /// and its name is returned in the list of conflicts.
+    /// The fallback of `overrides` is kept too, if it has one,
+    /// and names are compared ignoring case if `self` does.
+    pub fn merged(&self, overrides: &CustomComponents<'a>) -> (CustomComponents<'a>, Vec<Cow<'static, str>>) {
+        let conflicts = overrides.components.values()
+            .filter(|c| self.contains(&c.name))
+            .map(|c| c.name.clone())
+            .collect();
+
+        let mut merged = Self {
+            components: Default::default(),
+            fallback: overrides.fallback.clone().or_else(|| self.fallback.clone()),
+            async_placeholder: overrides.async_placeholder.clone().or_else(|| self.async_placeholder.clone()),
+            case_insensitive: self.case_insensitive,
+        };
+        for component in self.components.values() {
+            merged.insert(component.clone());
+        }
+        for component in overrides.components.values() {
+            // overriding is not a conflict of case
+            let key = merged.key(&component.name).into_owned();
+            merged.components.remove(key.as_str());
+            merged.insert(component.clone());
+        }
+
+        (merged, conflicts)
+    }
+}
+
+type RegistryFn = dyn for<'b> Fn(&'b ScopeState, MdComponentProps<'b>) -> Result<Element<'b>, ComponentCreationError>;
+
+/// components shared by several [`Markdown`](crate::Markdown) components,
+/// that can be registered after they are rendered,
+/// for example by plugins loaded asynchronously.
+///
+/// The `Markdown` components using a registry render again when a component is registered.
+/// Clones refer to the same registry.
+#[derive(Clone, Default)]
+pub struct ComponentRegistry(Rc<RefCell<Registry>>);
+
+#[derive(Default)]
+struct Registry {
+    components: BTreeMap<Cow<'static, str>, Rc<RegistryFn>>,
+    /// the scopes to render again when a component is registered
+    subscribers: Vec<(ScopeId, Arc<dyn Fn(ScopeId) + Send + Sync>)>,
+}
+
+impl ComponentRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// register a new component, and render again the `Markdown` components using the registry
+    pub fn register<F>(&self, name: impl Into<Cow<'static, str>>, component: F)
+        where F: for<'b> Fn(&'b ScopeState, MdComponentProps<'b>) -> Result<Element<'b>, ComponentCreationError> + 'static
+    {
+        let mut registry = self.0.borrow_mut();
+        registry.components.insert(name.into(), Rc::new(component));
+        for (scope, update) in &registry.subscribers {
+            update(*scope)
+        }
+    }
+
+    /// whether a component is registered with this name
+    pub fn contains(&self, name: &str) -> bool {
+        self.get(name, false).is_some()
+    }
+
+    /// the names of the registered components, in alphabetical order
+    pub fn names(&self) -> Vec<String> {
+        self.0.borrow().components.keys().map(|name| name.to_string()).collect()
+    }
+
+    /// the component registered with this name.
+    /// With `ignore_case`, an exact match is preferred to one differing by case
+    pub(crate) fn get(&self, name: &str, ignore_case: bool) -> Option<Rc<RegistryFn>> {
+        let registry = self.0.borrow();
+        if let Some(component) = registry.components.get(name) {
+            return Some(component.clone())
+        }
+        if !ignore_case {
+            return None
+        }
+        let name = name.to_lowercase();
+        registry.components.iter()
+            .find(|(registered, _)| registered.to_lowercase() == name)
+            .map(|(_, component)| component.clone())
+    }
+
+    /// render `cx` again when a component is registered, until the subscription is dropped
+    pub(crate) fn subscribe(&self, cx: &ScopeState) -> RegistrySubscription {
+        self.0.borrow_mut().subscribers.push((cx.scope_id(), cx.schedule_update_any()));
+        RegistrySubscription {
+            registry: self.clone(),
+            scope: cx.scope_id(),
+        }
+    }
+
+    pub(crate) fn same(&self, other: &Self) -> bool {
+        Rc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+/// provide `registry` to the `Markdown` components below this one,
+/// unless they have a `registry` prop. It is created with `init` on the first render
+pub fn use_markdown_components<'a>(cx: &'a ScopeState, init: impl FnOnce() -> ComponentRegistry) -> &'a ComponentRegistry {
+    cx.use_hook(|| cx.provide_context(init()))
+}
+
+/// a scope rendered again when a component is registered
+pub(crate) struct RegistrySubscription {
+    pub registry: ComponentRegistry,
+    scope: ScopeId,
+}
+
+impl Drop for RegistrySubscription {
+    fn drop(&mut self) {
+        self.registry.0.borrow_mut()
+            .subscribers
+            .retain(|(scope, _)| *scope != self.scope)
+    }
+}
+
+/// description of a custom component, for documentation and validation
+#[derive(Clone, PartialEq, Debug, Default)]
+pub struct ComponentMeta {
+    pub doc: String,
+    pub attributes: Vec<AttributeMeta>,
+    /// whether the component can be used with an opening and a closing tag
+    pub accepts_children: bool,
+}
+
+/// description of an attribute of a custom component
+#[derive(Clone, PartialEq, Debug, Default)]
+pub struct AttributeMeta {
+    pub name: String,
+    /// the kind of value expected, like `"integer"` or `"url"`
+    pub type_hint: String,
+    pub required: bool,
+    /// the value used when the attribute is absent, if any
+    pub default: Option<String>,
+    pub doc: String,
+}
+
+impl ComponentMeta {
+    /// check the attributes of a use of the component named `name`
+    pub(crate) fn validate<V>(&self, name: &str, props: &rust_web_markdown::MdComponentProps<V>) -> Result<(), ComponentCreationError> {
+        if let Some(unknown) = props.attributes.keys()
+            .find(|a| !self.attributes.iter().any(|m| &m.name == *a)) {
+            return Err(format!("<{name}> has no attribute `{unknown}`").into())
+        }
+
+        if let Some(missing) = self.attributes.iter()
+            .find(|m| m.required && !props.attributes.contains_key(&m.name)) {
+            return Err(format!("<{name}> requires the attribute `{}`", missing.name).into())
+        }
+
+        Ok(())
+    }
+}
+
+/// helpers to read the props of custom components
+pub trait ComponentPropsExt {
+    /// whether the boolean attribute `name` is set:
+    /// written without a value like in `<Alert dismissible/>`, or with any value but `"false"`
+    fn get_flag(&self, name: &str) -> bool;
+
+    /// the `(name, value)` pairs of the attributes, in alphabetical order
+    fn attributes(&self) -> Box<dyn Iterator<Item=(&str, &str)> + '_>;
+
+    /// a reader of the attributes remembering which ones were read,
+    /// to find the ones the component ignores
+    fn reader(&self) -> AttributeReader<'_>;
+
+    /// the byte range in `src` of the markdown between the opening and the closing tag,
+    /// before it was rendered to `children`. `None` for self-closing tags.
+    /// Only available while the component is called by [`Markdown`](crate::Markdown)
+    fn children_source(&self) -> Option<Range<usize>>;
+
+    /// the markdown between the opening and the closing tag,
+    /// given the `src` of the [`Markdown`](crate::Markdown) component
+    fn children_text<'s>(&self, src: &'s str) -> Option<&'s str> {
+        src.get(self.children_source()?)
+    }
+
+    /// parse the attribute `name` as json, like `data='[1, 2, 3]'`
+    #[cfg(feature="json-props")]
+    fn get_json<T: DeserializeOwned>(&self, name: &str) -> Result<T, ComponentCreationError>;
+
+    /// parse the attribute `name` as json, if it is present
+    #[cfg(feature="json-props")]
+    fn get_json_optional<T: DeserializeOwned>(&self, name: &str) -> Result<Option<T>, ComponentCreationError>;
+}
+
+/// parse the json `value` of the attribute `name`
+#[cfg(feature="json-props")]
+fn parse_json<T: DeserializeOwned>(name: &str, value: Option<&str>) -> Result<Option<T>, ComponentCreationError> {
+    value
+        .map(|value| serde_json::from_str(value)
+            .map_err(|e| format!("invalid json for `{name}`: {e}").into()))
+        .transpose()
+}
+
+/// what a component called by [`Markdown`](crate::Markdown) can know about the document
+pub(crate) struct ComponentCall {
+    pub children_source: Option<Range<usize>>,
+    pub outline: Rc<Vec<OutlineHeading>>,
+    /// the label of `<Toc/>`, see [`A11yConfig`](crate::A11yConfig)
+    pub toc_label: Option<String>,
+}
+
+thread_local! {
+    // set while a component is called, there is a single renderer per thread
+    static CALL: RefCell<Option<Rc<ComponentCall>>> = RefCell::new(None);
+}
+
+/// puts the previous call back when dropped, even if the component panicked
+struct CallGuard(Option<Rc<ComponentCall>>);
+
+impl Drop for CallGuard {
+    fn drop(&mut self) {
+        let previous = self.0.take();
+        CALL.with(|c| *c.borrow_mut() = previous);
+    }
+}
+
+/// call `f`, rendering a component, with `call` describing where it is
+pub(crate) fn with_call<T>(call: ComponentCall, f: impl FnOnce() -> T) -> T {
+    let _guard = CallGuard(CALL.with(|c| c.replace(Some(Rc::new(call)))));
+    f()
+}
+
+/// the description of the component being called, if any
+pub(crate) fn current_call() -> Option<Rc<ComponentCall>> {
+    CALL.with(|c| c.borrow().clone())
+}
+
+impl<V> ComponentPropsExt for rust_web_markdown::MdComponentProps<V> {
+    fn get_flag(&self, name: &str) -> bool {
+        self.attributes.get(name).is_some_and(|value| value != "false")
+    }
+
+    fn attributes(&self) -> Box<dyn Iterator<Item=(&str, &str)> + '_> {
+        Box::new(self.attributes.iter().map(|(k, v)| (k.as_str(), v.as_str())))
+    }
+
+    fn reader(&self) -> AttributeReader<'_> {
+        AttributeReader {
+            attributes: &self.attributes,
+            read: Default::default(),
+        }
+    }
+
+    fn children_source(&self) -> Option<Range<usize>> {
+        current_call()?.children_source.clone()
+    }
+
+    #[cfg(feature="json-props")]
+    fn get_json<T: DeserializeOwned>(&self, name: &str) -> Result<T, ComponentCreationError> {
+        self.get_json_optional(name)?
+            .ok_or_else(|| format!("please provide the attribute `{name}`").into())
+    }
+
+    #[cfg(feature="json-props")]
+    fn get_json_optional<T: DeserializeOwned>(&self, name: &str) -> Result<Option<T>, ComponentCreationError> {
+        parse_json(name, self.attributes.get(name).map(String::as_str))
+    }
+}
+
+/// reads the attributes of a component, see [`ComponentPropsExt::reader`].
+/// Once the component has read the attributes it expects,
+/// [`unused_attributes`](Self::unused_attributes) lists the unexpected ones,
+/// typically typos to report.
+pub struct AttributeReader<'p> {
+    attributes: &'p BTreeMap<String, String>,
+    read: RefCell<BTreeSet<&'p str>>,
+}
+
+impl<'p> AttributeReader<'p> {
+    pub fn get(&self, name: &str) -> Option<&'p str> {
+        let (name, value) = self.attributes.get_key_value(name)?;
+        self.read.borrow_mut().insert(name);
+        Some(value)
+    }
+
+    pub fn get_flag(&self, name: &str) -> bool {
+        self.get(name).is_some_and(|value| value != "false")
+    }
+
+    pub fn get_parsed<T>(&self, name: &str) -> Result<T, ComponentCreationError>
+        where T: FromStr, T::Err: Display
+    {
+        self.get_parsed_optional(name)?
+            .ok_or_else(|| format!("please provide the attribute `{name}`").into())
+    }
+
+    pub fn get_parsed_optional<T>(&self, name: &str) -> Result<Option<T>, ComponentCreationError>
+        where T: FromStr, T::Err: Display
+    {
+        self.get(name)
+            .map(|value| value.parse().map_err(|e| format!("invalid value for `{name}`: {e}").into()))
+            .transpose()
+    }
+
+    #[cfg(feature="json-props")]
+    pub fn get_json<T: DeserializeOwned>(&self, name: &str) -> Result<T, ComponentCreationError> {
+        self.get_json_optional(name)?
+            .ok_or_else(|| format!("please provide the attribute `{name}`").into())
+    }
+
+    #[cfg(feature="json-props")]
+    pub fn get_json_optional<T: DeserializeOwned>(&self, name: &str) -> Result<Option<T>, ComponentCreationError> {
+        parse_json(name, self.get(name))
+    }
+
+    /// the names of the attributes not read yet, in alphabetical order
+    pub fn unused_attributes(&self) -> Vec<&'p str> {
+        let read = self.read.borrow();
+        self.attributes.keys()
+            .map(String::as_str)
+            .filter(|name| !read.contains(name))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn call(children: Range<usize>) -> ComponentCall {
+        ComponentCall {
+            children_source: Some(children),
+            outline: Default::default(),
+            toc_label: None,
+        }
+    }
+
+    #[test]
+    fn nested_calls_restore_the_outer_one() {
+        with_call(call(0..4), || {
+            with_call(call(1..2), || {
+                assert_eq!(current_call().unwrap().children_source, Some(1..2));
+            });
+            assert_eq!(current_call().unwrap().children_source, Some(0..4));
+        });
+        assert!(current_call().is_none());
+    }
+
+    #[test]
+    fn panicking_component_does_not_leave_its_call() {
+        let result = std::panic::catch_unwind(|| {
+            with_call(call(0..4), || panic!("broken component"))
+        });
+        assert!(result.is_err());
+        assert!(current_call().is_none());
+    }
+}
@@ -0,0 +1,130 @@
+use crate::rewrite::Rewriter;
+
+/// the tag of a definition list
+pub(crate) const LIST_TAG: &str = "md-dl";
+/// the tag of a term of a definition list
+pub(crate) const TERM_TAG: &str = "md-dt";
+/// the tag of a definition of a term
+pub(crate) const DETAILS_TAG: &str = "md-dd";
+
+/// rewrite the definition lists of `src` as tags:
+///
+/// ```text
+/// Term
+/// : definition
+///
+///   continued in an indented paragraph
+/// : another definition
+/// ```
+///
+/// becomes a `<md-dl>` containing a `<md-dt>` and two `<md-dd>`,
+/// whose content is parsed as markdown.
+///
+/// Returns the new source, and where the length of the source changed:
+/// offsets in the new source with the total change of length before them.
+/// `None` if there is no definition list
+pub(crate) fn rewrite_definition_lists(src: &str) -> Option<(String, Vec<(usize, isize)>)> {
+    let lines: Vec<&str> = src.split_inclusive('\n').collect();
+    let mut rewriter = Rewriter::default();
+    let mut in_list = false;
+    // the fence of the code block we are in
+    let mut fence: Option<&str> = None;
+    let starts_item = |i: usize| is_term(lines[i]) && lines.get(i + 1).is_some_and(|l| marker_len(l).is_some());
+
+    let mut i = 0;
+    while i < lines.len() {
+        let line = lines[i];
+        let trimmed = line.trim();
+
+        if !in_list {
+            if let Some(f) = fence {
+                if trimmed.starts_with(f) {
+                    fence = None
+                }
+            } else if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+                fence = Some(&trimmed[..3]);
+            } else if starts_item(i) && (i == 0 || is_blank(lines[i - 1])) {
+                rewriter.insert(&format!("\n<{LIST_TAG}>\n\n"));
+                in_list = true;
+                continue
+            }
+            rewriter.keep(line);
+            i += 1;
+            continue
+        }
+
+        if starts_item(i) {
+            rewriter.insert(&format!("<{TERM_TAG}>\n\n"));
+            rewriter.keep(line);
+            rewriter.insert(&format!("\n</{TERM_TAG}>\n\n"));
+            i += 1;
+        } else if let Some(marker) = marker_len(line) {
+            rewriter.insert(&format!("<{DETAILS_TAG}>\n\n"));
+            rewriter.replace(marker, "");
+            rewriter.keep(&line[marker..]);
+            i += 1;
+            while i < lines.len() {
+                let line = lines[i];
+                if is_blank(line) {
+                    // a blank line is part of the definition if an indented paragraph follows
+                    let next = lines[i..].iter().find(|l| !is_blank(l));
+                    if !next.is_some_and(|l| indent_len(l) > 0) {
+                        break
+                    }
+                    rewriter.keep(line);
+                } else if indent_len(line) > 0 {
+                    let indent = indent_len(line);
+                    rewriter.replace(indent, "");
+                    rewriter.keep(&line[indent..]);
+                } else if !is_blank(lines[i - 1]) && marker_len(line).is_none() && !starts_item(i) {
+                    // lazy continuation of the paragraph
+                    rewriter.keep(line);
+                } else {
+                    break
+                }
+                i += 1;
+            }
+            rewriter.insert(&format!("\n</{DETAILS_TAG}>\n\n"));
+        } else if is_blank(line) && lines[i..].iter().position(|l| !is_blank(l)).is_some_and(|n| starts_item(i + n)) {
+            rewriter.keep(line);
+            i += 1;
+        } else {
+            rewriter.insert(&format!("</{LIST_TAG}>\n\n"));
+            in_list = false;
+        }
+    }
+
+    if in_list {
+        rewriter.insert(&format!("\n</{LIST_TAG}>\n"));
+    }
+    rewriter.finish()
+}
+
+fn is_blank(line: &str) -> bool {
+    line.trim().is_empty()
+}
+
+fn is_term(line: &str) -> bool {
+    !is_blank(line) && indent_len(line) == 0 && marker_len(line).is_none()
+}
+
+/// the length of the `: ` starting a definition, up to its content
+fn marker_len(line: &str) -> Option<usize> {
+    let spaces = line.len() - line.trim_start_matches(' ').len();
+    if spaces > 3 {
+        return None
+    }
+    let rest = line[spaces..].strip_prefix(':')?;
+    let content = rest.trim_start_matches([' ', '\t']);
+    // `:` alone would make the definition start on the next line
+    (content.len() < rest.len()).then(|| line.len() - content.len())
+}
+
+/// the indentation of a line continuing a definition:
+/// up to 4 spaces, or a tab
+fn indent_len(line: &str) -> usize {
+    if line.starts_with('\t') {
+        return 1
+    }
+    line.bytes().take(4).take_while(|b| *b == b' ').count()
+}
@@ -0,0 +1,112 @@
+use std::cell::{Cell, RefCell};
+use std::rc::Rc;
+
+use dioxus::prelude::*;
+use dioxus::html::prelude::EvalProvider;
+
+use crate::clipboard::js_string;
+use crate::visibility::instance_id;
+
+/// a click received by the root, resolved to the click targets it hit
+pub(crate) struct ResolvedClick {
+    /// the indices of the click targets under the pointer, innermost first
+    pub targets: Vec<usize>,
+    pub event: MouseEvent,
+}
+
+/// the clicks on the elements of one `Markdown` instance, received by a single listener on its root.
+/// The elements have a `data-md-target` attribute with the index of their click target,
+/// which javascript records from the target of each click since the event does not tell it
+pub(crate) struct DelegatedClicks {
+    /// the value of the `data-md-root` attribute of the root
+    pub id: String,
+    /// whether the targets of the clicks are recorded, see `listen`
+    listening: Cell<bool>,
+    /// the number of renders so far, the targets of a click are only valid until the next one
+    generation: Cell<usize>,
+    /// the clicks resolved since the last render, with the render they happened in
+    resolved: Rc<RefCell<Vec<(usize, ResolvedClick)>>>,
+}
+
+impl Default for DelegatedClicks {
+    fn default() -> Self {
+        Self {
+            id: instance_id(),
+            listening: Cell::new(false),
+            generation: Cell::new(0),
+            resolved: Default::default(),
+        }
+    }
+}
+
+impl DelegatedClicks {
+    /// record the click targets of each click in the root, from the element the click targeted,
+    /// before the listener of the root receives it.
+    /// Clicks from the keyboard or from scripts are recorded like the others
+    pub fn listen(&self, eval: &Rc<dyn EvalProvider>) {
+        if self.listening.replace(true) {
+            return
+        }
+        let script = format!(
+            r#"const root = {root};
+            window.mdClicks ??= {{}};
+            document.addEventListener("click", event => {{
+                let targets = [];
+                for (let element = event.target; element instanceof Element; element = element.parentElement) {{
+                    const id = element.getAttribute("data-md-root");
+                    if (id === root) {{
+                        window.mdClicks[root] = targets;
+                        return;
+                    }}
+                    // the targets of a nested document are not ours
+                    if (id !== null) targets = [];
+                    const target = element.getAttribute("data-md-target");
+                    if (target !== null) targets.push(Number(target));
+                }}
+            }}, true);"#,
+            root = js_string(&self.id),
+        );
+        // without javascript, clicks are just not reported
+        let _ = eval.new_evaluator(script);
+    }
+
+    /// find the click targets of `event`, recorded by `listen`,
+    /// to report them in the next render with `next_render`
+    pub fn resolve(&self, cx: &ScopeState, eval: &Rc<dyn EvalProvider>, event: MouseEvent) {
+        let script = format!(
+            r#"const root = {root};
+            const targets = window.mdClicks?.[root] ?? [];
+            delete window.mdClicks?.[root];
+            return targets;"#,
+            root = js_string(&self.id),
+        );
+        // without javascript, the click is just not reported
+        let Ok(evaluator) = eval.new_evaluator(script) else {
+            return
+        };
+        let generation = self.generation.get();
+        let resolved = self.resolved.clone();
+        let update = cx.schedule_update();
+        cx.spawn(async move {
+            let Ok(targets) = evaluator.join().await else { return };
+            let targets: Vec<usize> = targets.as_array().into_iter().flatten()
+                .filter_map(|target| target.as_u64())
+                .map(|target| target as usize)
+                .collect();
+            if !targets.is_empty() {
+                resolved.borrow_mut().push((generation, ResolvedClick { targets, event }));
+                update()
+            }
+        });
+    }
+
+    /// the clicks resolved against the targets of the render that just ended,
+    /// the others are dropped since their targets changed
+    pub fn next_render(&self) -> Vec<ResolvedClick> {
+        let generation = self.generation.replace(self.generation.get() + 1);
+        self.resolved.take().into_iter()
+            .filter(|(g, _)| *g == generation)
+            .map(|(_, click)| click)
+            .collect()
+    }
+}
@@ -0,0 +1,59 @@
+/// guess the language of `code` with a few simple rules:
+/// shebangs, keywords typical of a language, and the density of braces.
+/// Returns `None` rather than a wrong guess when nothing matches
+pub fn detect_language(code: &str) -> Option<String> {
+    let first_line = code.lines().next().unwrap_or_default();
+    if let Some(interpreter) = first_line.strip_prefix("#!") {
+        let program = interpreter.split_whitespace()
+            .last()
+            .and_then(|path| path.rsplit('/').next())
+            .unwrap_or_default();
+        let language = match program {
+            p if p.starts_with("python") => "python",
+            "sh" | "bash" | "zsh" => "bash",
+            "node" => "javascript",
+            "ruby" => "ruby",
+            "perl" => "perl",
+            _ => return None,
+        };
+        return Some(language.into())
+    }
+
+    let has_line = |prefix: &str| code.lines().any(|line| line.trim_start().starts_with(prefix));
+
+    let language = if code.contains("fn main") || has_line("use std::") || code.contains("let mut ") {
+        "rust"
+    }
+    else if has_line("def ") || has_line("import ") && !code.contains(';') {
+        "python"
+    }
+    else if has_line("package ") && code.contains("func ") {
+        "go"
+    }
+    else if has_line("#include") {
+        "c"
+    }
+    else if has_line("function ") || has_line("const ") || code.contains("=>") {
+        "javascript"
+    }
+    else if brace_density(code) > 0.05 {
+        // structured, but nothing more specific
+        "c"
+    }
+    else {
+        return None
+    };
+    Some(language.into())
+}
+
+/// the proportion of braces and semicolons in the non-whitespace characters of `code`
+fn brace_density(code: &str) -> f64 {
+    let chars = code.chars().filter(|c| !c.is_whitespace());
+    let (total, braces) = chars.fold((0, 0), |(total, braces), c| {
+        (total + 1, braces + matches!(c, '{' | '}' | ';') as usize)
+    });
+    match total {
+        0 => 0.,
+        total => braces as f64 / total as f64,
+    }
+}
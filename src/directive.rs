@@ -0,0 +1,97 @@
+use crate::html::{is_name, escape_attribute};
+use crate::rewrite::{self, Rewriter};
+
+/// the tag of the directives whose name is not a component
+pub(crate) const FALLBACK_TAG: &str = "md-directive";
+
+/// rewrite the `:::` containers of `src` as component tags:
+/// `::: name title` becomes `<name title="title">` if `is_component(name)`,
+/// and `<md-directive name="name" title="title">` otherwise.
+///
+/// Returns the new source, and where the length of the source changed:
+/// offsets in the new source with the total change of length before them.
+/// `None` if there is no directive
+pub(crate) fn rewrite_directives(src: &str, is_component: impl Fn(&str) -> bool) -> Option<(String, Vec<(usize, isize)>)> {
+    let mut rewriter = Rewriter::default();
+    // the open containers: their number of colons and tag
+    let mut open: Vec<(usize, &str)> = Vec::new();
+    // the fence of the code block we are in
+    let mut fence: Option<&str> = None;
+
+    for line in src.split_inclusive('\n') {
+        let trimmed = line.trim();
+        if let Some(f) = fence {
+            if trimmed.starts_with(f) {
+                fence = None
+            }
+            rewriter.keep(line);
+            continue
+        }
+        if let Some(f) = rewrite::fence(line) {
+            fence = Some(f);
+            rewriter.keep(line);
+            continue
+        }
+
+        let colons = trimmed.len() - trimmed.trim_start_matches(':').len();
+        let rest = trimmed[colons..].trim();
+        let tag = if colons < 3 {
+            None
+        } else if rest.is_empty() {
+            // the innermost container opened with as many colons or less
+            open.iter().rposition(|(n, _)| *n <= colons).map(|depth| {
+                let (_, name) = open[depth];
+                open.truncate(depth);
+                format!("\n</{name}>\n\n")
+            })
+        } else {
+            let (name, title) = rest.split_once(char::is_whitespace).unwrap_or((rest, ""));
+            let title = escape_attribute(title.trim());
+            is_name(name).then(|| match is_component(name) {
+                true => {
+                    open.push((colons, name));
+                    format!("\n<{name} title=\"{title}\">\n\n")
+                }
+                false => {
+                    open.push((colons, FALLBACK_TAG));
+                    format!("\n<{FALLBACK_TAG} name=\"{name}\" title=\"{title}\">\n\n")
+                }
+            })
+        };
+
+        match tag {
+            Some(tag) => rewriter.replace(line.len(), &tag),
+            None => rewriter.keep(line),
+        }
+    }
+
+    if !rewriter.changed() {
+        return None
+    }
+    // containers left open end with the document
+    for (_, name) in open.into_iter().rev() {
+        rewriter.insert(&format!("\n</{name}>\n"));
+    }
+    rewriter.finish()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn title_is_escaped() {
+        let (rewritten, _) = rewrite_directives("::: note a \"<b>\" & c\ntext\n:::\n", |_| true).unwrap();
+        assert_eq!(rewritten, "\n<note title=\"a &quot;&lt;b&gt;&quot; &amp; c\">\n\ntext\n\n</note>\n\n");
+    }
+
+    #[test]
+    fn offsets_are_restored() {
+        let src = "::: note\ntext\n:::\nafter";
+        let (rewritten, shifts) = rewrite_directives(src, |_| true).unwrap();
+        let text = rewritten.find("text").unwrap();
+        assert_eq!(rewrite::unshift(&shifts, text), src.find("text").unwrap());
+        let after = rewritten.find("after").unwrap();
+        assert_eq!(rewrite::unshift(&shifts, after), src.find("after").unwrap());
+    }
+}
@@ -0,0 +1,264 @@
+/// a part of a text with emoji shortcodes, see [`split_shortcodes`]
+#[derive(Clone, PartialEq, Debug)]
+pub(crate) enum EmojiPiece<'t, 'c> {
+    Text(&'t str),
+    /// a shortcode of the built-in table, as the unicode emoji
+    Emoji(&'static str),
+    /// a custom shortcode, with the url of its image
+    Custom { shortcode: &'t str, url: &'c str },
+}
+
+/// the built-in shortcodes, sorted by name.
+/// Empty without the `emoji` feature, to keep the table out of the binary
+#[cfg(feature="emoji")]
+static EMOJI: &[(&str, &str)] = &[
+    ("+1", "👍"),
+    ("-1", "👎"),
+    ("100", "💯"),
+    ("airplane", "✈️"),
+    ("alarm_clock", "⏰"),
+    ("angry", "😠"),
+    ("apple", "🍎"),
+    ("art", "🎨"),
+    ("baby", "👶"),
+    ("balloon", "🎈"),
+    ("bangbang", "‼️"),
+    ("beer", "🍺"),
+    ("beers", "🍻"),
+    ("bell", "🔔"),
+    ("bike", "🚲"),
+    ("bird", "🐦"),
+    ("birthday", "🎂"),
+    ("blush", "😊"),
+    ("bomb", "💣"),
+    ("book", "📖"),
+    ("books", "📚"),
+    ("boom", "💥"),
+    ("brain", "🧠"),
+    ("bug", "🐛"),
+    ("bulb", "💡"),
+    ("bus", "🚌"),
+    ("cake", "🍰"),
+    ("calendar", "📆"),
+    ("camera", "📷"),
+    ("car", "🚗"),
+    ("cat", "🐱"),
+    ("chart_with_downwards_trend", "📉"),
+    ("chart_with_upwards_trend", "📈"),
+    ("check", "✔️"),
+    ("checkered_flag", "🏁"),
+    ("cherries", "🍒"),
+    ("clap", "👏"),
+    ("clipboard", "📋"),
+    ("clock1", "🕐"),
+    ("cloud", "☁️"),
+    ("coffee", "☕"),
+    ("computer", "💻"),
+    ("confused", "😕"),
+    ("construction", "🚧"),
+    ("cool", "🆒"),
+    ("cow", "🐮"),
+    ("crab", "🦀"),
+    ("crown", "👑"),
+    ("cry", "😢"),
+    ("cupid", "💘"),
+    ("dash", "💨"),
+    ("disappointed", "😞"),
+    ("dizzy", "💫"),
+    ("dog", "🐶"),
+    ("dollar", "💵"),
+    ("dragon", "🐉"),
+    ("earth_africa", "🌍"),
+    ("earth_americas", "🌎"),
+    ("earth_asia", "🌏"),
+    ("email", "📧"),
+    ("eyes", "👀"),
+    ("facepalm", "🤦"),
+    ("fire", "🔥"),
+    ("fish", "🐟"),
+    ("flushed", "😳"),
+    ("frog", "🐸"),
+    ("gear", "⚙️"),
+    ("gem", "💎"),
+    ("ghost", "👻"),
+    ("gift", "🎁"),
+    ("grin", "😁"),
+    ("grinning", "😀"),
+    ("hammer", "🔨"),
+    ("hammer_and_wrench", "🛠️"),
+    ("heart", "❤️"),
+    ("heart_eyes", "😍"),
+    ("heavy_check_mark", "✔️"),
+    ("heavy_minus_sign", "➖"),
+    ("heavy_plus_sign", "➕"),
+    ("hourglass", "⌛"),
+    ("house", "🏠"),
+    ("hugs", "🤗"),
+    ("hushed", "😯"),
+    ("information_source", "ℹ️"),
+    ("innocent", "😇"),
+    ("joy", "😂"),
+    ("key", "🔑"),
+    ("kiss", "💋"),
+    ("laughing", "😆"),
+    ("link", "🔗"),
+    ("lock", "🔒"),
+    ("mag", "🔍"),
+    ("memo", "📝"),
+    ("moon", "🌙"),
+    ("muscle", "💪"),
+    ("mushroom", "🍄"),
+    ("musical_note", "🎵"),
+    ("neutral_face", "😐"),
+    ("no_entry", "⛔"),
+    ("ok", "🆗"),
+    ("ok_hand", "👌"),
+    ("package", "📦"),
+    ("partying_face", "🥳"),
+    ("pencil", "📝"),
+    ("pencil2", "✏️"),
+    ("penguin", "🐧"),
+    ("pizza", "🍕"),
+    ("point_down", "👇"),
+    ("point_left", "👈"),
+    ("point_right", "👉"),
+    ("point_up", "☝️"),
+    ("pray", "🙏"),
+    ("pushpin", "📌"),
+    ("question", "❓"),
+    ("rage", "😡"),
+    ("rainbow", "🌈"),
+    ("raised_hands", "🙌"),
+    ("recycle", "♻️"),
+    ("relaxed", "☺️"),
+    ("relieved", "😌"),
+    ("rocket", "🚀"),
+    ("rofl", "🤣"),
+    ("rose", "🌹"),
+    ("rotating_light", "🚨"),
+    ("sad", "😞"),
+    ("scream", "😱"),
+    ("see_no_evil", "🙈"),
+    ("shrug", "🤷"),
+    ("skull", "💀"),
+    ("sleeping", "😴"),
+    ("slightly_smiling_face", "🙂"),
+    ("smile", "😄"),
+    ("smiley", "😃"),
+    ("smirk", "😏"),
+    ("snail", "🐌"),
+    ("snake", "🐍"),
+    ("snowflake", "❄️"),
+    ("sob", "😭"),
+    ("sparkles", "✨"),
+    ("speech_balloon", "💬"),
+    ("star", "⭐"),
+    ("star2", "🌟"),
+    ("stop_sign", "🛑"),
+    ("stuck_out_tongue", "😛"),
+    ("sunglasses", "😎"),
+    ("sunny", "☀️"),
+    ("sweat", "😓"),
+    ("sweat_smile", "😅"),
+    ("tada", "🎉"),
+    ("thinking", "🤔"),
+    ("thumbsdown", "👎"),
+    ("thumbsup", "👍"),
+    ("tired_face", "😫"),
+    ("trophy", "🏆"),
+    ("turtle", "🐢"),
+    ("umbrella", "☔"),
+    ("unamused", "😒"),
+    ("unicorn", "🦄"),
+    ("unlock", "🔓"),
+    ("upside_down_face", "🙃"),
+    ("v", "✌️"),
+    ("warning", "⚠️"),
+    ("wave", "👋"),
+    ("white_check_mark", "✅"),
+    ("wink", "😉"),
+    ("worried", "😟"),
+    ("wrench", "🔧"),
+    ("x", "❌"),
+    ("yum", "😋"),
+    ("zap", "⚡"),
+    ("zzz", "💤"),
+];
+
+#[cfg(not(feature="emoji"))]
+static EMOJI: &[(&str, &str)] = &[];
+
+/// the unicode emoji of the shortcode `name`, without the colons
+pub(crate) fn emoji(name: &str) -> Option<&'static str> {
+    EMOJI.binary_search_by_key(&name, |(n, _)| n).ok().map(|i| EMOJI[i].1)
+}
+
+/// split `text` around the `:shortcode:` it contains.
+/// `custom` gives the url of the image of custom shortcodes, which take precedence
+/// over the built-in ones. Unknown shortcodes are left as they are.
+/// `None` if there is no known shortcode
+pub(crate) fn split_shortcodes<'t, 'c>(text: &'t str, custom: impl Fn(&str) -> Option<&'c str>) -> Option<Vec<EmojiPiece<'t, 'c>>> {
+    let mut pieces = Vec::new();
+    // the start of the text not pushed yet
+    let mut start = 0;
+    // where to look for the next opening colon
+    let mut from = 0;
+
+    while let Some(open) = text[from..].find(':').map(|i| from + i) {
+        let Some(close) = text[open + 1..].find(':').map(|i| open + 1 + i) else { break };
+        let name = &text[open + 1..close];
+        let valid = !name.is_empty()
+            && name.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '+' | '-'));
+        let piece = match (custom(name), emoji(name)) {
+            _ if !valid => None,
+            (Some(url), _) => Some(EmojiPiece::Custom { shortcode: &text[open..=close], url }),
+            (None, Some(emoji)) => Some(EmojiPiece::Emoji(emoji)),
+            (None, None) => None,
+        };
+        match piece {
+            Some(piece) => {
+                if start < open {
+                    pieces.push(EmojiPiece::Text(&text[start..open]))
+                }
+                pieces.push(piece);
+                start = close + 1;
+                from = close + 1;
+            }
+            // the closing colon may open the next shortcode
+            None => from = close,
+        }
+    }
+
+    if pieces.is_empty() {
+        return None
+    }
+    if start < text.len() {
+        pieces.push(EmojiPiece::Text(&text[start..]))
+    }
+    Some(pieces)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn custom_shortcodes_are_split_out() {
+        let custom = |name: &str| (name == "ferris").then_some("ferris.png");
+        let pieces = split_shortcodes("a :ferris: b :unknown: c", custom).unwrap();
+        assert_eq!(pieces, [
+            EmojiPiece::Text("a "),
+            EmojiPiece::Custom { shortcode: ":ferris:", url: "ferris.png" },
+            EmojiPiece::Text(" b :unknown: c"),
+        ]);
+        assert_eq!(split_shortcodes("12:30 and :ferris:", custom).unwrap()[1], EmojiPiece::Custom { shortcode: ":ferris:", url: "ferris.png" });
+        assert_eq!(split_shortcodes("no :shortcode: here", custom), None);
+    }
+
+    #[cfg(feature="emoji")]
+    #[test]
+    fn the_table_is_sorted() {
+        assert!(EMOJI.windows(2).all(|w| w[0].0 < w[1].0));
+        assert_eq!(emoji("+1"), Some("👍"));
+    }
+}
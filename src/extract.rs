@@ -0,0 +1,158 @@
+use core::ops::Range;
+
+use crate::tree::{parse_to_tree, MdNode, NodeKind, Alignment, ParseConfig};
+
+/// a table of the markdown document, with its cells as plain text
+#[derive(Clone, PartialEq, Debug)]
+pub struct ExtractedTable {
+    pub headers: Vec<String>,
+    pub rows: Vec<Vec<String>>,
+    pub alignments: Vec<Alignment>,
+    /// the range of the table in the markdown source
+    pub range: Range<usize>,
+}
+
+/// the tables of `src`, parsed like [`parse_to_tree`] does.
+/// Cell contents are flattened with [`MdNode::plain_text`]
+pub fn extract_tables(src: &str, config: &ParseConfig) -> Vec<ExtractedTable> {
+    tables_in(&parse_to_tree(src, config, |_| false))
+}
+
+/// the tables of a tree, in document order
+pub(crate) fn tables_in(tree: &MdNode) -> Vec<ExtractedTable> {
+    let mut tables = Vec::new();
+    collect_tables(tree, &mut tables);
+    tables
+}
+
+fn collect_tables(node: &MdNode, tables: &mut Vec<ExtractedTable>) {
+    match table_of(node) {
+        Some(table) => tables.push(table),
+        None => node.children.iter().for_each(|child| collect_tables(child, tables)),
+    }
+}
+
+/// the table `node` is, if it is one
+pub(crate) fn table_of(node: &MdNode) -> Option<ExtractedTable> {
+    let NodeKind::Table { alignments } = &node.kind else {
+        return None
+    };
+
+    let cells = |row: &MdNode| row.children.iter()
+        .map(MdNode::plain_text)
+        .collect::<Vec<_>>();
+
+    let mut table = ExtractedTable {
+        headers: Vec::new(),
+        rows: Vec::new(),
+        alignments: alignments.clone(),
+        range: node.range.clone(),
+    };
+    for child in &node.children {
+        match child.kind {
+            NodeKind::TableHead => table.headers = cells(child),
+            NodeKind::TableRow => table.rows.push(cells(child)),
+            _ => (),
+        }
+    }
+    Some(table)
+}
+
+impl ExtractedTable {
+    /// whether the header row has any text
+    pub fn has_headers(&self) -> bool {
+        self.headers.iter().any(|h| !h.trim().is_empty())
+    }
+
+    /// the rows as lines of text, like `Header: value; Header: value`,
+    /// or `value, value` if the table has no headers
+    pub fn linearized(&self) -> Vec<String> {
+        self.rows.iter().map(|row| {
+            if !self.has_headers() {
+                return row.join(", ")
+            }
+            row.iter()
+                .enumerate()
+                .map(|(i, cell)| match self.headers.get(i).filter(|h| !h.trim().is_empty()) {
+                    Some(header) => format!("{header}: {cell}"),
+                    None => cell.clone(),
+                })
+                .collect::<Vec<_>>()
+                .join("; ")
+        })
+        .collect()
+    }
+}
+
+/// an item of a task list, like `- [x] done`
+#[derive(Clone, PartialEq, Debug)]
+pub struct TaskItem {
+    /// the text of the item, without its nested lists
+    pub text: String,
+    pub checked: bool,
+    /// the number of list items this item is nested in
+    pub depth: usize,
+    /// the indices of the enclosing task items in the returned list,
+    /// from the outermost to the innermost
+    pub parents: Vec<usize>,
+    /// the range of the whole item in the markdown source
+    pub range: Range<usize>,
+    /// the range of the `[ ]` or `[x]` marker in the markdown source
+    pub marker_range: Range<usize>,
+}
+
+/// the task list items of `src`, in document order, parsed like [`parse_to_tree`] does.
+pub fn extract_tasks(src: &str, config: &ParseConfig) -> Vec<TaskItem> {
+    let tree = parse_to_tree(src, config, |_| false);
+    let mut tasks = Vec::new();
+    collect_tasks(&tree, 0, &mut Vec::new(), &mut tasks);
+    tasks
+}
+
+fn collect_tasks(node: &MdNode, depth: usize, parents: &mut Vec<usize>, tasks: &mut Vec<TaskItem>) {
+    if node.kind != NodeKind::Item {
+        node.children.iter().for_each(|child| collect_tasks(child, depth, parents, tasks));
+        return
+    }
+
+    let marker = find_marker(node);
+    let is_task = marker.is_some();
+    if let Some((checked, marker_range)) = marker {
+        tasks.push(TaskItem {
+            text: item_text(node).trim().to_string(),
+            checked,
+            depth,
+            parents: parents.clone(),
+            range: node.range.clone(),
+            marker_range,
+        });
+        parents.push(tasks.len() - 1);
+    }
+
+    node.children.iter().for_each(|child| collect_tasks(child, depth + 1, parents, tasks));
+
+    if is_task {
+        parents.pop();
+    }
+}
+
+/// the task marker of a list item, which may be wrapped in a paragraph in loose lists
+fn find_marker(item: &MdNode) -> Option<(bool, Range<usize>)> {
+    let first = item.children.first()?;
+    match first.kind {
+        NodeKind::TaskListMarker(checked) => Some((checked, first.range.clone())),
+        NodeKind::Paragraph => match first.children.first()? {
+            MdNode { kind: NodeKind::TaskListMarker(checked), range, .. } => Some((*checked, range.clone())),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+fn item_text(item: &MdNode) -> String {
+    item.children.iter()
+        .filter(|child| !matches!(child.kind, NodeKind::List{..}))
+        .map(MdNode::plain_text)
+        .collect::<Vec<_>>()
+        .join(" ")
+}
@@ -0,0 +1,109 @@
+use crate::cache::StableHasher;
+use crate::{ComponentRegistry, MdProps, RenderEnv, SoftBreakMode, TableFallback};
+
+impl MdProps<'_> {
+    /// a hash of the source and of every option that affects the output.
+    /// Use it as a cache key, or to detect that the rendered content changed.
+    ///
+    /// The fingerprint is stable across processes, platforms and patch versions.
+    /// It may change in minor versions, as new options are added.
+    ///
+    /// Callbacks can not be compared, so only their presence is taken into account.
+    pub fn content_fingerprint(&self) -> u64 {
+        let mut h = StableHasher::new();
+
+        h.write_str(self.src);
+        h.write_u64(self.parse_options.map_or(u64::MAX, |o| o.bits() as u64));
+        for extension in [self.smart_punctuation, self.tables, self.strikethrough, self.task_lists, self.footnotes] {
+            h.write_bool(extension)
+        }
+        h.write_str(self.theme.as_deref().unwrap_or_default());
+        h.write_bool(self.highlight_classes);
+        h.write_bool(self.wikilinks);
+        h.write_bool(self.hard_line_breaks);
+        h.write_bool(self.normalize_newlines);
+        h.write_bool(self.decode_entities);
+        h.write_u64(match self.soft_break {
+            SoftBreakMode::Space => 0,
+            SoftBreakMode::Br => 1,
+            SoftBreakMode::Nothing => 2,
+        });
+        h.write_u64(self.break_long_words.map_or(u64::MAX, |n| n as u64));
+        h.write_u64(self.scroll_offset.map_or(u64::MAX, f64::to_bits));
+        h.write_bool(self.render_env == Some(RenderEnv::Server));
+        h.write_bool(self.respect_item_numbers);
+        if let Some(a11y) = &self.accessibility {
+            h.write_str(&a11y.math_label);
+            h.write_str(&a11y.footnotes_label);
+            h.write_str(&a11y.toc_label);
+        }
+        h.write_bool(self.trusted_html);
+        let allowed = self.allowed_elements.unwrap_or_default();
+        for allowed in [allowed.headings, allowed.images, allowed.block_quotes, allowed.tables, allowed.rules] {
+            h.write_bool(allowed)
+        }
+        h.write_bool(self.inline_mode);
+        h.write_u64(match self.table_fallback {
+            TableFallback::Lines => 0,
+            TableFallback::DefinitionList => 1,
+            TableFallback::Hidden => 2,
+        });
+        h.write_bool(self.block_drag);
+        h.write_bool(self.editable_blocks);
+        h.write_bool(self.inline_code_languages);
+        h.write_bool(self.directives);
+        h.write_bool(self.definition_lists);
+        h.write_bool(self.alerts);
+        h.write_bool(self.math);
+        h.write_bool(self.emoji);
+        h.write_bool(self.mark);
+        h.write_bool(self.autolinks);
+        for (shortcode, url) in self.custom_emoji.iter().flatten() {
+            h.write_str(shortcode);
+            h.write_str(url);
+        }
+        h.write_u64(self.max_nesting_depth as u64);
+        h.write_bool(self.source_attributes);
+        h.write_bool(self.source_positions || self.scroll_container.is_some() && self.scroll_state.is_some());
+        h.write_bool(self.heading_ids);
+        h.write_bool(self.slugify.is_some());
+        h.write_bool(self.heading_anchors);
+        h.write_bool(self.copy_buttons);
+
+        h.write_bool(self.render_links.is_some());
+        h.write_bool(self.on_link_click.is_some());
+        h.write_bool(self.on_link_visible.is_some());
+        h.write_bool(self.render_links_with_details.is_some());
+        h.write_bool(self.resolve_wikilink.is_some());
+        h.write_bool(self.wikilink_exists.is_some());
+        h.write_str(self.missing_wikilink_class.as_deref().unwrap_or_default());
+        h.write_bool(self.render_images.is_some());
+        h.write_bool(self.render_code_blocks.is_some());
+        h.write_bool(self.render_headings.is_some());
+        h.write_bool(self.render_tables.is_some());
+        h.write_bool(self.render_blockquotes.is_some());
+        h.write_bool(self.render_tasklist_item.is_some());
+        h.write_bool(self.render_math.is_some());
+        h.write_bool(self.render_footnote_reference.is_some());
+        h.write_bool(self.render_footnote_definition.is_some());
+        h.write_bool(self.prehighlighted_code.is_some());
+        h.write_bool(self.defer_highlighting);
+        h.write_bool(self.code_line_annotations.is_some());
+        h.write_bool(self.detect_language.is_some());
+        h.write_bool(self.ruby_annotator.is_some());
+        h.write_bool(self.transform_text.is_some());
+        h.write_bool(self.render_component_error.is_some());
+        h.write_bool(self.clipboard.is_some());
+        for id in self.initial_open_sections.iter().flatten() {
+            h.write_str(id)
+        }
+        for name in self.components.names() {
+            h.write_str(name)
+        }
+        for name in self.registry.iter().flat_map(ComponentRegistry::names) {
+            h.write_str(&name)
+        }
+
+        h.finish()
+    }
+}
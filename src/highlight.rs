@@ -0,0 +1,250 @@
+use std::cell::{Cell, RefCell};
+use std::collections::{HashMap, VecDeque};
+use std::future::Future;
+use std::pin::Pin;
+use std::rc::Rc;
+use std::sync::OnceLock;
+use std::task::{Context, Poll};
+
+use dioxus::prelude::ScopeState;
+
+use syntect::easy::HighlightLines;
+use syntect::highlighting::ThemeSet;
+use syntect::html::{css_for_theme_with_class_style, line_tokens_to_classed_spans, styled_line_to_highlighted_html, ClassStyle, IncludeBackground};
+use syntect::parsing::{ParseState, Scope, ScopeStack, SyntaxSet};
+use syntect::util::LinesWithEndings;
+
+use crate::cache::{CacheKey, RenderCache};
+
+const DEFAULT_THEME: &str = "base16-ocean.light";
+
+fn syntaxes() -> &'static SyntaxSet {
+    static SYNTAXES: OnceLock<SyntaxSet> = OnceLock::new();
+    SYNTAXES.get_or_init(SyntaxSet::load_defaults_newlines)
+}
+
+fn themes() -> &'static ThemeSet {
+    static THEMES: OnceLock<ThemeSet> = OnceLock::new();
+    THEMES.get_or_init(ThemeSet::load_defaults)
+}
+
+/// the background of `theme` as a css declaration, if it has one
+pub(crate) fn background(theme: Option<&str>) -> Option<String> {
+    let color = themes().themes.get(theme.unwrap_or(DEFAULT_THEME))?.settings.background?;
+    Some(format!("background-color: #{:02x}{:02x}{:02x}", color.r, color.g, color.b))
+}
+
+/// the prefix of the css classes of `theme`, like `hl-base16-ocean-light-`,
+/// so that the classes of two themes used on the same page do not collide.
+/// `None` if the theme is unknown
+fn class_prefix(theme: Option<&str>) -> Option<&'static str> {
+    // syntect wants static prefixes, there is one per default theme
+    static PREFIXES: OnceLock<HashMap<String, &'static str>> = OnceLock::new();
+    let prefixes = PREFIXES.get_or_init(|| {
+        themes().themes.keys()
+            .map(|name| {
+                let slug: String = name.chars()
+                    .map(|c| if c.is_ascii_alphanumeric() { c.to_ascii_lowercase() } else { '-' })
+                    .collect();
+                let prefix: &'static str = Box::leak(format!("hl-{slug}-").into_boxed_str());
+                (name.clone(), prefix)
+            })
+            .collect()
+    });
+    prefixes.get(theme.unwrap_or(DEFAULT_THEME)).copied()
+}
+
+/// the stylesheet of `theme` for code highlighted with classes, and its id.
+/// Returns `None` if the theme is unknown
+pub(crate) fn theme_css(theme: Option<&str>) -> Option<(&'static str, Rc<str>)> {
+    thread_local! {
+        static CSS: RefCell<HashMap<&'static str, Rc<str>>> = RefCell::new(HashMap::new());
+    }
+    let prefix = class_prefix(theme)?;
+    let id = prefix.trim_end_matches('-');
+    if let Some(css) = CSS.with(|css| css.borrow().get(id).cloned()) {
+        return Some((id, css))
+    }
+    let style = themes().themes.get(theme.unwrap_or(DEFAULT_THEME))?;
+    let css: Rc<str> = css_for_theme_with_class_style(style, ClassStyle::SpacedPrefixed { prefix }).ok()?.into();
+    CSS.with(|cache| cache.borrow_mut().insert(id, css.clone()));
+    Some((id, css))
+}
+
+/// the key of the highlighted html of `code`
+fn key(code: &str, language: &str, theme: Option<&str>, classes: bool) -> CacheKey {
+    CacheKey::Highlight {
+        code: code.to_string(),
+        language: Some(language.to_string()),
+        theme: theme.map(String::from),
+        classes,
+    }
+}
+
+/// highlight code as html, without background,
+/// with inline styles or with the css classes of the theme, see `theme_css`.
+/// Returns `None` if the language or the theme is unknown
+pub(crate) fn highlight(code: &str, language: &str, theme: Option<&str>, classes: bool, cache: Option<&dyn RenderCache>) -> Option<Rc<str>> {
+    let key = key(code, language, theme, classes);
+    if let Some(html) = cache.and_then(|c| c.get(&key)) {
+        return Some(html)
+    }
+
+    let html: Rc<str> = highlight_lines(code, language, theme, classes)?.concat().into();
+
+    if let Some(cache) = cache {
+        cache.put(key, html.clone())
+    }
+    Some(html)
+}
+
+/// highlight each line of code as html, without background, like `highlight`.
+/// The html of a line ends with its newline, if it has one
+pub(crate) fn highlight_lines(code: &str, language: &str, theme: Option<&str>, classes: bool) -> Option<Vec<String>> {
+    let syntax = syntaxes().find_syntax_by_token(language)?;
+    if classes {
+        return classed_lines(code, syntax, class_prefix(theme)?)
+    }
+    let theme = themes().themes.get(theme.unwrap_or(DEFAULT_THEME))?;
+
+    let mut highlighter = HighlightLines::new(syntax, theme);
+    LinesWithEndings::from(code)
+        .map(|line| {
+            let regions = highlighter.highlight_line(line, syntaxes()).ok()?;
+            styled_line_to_highlighted_html(&regions, IncludeBackground::No).ok()
+        })
+        .collect()
+}
+
+/// highlight each line of code with css classes prefixed by `prefix`.
+/// The spans still open at the end of a line are closed, and opened again on the next one
+fn classed_lines(code: &str, syntax: &syntect::parsing::SyntaxReference, prefix: &'static str) -> Option<Vec<String>> {
+    let mut parser = ParseState::new(syntax);
+    let mut stack = ScopeStack::new();
+    LinesWithEndings::from(code)
+        .map(|line| {
+            let mut html = String::new();
+            for scope in stack.as_slice() {
+                html.push_str(&format!(r#"<span class="{}">"#, scope_classes(*scope, prefix)));
+            }
+            let ops = parser.parse_line(line, syntaxes()).ok()?;
+            let (spans, _) = line_tokens_to_classed_spans(line, &ops, ClassStyle::SpacedPrefixed { prefix }, &mut stack).ok()?;
+            html.push_str(&spans);
+            html.push_str(&"</span>".repeat(stack.len()));
+            Some(html)
+        })
+        .collect()
+}
+
+/// the classes of the span of `scope`, like syntect names them
+fn scope_classes(scope: Scope, prefix: &str) -> String {
+    scope.build_string()
+        .split('.')
+        .map(|atom| format!("{prefix}{atom}"))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// bytes of code highlighted between two renders, see `DeferredHighlights`
+const BATCH: usize = 8 * 1024;
+
+/// code blocks are forgotten past this number, the next renders highlight them again
+const MAX_DEFERRED: usize = 4096;
+
+/// a code block waiting to be highlighted
+struct Job {
+    key: CacheKey,
+    code: String,
+    language: String,
+    theme: Option<String>,
+    classes: bool,
+    cache: Option<Rc<dyn RenderCache>>,
+}
+
+/// code blocks highlighted after the document is displayed, a batch at a time,
+/// see `defer_highlighting`. Kept between renders
+#[derive(Default)]
+pub(crate) struct DeferredHighlights {
+    /// the html of the highlighted blocks, `None` if they can not be highlighted
+    done: Rc<RefCell<HashMap<CacheKey, Option<Rc<str>>>>>,
+    queue: Rc<RefCell<VecDeque<Job>>>,
+    /// whether a task is highlighting the queue
+    working: Rc<Cell<bool>>,
+}
+
+impl DeferredHighlights {
+    /// the highlighted html of `code`, like `highlight`.
+    /// Until it is highlighted, `None` is returned and `cx` is rendered again once it is
+    pub fn get(&self, cx: &ScopeState, code: &str, language: &str, theme: Option<&str>, classes: bool, cache: Option<Rc<dyn RenderCache>>) -> Option<Rc<str>> {
+        let key = key(code, language, theme, classes);
+        if let Some(html) = self.done.borrow().get(&key) {
+            return html.clone()
+        }
+        if let Some(html) = cache.as_ref().and_then(|c| c.get(&key)) {
+            return Some(html)
+        }
+
+        let mut queue = self.queue.borrow_mut();
+        if !queue.iter().any(|job| job.key == key) {
+            queue.push_back(Job {
+                key,
+                code: code.to_string(),
+                language: language.to_string(),
+                theme: theme.map(String::from),
+                classes,
+                cache,
+            })
+        }
+        drop(queue);
+        self.start(cx);
+        None
+    }
+
+    /// highlight the queued blocks in a task, rendering `cx` again after each batch
+    fn start(&self, cx: &ScopeState) {
+        if self.working.replace(true) {
+            return
+        }
+        let done = self.done.clone();
+        let queue = self.queue.clone();
+        let working = self.working.clone();
+        let update = cx.schedule_update();
+        cx.spawn(async move {
+            loop {
+                let mut budget = BATCH;
+                while budget > 0 {
+                    let Some(job) = queue.borrow_mut().pop_front() else { break };
+                    budget = budget.saturating_sub(job.code.len().max(1));
+                    let html = highlight(&job.code, &job.language, job.theme.as_deref(), job.classes, job.cache.as_deref());
+                    let mut done = done.borrow_mut();
+                    if done.len() >= MAX_DEFERRED {
+                        done.clear()
+                    }
+                    done.insert(job.key, html);
+                }
+                update();
+                if queue.borrow().is_empty() {
+                    break
+                }
+                // let the batch be displayed before the next one
+                YieldNow(false).await
+            }
+            working.set(false)
+        });
+    }
+}
+
+/// a future ready the second time it is polled
+struct YieldNow(bool);
+
+impl Future for YieldNow {
+    type Output = ();
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context) -> Poll<()> {
+        if std::mem::replace(&mut self.0, true) {
+            return Poll::Ready(())
+        }
+        cx.waker().wake_by_ref();
+        Poll::Pending
+    }
+}
@@ -0,0 +1,118 @@
+use std::borrow::Cow;
+
+/// a single html tag, as found in raw html events
+#[derive(Clone, PartialEq, Debug)]
+pub(crate) struct HtmlTag<'s> {
+    pub name: &'s str,
+    /// valueless attributes have an empty value,
+    /// and are listed in `flags` too
+    pub attributes: Vec<(&'s str, &'s str)>,
+    pub flags: Vec<&'s str>,
+    pub kind: TagKind,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub(crate) enum TagKind {
+    /// `<name>`
+    Open,
+    /// `</name>`
+    Close,
+    /// `<name/>`
+    SelfClosing,
+}
+
+/// parse `html` if it consists of exactly one tag
+pub(crate) fn parse_tag(html: &str) -> Option<HtmlTag<'_>> {
+    let inner = html.trim().strip_prefix('<')?.strip_suffix('>')?;
+
+    if let Some(name) = inner.strip_prefix('/') {
+        let name = name.trim();
+        return is_name(name).then_some(HtmlTag {
+            name,
+            attributes: Vec::new(),
+            flags: Vec::new(),
+            kind: TagKind::Close,
+        })
+    }
+
+    let (inner, kind) = match inner.strip_suffix('/') {
+        Some(inner) => (inner, TagKind::SelfClosing),
+        None => (inner, TagKind::Open),
+    };
+
+    let name_end = inner.find(char::is_whitespace).unwrap_or(inner.len());
+    let name = &inner[..name_end];
+    if !is_name(name) {
+        return None
+    }
+
+    let (attributes, flags) = parse_attributes(&inner[name_end..])?;
+    Some(HtmlTag {
+        name,
+        attributes,
+        flags,
+        kind,
+    })
+}
+
+/// `value` escaped to be written between the double quotes of an attribute
+pub(crate) fn escape_attribute(value: &str) -> String {
+    value.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// `value` with the characters escaped by [`escape_attribute`] restored
+pub(crate) fn unescape_attribute(value: &str) -> Cow<'_, str> {
+    if !value.contains('&') {
+        return value.into()
+    }
+    value.replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        // last, so that `&amp;lt;` stays `&lt;`
+        .replace("&amp;", "&")
+        .into()
+}
+
+pub(crate) fn is_name(name: &str) -> bool {
+    name.starts_with(|c: char| c.is_ascii_alphabetic())
+        && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+}
+
+/// parse `name="value"` pairs and valueless attributes.
+/// Returns `None` if they are malformed
+fn parse_attributes(mut s: &str) -> Option<(Vec<(&str, &str)>, Vec<&str>)> {
+    let mut attributes = Vec::new();
+    let mut flags = Vec::new();
+    loop {
+        s = s.trim_start();
+        if s.is_empty() {
+            return Some((attributes, flags))
+        }
+
+        let name_end = s.find(|c: char| c == '=' || c.is_whitespace()).unwrap_or(s.len());
+        let name = &s[..name_end];
+        if name.is_empty() {
+            return None
+        }
+        s = s[name_end..].trim_start();
+
+        let value = match s.strip_prefix('=') {
+            Some(rest) => {
+                let rest = rest.trim_start();
+                let quote = rest.chars().next().filter(|c| *c == '"' || *c == '\'')?;
+                let rest = &rest[1..];
+                let end = rest.find(quote)?;
+                s = &rest[end + 1..];
+                &rest[..end]
+            }
+            None => {
+                flags.push(name);
+                ""
+            }
+        };
+        attributes.push((name, value));
+    }
+}
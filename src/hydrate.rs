@@ -0,0 +1,153 @@
+use std::cell::{Cell, RefCell};
+use std::rc::Rc;
+
+use dioxus::prelude::*;
+use dioxus::html::prelude::EvalProvider;
+
+use crate::clipboard::js_string;
+use crate::visibility::instance_id;
+use crate::{extract_tasks, ParseConfig};
+
+#[derive(Props)]
+pub struct HydratedMarkdownProps<'a> {
+    /// the html of the document, rendered on the server by `render_to_hydratable_string`
+    html: &'a str,
+
+    /// the source the html was rendered from.
+    /// Clicking the checkbox of a task list item checks or unchecks it in the source,
+    /// like with `Markdown`; render the new html on the server to display it
+    task_list_sync: Option<UseState<String>>,
+
+    /// called with the href of the relative links when they are clicked,
+    /// for example to route them in the app. The browser does not follow them.
+    /// Links with a scheme and links to fragments are followed as usual
+    on_link_click: Option<EventHandler<'a, String>>,
+
+    /// add a button copying their code to the code blocks
+    #[props(default = false)]
+    copy_buttons: bool,
+}
+
+/// a message sent by the script of a hydrated document
+enum Hydrated {
+    /// a relative link was clicked
+    Link(String),
+}
+
+/// the script attaching the handlers of a hydrated document, and its messages
+#[derive(Default)]
+struct Hydration {
+    id: String,
+    started: Cell<bool>,
+    received: Rc<RefCell<Vec<Hydrated>>>,
+}
+
+/// markdown rendered to html on the server, with the handlers of its interactive parts attached on the client.
+///
+/// The html is inserted as is, so the client does not build a virtual dom of the whole document:
+/// a script finds the interactive elements by the markers `render_to_hydratable_string` added
+/// (`data-sourcepos` on blocks) and listens to them.
+/// Only copy buttons, task checkboxes and relative links are interactive,
+/// custom components keep the html they were rendered to on the server.
+/// Without javascript, the document is displayed but nothing is interactive
+#[allow(non_snake_case)]
+pub fn HydratedMarkdown<'a>(cx: Scope<'a, HydratedMarkdownProps<'a>>) -> Element<'a> {
+    let eval = cx.use_hook(|| cx.consume_context::<Rc<dyn EvalProvider>>()).clone();
+    let hydration = cx.use_hook(|| Hydration { id: instance_id(), ..Default::default() });
+
+    for message in hydration.received.take() {
+        match message {
+            Hydrated::Link(href) => {
+                if let Some(on_link_click) = &cx.props.on_link_click {
+                    on_link_click.call(href)
+                }
+            }
+        }
+    }
+
+    if let Some(eval) = eval.filter(|_| !hydration.started.replace(true)) {
+        hydrate(cx, hydration, &eval);
+    }
+
+    let id = &hydration.id;
+    let html = cx.props.html;
+    render!{
+        div {
+            "data-md-hydrated": "{id}",
+            dangerous_inner_html: "{html}",
+        }
+    }
+}
+
+/// attach the handlers of the document, once its html is mounted
+fn hydrate(cx: &Scoped<'_, HydratedMarkdownProps<'_>>, hydration: &Hydration, eval: &Rc<dyn EvalProvider>) {
+    let script = format!(
+        r#"const root = () => document.querySelector("[data-md-hydrated=" + CSS.escape({id}) + "]");
+        const start = element => Number(element.getAttribute("data-sourcepos").split("-")[0]);
+        requestAnimationFrame(() => {{
+            if (!{copy_buttons} || !root()) return;
+            root().querySelectorAll("pre").forEach(pre => {{
+                const copyable = document.createElement("div");
+                copyable.className = "md-copyable";
+                const button = document.createElement("button");
+                button.className = "md-copy";
+                button.type = "button";
+                button.setAttribute("aria-label", "copy");
+                button.textContent = "Copy";
+                button.addEventListener("click", () => navigator.clipboard.writeText(pre.innerText));
+                pre.replaceWith(copyable);
+                copyable.append(button, pre);
+            }});
+        }});
+        document.addEventListener("click", event => {{
+            const element = event.target;
+            if (!(element instanceof Element) || !root()?.contains(element)) return;
+            const item = element.matches("input[type=checkbox]") && element.closest("li[data-sourcepos]");
+            if (item && {tasks}) {{
+                dioxus.send({{ task: start(item) }});
+                return;
+            }}
+            const link = element.closest("a[href]");
+            const href = link?.getAttribute("href");
+            if (href && {links} && !/^([a-z][a-z0-9+.-]*:|#)/i.test(href)) {{
+                event.preventDefault();
+                dioxus.send({{ link: href }});
+            }}
+        }});"#,
+        id = js_string(&hydration.id),
+        copy_buttons = cx.props.copy_buttons,
+        tasks = cx.props.task_list_sync.is_some(),
+        links = cx.props.on_link_click.is_some(),
+    );
+    // without javascript, the document is only displayed
+    let Ok(hydrated) = eval.new_evaluator(script) else {
+        return
+    };
+    let source = cx.props.task_list_sync.clone();
+    let received = hydration.received.clone();
+    let update = cx.schedule_update();
+    cx.spawn(async move {
+        while let Ok(message) = hydrated.recv().await {
+            if let (Some(start), Some(source)) = (message["task"].as_u64(), &source) {
+                toggle_task(source, start as usize)
+            }
+            if let Some(href) = message["link"].as_str() {
+                received.borrow_mut().push(Hydrated::Link(href.to_string()));
+                update()
+            }
+        }
+    });
+}
+
+/// check or uncheck the task of the list item starting at `start` in `source`
+fn toggle_task(source: &UseState<String>, start: usize) {
+    let current = source.current();
+    // the configuration `render_to_hydratable_string` renders with
+    let tasks = extract_tasks(&current, &ParseConfig::default());
+    let Some(task) = tasks.iter().find(|task| task.marker_range.start >= start) else {
+        return
+    };
+    let mut toggled = current.to_string();
+    toggled.replace_range(task.marker_range.clone(), if task.checked { "[ ]" } else { "[x]" });
+    source.set(toggled)
+}
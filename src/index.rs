@@ -0,0 +1,674 @@
+use core::ops::Range;
+use std::collections::HashMap;
+
+use pulldown_cmark_wikilink::{Event, Tag, ParserOffsetIter, Options, LinkType};
+
+use crate::{AlertKind, LinkKind, RenderStats};
+
+use crate::code;
+use crate::extract::{tables_in, ExtractedTable};
+use crate::tree::{parse_rewritten, ParseConfig};
+use crate::html::{parse_tag, TagKind};
+
+/// a summary of the markdown source, built by a separate pass of the parser.
+/// The renderer only sees html elements, so anything that needs to know
+/// *where* in the document an element comes from looks it up here.
+#[derive(Default)]
+pub(crate) struct DocumentIndex {
+    /// source ranges of inline code spans
+    code_spans: Vec<Range<usize>>,
+
+    /// code spans followed by a language annotation like `{.rust}`:
+    /// the range of the code span, the range of the annotation and the language
+    code_languages: Vec<(Range<usize>, Range<usize>, String)>,
+
+    /// for each item of an ordered list, its start offset
+    /// and the number written by the author
+    item_numbers: Vec<(usize, u64)>,
+
+    /// raw html events, with the index of the run of
+    /// contiguous html events they are part of
+    html_events: Vec<(Range<usize>, usize)>,
+
+    /// source ranges of the runs of contiguous html events
+    html_runs: Vec<Range<usize>>,
+
+    /// images that are not part of the alt text of another image
+    images: Vec<ImageEntry>,
+
+    /// source ranges of the blocks at the root of the document
+    top_level_blocks: Vec<Range<usize>>,
+
+    /// number of code blocks, replaced by placeholders
+    code_blocks: usize,
+
+    tables: Vec<ExtractedTable>,
+
+    /// character references (`&copy;`, `&#8212;`...) decoded by the parser,
+    /// with the text they were decoded to
+    entities: Vec<(Range<usize>, String)>,
+
+    /// task list items: the range of the item, whether it is checked
+    /// and the range of its `[ ]` or `[x]` marker
+    tasks: Vec<(Range<usize>, bool, Range<usize>)>,
+
+    /// links, in document order
+    links: Vec<LinkEntry>,
+
+    /// footnote references: their range, their label
+    /// and the number of references to the same label before them
+    footnote_references: Vec<(Range<usize>, String, usize)>,
+
+    /// for each referenced footnote label, its number in the order of first reference
+    /// starting from 1, and its number of references
+    footnote_numbers: HashMap<String, (usize, usize)>,
+
+    /// footnote definitions: their range and label
+    footnote_definitions: Vec<(Range<usize>, String)>,
+
+    /// the level, plain text title, source range and `{#id}` of the headings
+    headings: Vec<(u8, String, Range<usize>, Option<String>)>,
+
+    /// opening and self-closing html tags, in the order their components are rendered
+    tags: Vec<TagEntry>,
+
+    /// whitespace-separated words of the text, code excluded
+    words: usize,
+
+    /// block quotes starting with an alert marker like `[!NOTE]`:
+    /// the range of the quote, the kind of alert and the range of the marker
+    alerts: Vec<(Range<usize>, AlertKind, Range<usize>)>,
+}
+
+pub(crate) struct TagEntry {
+    pub range: Range<usize>,
+    pub name: String,
+    /// whether it is inside a paragraph, among inline content
+    pub inline: bool,
+    /// its attributes written without a value
+    pub flags: Vec<String>,
+    /// the source between the tag and its closing tag.
+    /// `None` for self-closing and unclosed tags
+    pub children: Option<Range<usize>>,
+}
+
+pub(crate) struct LinkEntry {
+    pub range: Range<usize>,
+    pub url: String,
+    pub kind: LinkKind,
+    /// the text of the link, flattened to plain text
+    pub text: String,
+}
+
+pub(crate) struct ImageEntry {
+    pub range: Range<usize>,
+    pub url: String,
+    pub title: String,
+    /// the alt text, flattened to plain text
+    pub alt: String,
+    /// the range of the alt text in the source, if it is not empty
+    pub alt_range: Option<Range<usize>>,
+}
+
+/// the positions the lookups by cursor start from, for a render starting in the middle of the document
+pub(crate) struct Cursors {
+    pub html: usize,
+    pub images: usize,
+    pub links: usize,
+    pub tags: usize,
+    pub entities: usize,
+    pub tasks: usize,
+}
+
+/// how a raw html event is rendered when contiguous html is merged
+pub(crate) enum HtmlPart {
+    /// the first event of a run, rendered with the whole run
+    RunStart(Range<usize>),
+    /// an event already rendered with the start of its run
+    Merged,
+}
+
+impl DocumentIndex {
+    pub fn new(src: &str, options: Options, wikilinks: bool) -> Self {
+        let mut index = Self {
+            tables: tables_in(&parse_rewritten(src, &ParseConfig { options, wikilinks, ..ParseConfig::default() }, |_| false)),
+            ..Self::default()
+        };
+        // for each list we are in, whether it is ordered
+        let mut lists: Vec<bool> = Vec::new();
+        let mut previous_was_html = false;
+        // how many images we are in the alt text of
+        let mut image_depth = 0;
+        let mut depth = 0;
+        let mut in_heading = false;
+        let mut in_paragraph = false;
+        let mut in_link = false;
+        // the positions in `tags` of the tags not closed yet
+        let mut open_tags: Vec<usize> = Vec::new();
+        // the ranges of the list items we are in
+        let mut items: Vec<Range<usize>> = Vec::new();
+
+        for (event, range) in ParserOffsetIter::new_ext(src, options, wikilinks) {
+            // code blocks are replaced by placeholders before the source is rendered
+            if matches!(&event, Event::Html(html) if code::is_placeholder(html)) {
+                index.code_blocks += 1;
+                if depth == 0 {
+                    index.top_level_blocks.push(range)
+                }
+                previous_was_html = false;
+                continue
+            }
+            if let Event::Html(_) = event {
+                match index.html_runs.last_mut() {
+                    Some(run) if previous_was_html => run.end = range.end,
+                    _ => index.html_runs.push(range.clone()),
+                }
+                // an html block is a run of html events at the root
+                if depth == 0 {
+                    match index.top_level_blocks.last_mut() {
+                        Some(block) if previous_was_html => block.end = range.end,
+                        _ => index.top_level_blocks.push(range.clone()),
+                    }
+                }
+                index.html_events.push((range.clone(), index.html_runs.len() - 1));
+            }
+            previous_was_html = matches!(event, Event::Html(_));
+
+            match event {
+                Event::Start(_) => {
+                    if depth == 0 {
+                        index.top_level_blocks.push(range.clone())
+                    }
+                    depth += 1;
+                }
+                Event::End(_) => depth -= 1,
+                _ => (),
+            }
+
+            if in_heading {
+                let title = &mut index.headings.last_mut().unwrap().1;
+                match &event {
+                    Event::Text(s) | Event::Code(s) => title.push_str(s),
+                    Event::SoftBreak | Event::HardBreak => title.push(' '),
+                    _ => (),
+                }
+            }
+
+            if in_link {
+                let text = &mut index.links.last_mut().unwrap().text;
+                match &event {
+                    Event::Text(s) | Event::Code(s) => text.push_str(s),
+                    Event::SoftBreak | Event::HardBreak => text.push(' '),
+                    _ => (),
+                }
+            }
+
+            let closes_image = image_depth == 1 && matches!(event, Event::End(Tag::Image(..)));
+            if image_depth > 0 && !closes_image {
+                let image = index.images.last_mut().unwrap();
+                match &event {
+                    Event::Text(s) | Event::Code(s) => image.alt.push_str(s),
+                    Event::SoftBreak | Event::HardBreak => image.alt.push(' '),
+                    _ => (),
+                }
+                let alt_range = image.alt_range.get_or_insert(range.clone());
+                alt_range.end = alt_range.end.max(range.end);
+            }
+
+            if let (Event::Text(text), Some(code)) = (&event, index.code_spans.last()) {
+                if code.end == range.start {
+                    if let Some((annotation, language)) = language_annotation(text) {
+                        let annotation = range.start..range.start + annotation;
+                        index.code_languages.push((code.clone(), annotation, language.to_string()))
+                    }
+                }
+            }
+
+            if let Event::Text(text) = &event {
+                let written = &src[range.clone()];
+                if written.starts_with('&') && written.ends_with(';') && written != text.as_ref() {
+                    index.entities.push((range.clone(), text.to_string()))
+                }
+            }
+
+            match event {
+                Event::Start(Tag::Image(_, url, title)) => {
+                    if image_depth == 0 {
+                        index.images.push(ImageEntry {
+                            range,
+                            url: url.to_string(),
+                            title: title.to_string(),
+                            alt: String::new(),
+                            alt_range: None,
+                        })
+                    }
+                    image_depth += 1;
+                }
+                Event::End(Tag::Image(..)) => image_depth -= 1,
+                Event::Start(Tag::Heading(level, id, _)) => {
+                    index.headings.push((level as u8, String::new(), range, id.map(str::to_string)));
+                    in_heading = true;
+                }
+                Event::End(Tag::Heading(..)) => in_heading = false,
+                Event::Start(Tag::Paragraph) => in_paragraph = true,
+                Event::End(Tag::Paragraph) => in_paragraph = false,
+                Event::Html(html) => match parse_tag(&html) {
+                    Some(tag) if tag.kind == TagKind::Close => {
+                        // tags opened inside this one and never closed are ignored
+                        if let Some(depth) = open_tags.iter().rposition(|i| index.tags[*i].name == tag.name) {
+                            let open = &mut index.tags[open_tags[depth]];
+                            open.children = Some(open.range.end..range.start);
+                            open_tags.truncate(depth);
+                        }
+                    }
+                    Some(tag) => {
+                        if tag.kind == TagKind::Open {
+                            open_tags.push(index.tags.len())
+                        }
+                        index.tags.push(TagEntry {
+                            range,
+                            name: tag.name.to_string(),
+                            inline: in_paragraph,
+                            flags: tag.flags.iter().map(|f| f.to_string()).collect(),
+                            children: None,
+                        })
+                    }
+                    None => (),
+                },
+                Event::Text(text) => index.words += text.split_whitespace().count(),
+                Event::Code(_) => index.code_spans.push(range),
+                Event::FootnoteReference(label) => {
+                    let next = index.footnote_numbers.len() + 1;
+                    let (_, count) = index.footnote_numbers.entry(label.to_string()).or_insert((next, 0));
+                    index.footnote_references.push((range, label.to_string(), *count));
+                    *count += 1;
+                }
+                Event::Start(Tag::FootnoteDefinition(label)) => {
+                    index.footnote_definitions.push((range, label.to_string()))
+                }
+                Event::Start(Tag::BlockQuote) => {
+                    if let Some((kind, marker)) = alert_marker(&src[range.clone()]) {
+                        let marker = range.start + marker.start..range.start + marker.end;
+                        index.alerts.push((range, kind, marker))
+                    }
+                }
+                Event::Start(Tag::List(start)) => lists.push(start.is_some()),
+                Event::End(Tag::List(_)) => {lists.pop();},
+                Event::Start(Tag::Item) => {
+                    if lists.last() == Some(&true) {
+                        if let Some(n) = authored_number(&src[range.clone()]) {
+                            index.item_numbers.push((range.start, n))
+                        }
+                    }
+                    items.push(range);
+                }
+                Event::End(Tag::Item) => {items.pop();},
+                Event::Start(Tag::Link(link_type, url, _)) => {
+                    let kind = match link_type {
+                        _ if src[range.clone()].starts_with("[[") => LinkKind::Wikilink,
+                        LinkType::Inline => LinkKind::Inline,
+                        LinkType::Autolink => LinkKind::Autolink,
+                        LinkType::Email => LinkKind::Email,
+                        _ => LinkKind::Reference,
+                    };
+                    index.links.push(LinkEntry { range, url: url.to_string(), kind, text: String::new() });
+                    in_link = true;
+                }
+                Event::End(Tag::Link(..)) => in_link = false,
+                }
+                Event::TaskListMarker(checked) => {
+                    if let Some(item) = items.last() {
+                        index.tasks.push((item.clone(), checked, range))
+                    }
+                }
+                _ => ()
+            }
+        }
+
+        // components are rendered after their children, so an element comes after the ones it contains:
+        // ordered by their end, the innermost first when they end together
+        index.tags.sort_by_key(|tag| {
+            let end = tag.children.as_ref().map_or(tag.range.end, |children| children.end);
+            (end, std::cmp::Reverse(tag.range.start))
+        });
+
+        index
+    }
+
+    /// whether the byte at `offset` is inside an inline code span
+    pub fn in_code(&self, offset: usize) -> bool {
+        containing(&self.code_spans, offset, |r| r).is_some()
+    }
+
+    /// the language annotated on the code span containing the byte at `offset`
+    pub fn code_language(&self, offset: usize) -> Option<&str> {
+        containing(&self.code_languages, offset, |(code, _, _)| code)
+            .map(|(_, _, language)| language.as_str())
+    }
+
+    /// the length of the language annotation starting at `start`, if there is one
+    pub fn language_annotation(&self, start: usize) -> Option<usize> {
+        containing(&self.code_languages, start, |(_, annotation, _)| annotation)
+            .filter(|(_, annotation, _)| annotation.start == start)
+            .map(|(_, annotation, _)| annotation.len())
+    }
+
+    /// find the character reference decoded to `text`, searching from the `cursor`-th one.
+    /// Returns the position of the reference, to search the next one from there,
+    /// and its range in the source
+    pub fn entity(&self, cursor: usize, text: &str) -> Option<(usize, Range<usize>)> {
+        self.entities.iter()
+            .enumerate()
+            .skip(cursor)
+            .find(|(_, (_, decoded))| decoded == text)
+            .map(|(i, (range, _))| (i, range.clone()))
+    }
+
+    /// the task list item whose source range is `range`: its position among
+    /// the task items of the document, whether it is checked and the range of its marker
+    pub fn task(&self, range: &Range<usize>) -> Option<(usize, bool, Range<usize>)> {
+        let i = self.tasks.partition_point(|(item, _, _)| item.start < range.start);
+        let (item, checked, marker) = self.tasks.get(i)?;
+        (item == range).then(|| (i, *checked, marker.clone()))
+    }
+
+    /// whether the `i`-th task item is checked, and the range of its marker
+    pub fn task_at(&self, i: usize) -> Option<(bool, Range<usize>)> {
+        self.tasks.get(i).map(|(_, checked, marker)| (*checked, marker.clone()))
+    }
+
+    /// the plain text title of the heading whose source range is `range`
+    pub fn heading_title(&self, range: &Range<usize>) -> Option<&str> {
+        with_range(&self.headings, range, |(_, _, r, _)| r)
+            .map(|(_, title, _, _)| title.as_str())
+    }
+
+    /// the id given with a `{#id}` suffix to the heading whose source range is `range`
+    pub fn explicit_heading_id(&self, range: &Range<usize>) -> Option<&str> {
+        with_range(&self.headings, range, |(_, _, r, _)| r)
+            .and_then(|(_, _, _, id)| id.as_deref())
+    }
+
+    /// the table whose source range is `range`
+    pub fn table(&self, range: &Range<usize>) -> Option<&ExtractedTable> {
+        with_range(&self.tables, range, |table| &table.range)
+    }
+
+    /// the number written in the source for the ordered list item starting at `start`
+    pub fn item_number(&self, start: usize) -> Option<u64> {
+        let i = self.item_numbers.binary_search_by_key(&start, |(s, _)| *s).ok()?;
+        Some(self.item_numbers[i].1)
+    }
+
+    /// the position among the footnote references of the document of the one containing `offset`
+    pub fn footnote_reference(&self, offset: usize) -> Option<usize> {
+        let i = self.footnote_references.partition_point(|(range, _, _)| range.end <= offset);
+        self.footnote_references.get(i)
+            .is_some_and(|(range, _, _)| range.contains(&offset))
+            .then_some(i)
+    }
+
+    /// the range and label of the `i`-th footnote reference
+    pub fn footnote_reference_at(&self, i: usize) -> (Range<usize>, &str) {
+        let (range, label, _) = &self.footnote_references[i];
+        (range.clone(), label)
+    }
+
+    /// the label of the footnote definition whose source range is `range`
+    pub fn footnote_definition(&self, range: &Range<usize>) -> Option<&str> {
+        with_range(&self.footnote_definitions, range, |(r, _)| r)
+            .map(|(_, label)| label.as_str())
+    }
+
+    /// the number of the footnote `label`, in the order of first reference, starting from 1
+    pub fn footnote_number(&self, label: &str) -> Option<usize> {
+        self.footnote_numbers.get(label).map(|(number, _)| *number)
+    }
+
+    /// the number of references to `label` before the `i`-th footnote reference
+    pub fn footnote_occurrence(&self, i: usize) -> usize {
+        self.footnote_references[i].2
+    }
+
+    /// the labels of the footnote definitions that are never referenced
+    pub fn unused_footnotes(&self) -> impl Iterator<Item = &str> {
+        self.footnote_definitions.iter()
+            .map(|(_, label)| label.as_str())
+            .filter(|label| self.footnote_reference_count(label) == 0)
+    }
+
+    /// the number of references to `label`
+    pub fn footnote_reference_count(&self, label: &str) -> usize {
+        self.footnote_numbers.get(label).map_or(0, |(_, count)| *count)
+    }
+
+    /// the headings of the document: their level, plain text title and source range
+    pub fn headings(&self) -> impl Iterator<Item=(u8, &str, Range<usize>)> {
+        self.headings.iter().map(|(level, title, range, _)| (*level, title.as_str(), range.clone()))
+    }
+
+    /// the source ranges of the blocks at the root of the document
+    pub fn top_level_blocks(&self) -> &[Range<usize>] {
+        &self.top_level_blocks
+    }
+
+    /// the cursors of a render of the source from `offset`, past the entries before it
+    pub fn cursors_at(&self, offset: usize) -> Cursors {
+        Cursors {
+            html: self.html_events.partition_point(|(range, _)| range.start < offset),
+            images: self.images.partition_point(|image| image.range.start < offset),
+            links: self.links.partition_point(|link| link.range.start < offset),
+            tags: self.tags.partition_point(|tag| tag.children.as_ref().map_or(tag.range.end, |c| c.end) <= offset),
+            entities: self.entities.partition_point(|(range, _)| range.start < offset),
+            tasks: self.tasks.partition_point(|(_, _, marker)| marker.start < offset),
+        }
+    }
+
+    /// whether an html tag is open at `offset`, its closing tag coming after
+    pub fn in_tag(&self, offset: usize) -> bool {
+        self.tags.iter()
+            .any(|tag| tag.range.start < offset && tag.children.as_ref().is_some_and(|c| c.end >= offset))
+    }
+
+    /// the kind of alert of the block quote whose source range is `range`
+    pub fn alert(&self, range: &Range<usize>) -> Option<AlertKind> {
+        with_range(&self.alerts, range, |(r, _, _)| r)
+            .map(|(_, kind, _)| *kind)
+    }
+
+    /// whether the byte at `offset` is part of the marker of an alert
+    pub fn in_alert_marker(&self, offset: usize) -> bool {
+        containing(&self.alerts, offset, |(_, _, marker)| marker).is_some()
+    }
+
+    /// counts of the elements of the document, see `on_render_complete`
+    pub fn stats(&self) -> RenderStats {
+        RenderStats {
+            blocks: self.top_level_blocks.len(),
+            headings: self.headings.len(),
+            links: self.links.len(),
+            images: self.images.len(),
+            code_blocks: self.code_blocks,
+            words: self.words,
+        }
+    }
+
+    /// whether `range` is the range of a block at the root of the document
+    pub fn is_top_level_block(&self, range: &Range<usize>) -> bool {
+        self.top_level_blocks.binary_search_by_key(&range.start, |r| r.start)
+            .is_ok_and(|i| self.top_level_blocks[i] == *range)
+    }
+
+    /// find the image with `url`, searching from the `cursor`-th one.
+    /// Returns the position of the image, to search the next one from there.
+    pub fn image(&self, cursor: usize, url: &str) -> Option<(usize, &ImageEntry)> {
+        self.images.iter()
+            .enumerate()
+            .skip(cursor)
+            .find(|(_, image)| image.url == url)
+    }
+
+    /// find the link to `url`, searching from the `cursor`-th one.
+    /// Returns the position of the link, to search the next one from there.
+    pub fn link(&self, cursor: usize, url: &str) -> Option<(usize, &LinkEntry)> {
+        self.links.iter()
+            .enumerate()
+            .skip(cursor)
+            .find(|(_, link)| link.url == url)
+    }
+
+    /// whether the byte at `offset` is in the text of an autolink, which is an url or an email address
+    pub fn in_autolink(&self, offset: usize) -> bool {
+        containing(&self.links, offset, |link| &link.range)
+            .is_some_and(|link| matches!(link.kind, LinkKind::Autolink | LinkKind::Email))
+    }
+
+    /// find the opening tag named `name`, searching from the `cursor`-th tag in rendering order.
+    /// Returns the position of the tag, to search the next one from there.
+    pub fn tag(&self, cursor: usize, name: &str) -> Option<(usize, &TagEntry)> {
+        self.tags.iter()
+            .enumerate()
+            .skip(cursor)
+            .find(|(_, tag)| tag.name == name)
+    }
+
+    /// find the raw html event rendered as `html`, searching from the `cursor`-th one.
+    /// Returns the position of the event, to search the next one from there.
+    pub fn html_event(&self, src: &str, cursor: usize, html: &str) -> Option<(usize, HtmlPart)> {
+        let (i, (range, run)) = self.html_events.iter()
+            .enumerate()
+            .skip(cursor)
+            .find(|(_, (range, _))| src[range.clone()] == *html)?;
+
+        let run_range = self.html_runs[*run].clone();
+        let part = if run_range.start == range.start {
+            HtmlPart::RunStart(run_range)
+        } else {
+            HtmlPart::Merged
+        };
+        Some((i, part))
+    }
+}
+
+/// the entry of `entries`, sorted by start, whose range is `range`
+fn with_range<'e, T>(entries: &'e [T], range: &Range<usize>, key: impl Fn(&T) -> &Range<usize>) -> Option<&'e T> {
+    let i = entries.partition_point(|e| key(e).start < range.start);
+    entries.get(i).filter(|e| key(e) == range)
+}
+
+/// the entry of `entries`, sorted and without overlap, whose range contains `offset`
+fn containing<'e, T>(entries: &'e [T], offset: usize, key: impl Fn(&T) -> &Range<usize>) -> Option<&'e T> {
+    let i = entries.partition_point(|e| key(e).end <= offset);
+    entries.get(i).filter(|e| key(e).contains(&offset))
+}
+
+/// a language annotation like `{.rust}` at the start of `text`:
+/// its length and the language
+fn language_annotation(text: &str) -> Option<(usize, &str)> {
+    let inner = text.strip_prefix("{.")?;
+    let end = inner.find('}')?;
+    let language = &inner[..end];
+    let valid = !language.is_empty()
+        && language.chars().all(|c| c.is_ascii_alphanumeric() || "+-_#".contains(c));
+    valid.then_some((end + 3, language))
+}
+
+/// the number of an ordered list item, like `3.` or `3)`
+fn authored_number(item: &str) -> Option<u64> {
+    let item = item.trim_start();
+    let digits = item.find(|c: char| !c.is_ascii_digit()).unwrap_or(item.len());
+    item[..digits].parse().ok()
+}
+
+/// the position of `text` inside `src`, if `text` is a slice of `src`.
+/// Text events borrowed from the source can be located this way,
+/// text that was transformed by the parser cannot.
+pub(crate) fn offset_in(src: &str, text: &str) -> Option<usize> {
+    let start = src.as_ptr() as usize;
+    let ptr = text.as_ptr() as usize;
+    if ptr >= start && ptr + text.len() <= start + src.len() {
+        Some(ptr - start)
+    }
+    else {
+        None
+    }
+}
+
+/// the kind of alert of a block quote whose source is `quote`,
+/// if its first line is only a marker like `[!NOTE]`, and the range of the marker
+fn alert_marker(quote: &str) -> Option<(AlertKind, Range<usize>)> {
+    let line = quote.lines().next()?;
+    let content = line.trim_start().strip_prefix('>')?.trim();
+    let kind = AlertKind::of(content.strip_prefix("[!")?.strip_suffix(']')?)?;
+    let start = line.find('[')?;
+    Some((kind, start..start + content.len()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn index(src: &str) -> DocumentIndex {
+        DocumentIndex::new(src, Options::all(), false)
+    }
+
+    #[test]
+    fn footnotes_are_numbered_by_first_reference() {
+        let src = "b[^b] a[^a] b[^b]\n\n[^a]: A\n[^b]: B\n";
+        let index = index(src);
+        assert_eq!(index.footnote_number("b"), Some(1));
+        assert_eq!(index.footnote_number("a"), Some(2));
+        assert_eq!(index.footnote_reference_count("b"), 2);
+
+        let last = index.footnote_reference(src.find(" b[^b]").unwrap() + 2).unwrap();
+        assert_eq!(index.footnote_reference_at(last).1, "b");
+        assert_eq!(index.footnote_occurrence(last), 1);
+        assert_eq!(index.footnote_reference(0), None);
+    }
+
+    #[test]
+    fn unused_footnotes_are_listed() {
+        let index = index("a[^a]\n\n[^a]: A\n[^b]: B\n[^c]: C\n");
+        assert_eq!(index.unused_footnotes().collect::<Vec<_>>(), ["b", "c"]);
+    }
+
+    #[test]
+    fn nested_tasks_are_found_by_range() {
+        let src = "- [ ] outer\n  - [x] inner\n- [x] last\n";
+        let index = index(src);
+        for (i, task) in index.tasks.iter().enumerate() {
+            assert_eq!(index.task(&task.0).map(|(n, checked, _)| (n, checked)), Some((i, task.1)));
+        }
+        assert_eq!(index.tasks.len(), 3);
+        assert_eq!(index.task(&(1..4)), None);
+    }
+
+    #[test]
+    fn alt_text_is_flattened() {
+        let src = "![*rich* `alt` ![nested](n.png)](x.png)";
+        let index = index(src);
+        let (_, image) = index.image(0, "x.png").unwrap();
+        assert_eq!(image.alt, "rich alt nested");
+        assert_eq!(&src[image.alt_range.clone().unwrap()], "*rich* `alt` ![nested](n.png)");
+    }
+
+    #[test]
+    fn code_spans_are_found_by_offset() {
+        let src = "a `b` c `d`";
+        let index = index(src);
+        assert!(index.in_code(src.find('b').unwrap()));
+        assert!(!index.in_code(src.find('c').unwrap()));
+        assert!(index.in_code(src.find('d').unwrap()));
+        assert!(!index.in_code(src.len()));
+    }
+
+    #[test]
+    fn alerts_are_found_by_their_marker() {
+        let src = "> [!warning]\n> careful\n\n> [!UNKNOWN]\n> text\n";
+        let index = index(src);
+        assert_eq!(index.alerts.len(), 1);
+        assert_eq!(index.alert(&index.alerts[0].0), Some(AlertKind::Warning));
+        assert!(index.in_alert_marker(src.find("warning").unwrap()));
+        assert!(!index.in_alert_marker(src.find("careful").unwrap()));
+    }
+}
@@ -3,7 +3,67 @@ use rust_web_markdown::{
     CowStr,
 };
 
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, HashSet};
+use std::cell::{Cell, OnceCell, RefCell};
+
+mod a11y;
+mod allowlist;
+mod autolink;
+mod builtins;
+mod cache;
+mod capabilities;
+mod chunk;
+mod clipboard;
+mod code;
+mod components;
+mod definition;
+mod delegate;
+#[cfg(feature="detect-language")]
+mod detect;
+mod directive;
+mod emoji;
+mod extract;
+mod fingerprint;
+mod highlight;
+mod html;
+mod hydrate;
+mod index;
+mod links;
+mod loading;
+mod mark;
+mod outline;
+mod rewrite;
+mod scroll;
+mod selection;
+mod text;
+mod tree;
+mod visibility;
+#[cfg(feature="ssr")]
+mod ssr;
+
+pub use a11y::A11yConfig;
+pub use allowlist::{ElementAllowlist, ElementFilter, TableFallback};
+pub use text::RubySegment;
+pub use chunk::ChunkConfig;
+pub use clipboard::ClipboardSink;
+pub use capabilities::{Capabilities, DegradationReport, DisabledFeature};
+pub use links::MountedLink;
+#[cfg(feature="detect-language")]
+pub use detect::detect_language;
+pub use outline::{OutlineHeading, OutlineChange, OutlineDelta};
+pub use selection::selection_to_source_range;
+pub use scroll::ScrollState;
+pub use hydrate::{HydratedMarkdown, HydratedMarkdownProps};
+pub use components::{CustomComponents, ComponentRegistry, ComponentMeta, AttributeMeta, ComponentPropsExt, AttributeReader, use_markdown_components};
+pub use cache::{CacheKey, RenderCache, LruRenderCache, content_hash};
+pub use tree::{parse_to_tree, ParseConfig, MdNode, NodeKind, Alignment};
+pub use extract::{extract_tables, ExtractedTable, extract_tasks, TaskItem};
+#[cfg(feature="ssr")]
+pub use ssr::{render_to_string, render_to_hydratable_string};
+
+use index::{DocumentIndex, HtmlPart, LinkEntry, TagEntry};
+use emoji::EmojiPiece;
+use text::TextPiece;
 
 pub type MdComponentProps<'a> = rust_web_markdown::MdComponentProps<Element<'a>>;
 
@@ -18,6 +78,9 @@ pub use rust_web_markdown::{
 };
 
 use dioxus::prelude::*;
+use dioxus::html::prelude::EvalProvider;
+use dioxus::html::input_data::keyboard_types::Modifiers;
+use dioxus::html::input_data::MouseButton;
 
 use std::rc::Rc;
 
@@ -29,97 +92,2341 @@ pub mod debug {
     pub struct EventInfo(pub Vec<String>);
 }
 
+/// helpers to test custom components without rendering any markdown
+#[cfg(feature="testing")]
+pub mod testing {
+    use super::*;
+
+    /// build the props a custom component receives when called
+    /// with `attributes` and `children` from markdown.
+    /// Pass `None` as children for self-closing tags
+    pub fn component_props<'a, I, K, V>(attributes: I, children: Element<'a>) -> MdComponentProps<'a>
+        where I: IntoIterator<Item=(K, V)>, K: Into<String>, V: Into<String>
+    {
+        MdComponentProps {
+            attributes: attributes.into_iter()
+                .map(|(k, v)| (k.into(), v.into()))
+                .collect(),
+            children,
+        }
+    }
+
+    /// the stylesheets mounted by `Markdown` components of this app, in mount order
+    pub fn mounted_links() -> Vec<MountedLink> {
+        links::mounted_links()
+    }
+}
+
 
 #[derive(Props)]
 pub struct MdProps<'a> {
     src: &'a str,
 
-    /// the callback called when a component is clicked.
-    /// if you want to controll what happens when a link is clicked,
-    /// use [`render_links`][render_links]
-    on_click: Option<EventHandler<'a, MarkdownMouseEvent>>,
+    /// the callback called when a component is clicked.
+    /// if you want to controll what happens when a link is clicked,
+    /// use [`render_links`][render_links].
+    on_click: Option<EventHandler<'a, MarkdownMouseEvent>>,
+
+    /// 
+    render_links: Option<HtmlCallback<'a, LinkDescription<Element<'a>>>>,
+
+    /// the href of wikilinks, from their target.
+    /// By default the target is used as is
+    resolve_wikilink: Option<Rc<dyn Fn(&str) -> String>>,
+
+    /// whether the page a wikilink points to exists, from its target.
+    /// Links to missing pages get the `missing_wikilink_class`
+    wikilink_exists: Option<Rc<dyn Fn(&str) -> bool>>,
+
+    /// the class of links to missing pages, `wikilink-missing` by default
+    missing_wikilink_class: Option<String>,
+
+    /// set after each render to the targets of the wikilinks to missing pages
+    broken_links: Option<UseState<Vec<String>>>,
+
+    /// like `render_links`, with how the link is written in the source.
+    /// Takes precedence over `render_links`
+    render_links_with_details: Option<HtmlCallback<'a, LinkDetails<'a>>>,
+
+    /// custom renderer for images, instead of a bare `img`.
+    /// Images inside links are still wrapped in the link
+    render_images: Option<HtmlCallback<'a, ImageDescription<'a>>>,
+
+    /// custom renderer for fenced and indented code blocks,
+    /// used instead of highlighting them with syntect:
+    /// the code of the blocks is never highlighted.
+    /// Inline code is not affected
+    render_code_blocks: Option<HtmlCallback<'a, CodeBlockDescription>>,
+
+    /// guess the language of fenced code blocks without info string,
+    /// to highlight them. The `detect-language` feature provides a simple `detect_language`
+    detect_language: Option<Rc<dyn Fn(&str) -> Option<String>>>,
+
+    /// custom renderer for headings, instead of `h1`..`h6`
+    render_headings: Option<HtmlCallback<'a, HeadingDescription<'a>>>,
+
+    /// replace pieces of text, for example to turn `@mentions` into links.
+    /// Called with the text and its range in the markdown source;
+    /// returning `None` leaves the text to `emoji`, `ruby_annotator` and `break_long_words`.
+    /// Code is not passed to it, nor text transformed by the parser like character references.
+    ///
+    /// The result is rendered inside the element the text belongs to,
+    /// so clicks on it are also reported to `on_click`.
+    /// Handlers in the result can stop the propagation of the event to prevent that.
+    transform_text: Option<Rc<dyn Fn(&'a ScopeState, &str, Range<usize>) -> Option<Element<'a>>>>,
+
+    /// rendered in place of custom components that return an error.
+    /// By default, the name of the component and the error are displayed inline
+    render_component_error: Option<HtmlCallback<'a, ComponentError>>,
+
+    /// custom renderer for inline and display math
+    render_math: Option<HtmlCallback<'a, MathDescription>>,
+
+    /// render math with katex, which needs the `katex` feature.
+    /// The katex stylesheet is mounted once in the page, whatever the number of `Markdown`,
+    /// and the html of the expressions is taken from `cache` when it has it.
+    /// Otherwise, math is displayed as written, unless `render_math` is set
+    #[props(default)]
+    math: bool,
+
+    /// custom renderer for footnote references
+    render_footnote_reference: Option<HtmlCallback<'a, FootnoteReferenceDescription>>,
+
+    /// custom renderer for footnote definitions
+    render_footnote_definition: Option<HtmlCallback<'a, FootnoteDefinitionDescription<'a>>>,
+
+    /// custom renderer for the content of task list items, including the checkbox
+    render_tasklist_item: Option<HtmlCallback<'a, TaskListItemDescription<'a>>>,
+
+    /// called when the checkbox of a task list item is clicked, after `on_click`.
+    /// Not called for the markdown rendered by components, whose tasks are not in `src`
+    on_checkbox_toggle: Option<EventHandler<'a, CheckboxToggleEvent>>,
+
+    /// the state holding the markdown source, typically the one `src` is read from.
+    /// When set, clicking the checkbox of a task list item checks or unchecks it in the source.
+    /// The markdown rendered by components is not synced, its tasks are not in `src`
+    task_list_sync: Option<UseState<String>>,
+
+    /// custom renderer for block quotes, called for each level of nested quotes
+    render_blockquotes: Option<HtmlCallback<'a, BlockquoteDescription<'a>>>,
+
+    /// custom renderer for tables, called once per table with its rendered cells
+    render_tables: Option<HtmlCallback<'a, TableDescription<'a>>>,
+
+    /// the name of the theme used for syntax highlighting.
+    /// Only the default themes of [syntect::Theme] are supported
+    theme: Option<String>,
+
+    /// highlight code with css classes prefixed with the name of the `theme`, like `hl-base16-ocean-light-keyword`,
+    /// instead of inline styles, so that instances with different themes do not collide.
+    /// The stylesheet of a theme is added to the document once for all the instances using it,
+    /// or rendered before the first code block on the server
+    #[props(default = false)]
+    highlight_classes: bool,
+
+    /// wether to enable wikilinks support.
+    /// Wikilinks look like [[shortcut link]] or [[url|name]]
+    #[props(default = false)]
+    wikilinks: bool,
+
+    /// wether to convert soft breaks to hard breaks.
+    #[props(default = false)]
+    hard_line_breaks: bool,
+
+    /// pulldown_cmark options.
+    /// See [`Options`][pulldown_cmark_wikilink::Options] for reference.
+    /// Defaults to every option, but `smart_punctuation`, `tables`, `strikethrough`, `task_lists` and `footnotes`,
+    /// which are added by their props when true: set a prop to false to leave its option
+    /// to `parse_options`.
+    parse_options: Option<Options>,
+
+    /// turn straight quotes into curly ones, `--` and `---` into dashes and `...` into an ellipsis,
+    /// outside of code, even if `parse_options` is set.
+    /// When false, `parse_options` decides
+    #[props(default = true)]
+    smart_punctuation: bool,
+
+    /// parse GFM tables, even if `parse_options` is set.
+    /// When false, `parse_options` decides
+    #[props(default = true)]
+    tables: bool,
+
+    /// parse `~~strikethrough~~`, even if `parse_options` is set.
+    /// When false, `parse_options` decides
+    #[props(default = true)]
+    strikethrough: bool,
+
+    /// parse `- [ ]` and `- [x]` list items as tasks, even if `parse_options` is set.
+    /// When false, `parse_options` decides
+    #[props(default = true)]
+    task_lists: bool,
+
+    /// parse footnote references and definitions, even if `parse_options` is set.
+    /// When false, `parse_options` decides
+    #[props(default = true)]
+    footnotes: bool,
+
+    #[props(default)]
+    components: CustomComponents<'a>,
+
+    /// components that can be registered after the first render.
+    /// `components` take precedence over them.
+    /// Defaults to the registry provided by an ancestor with [`use_markdown_components`]
+    registry: Option<ComponentRegistry>,
+
+    /// render `:::` containers with the component named like them:
+    /// `::: warning Be careful` renders the `warning` component
+    /// with `Be careful` as its `title` attribute, and the content as children.
+    /// Containers nest by using more colons for the outer ones.
+    /// Without a `warning` component, it renders a `div` with the `warning` class,
+    /// and `::: details Title` renders a collapsible section, see `on_section_toggle`
+    #[props(default = false)]
+    directives: bool,
+
+    /// called when a collapsible section is opened or closed.
+    /// Their open state is kept between renders
+    on_section_toggle: Option<EventHandler<'a, SectionToggleEvent>>,
+
+    /// the ids of the collapsible sections open on the first render, see [`SectionToggleEvent::id`]
+    initial_open_sections: Option<Vec<String>>,
+
+    /// how many times markdown can be nested in itself with [`MdRenderer`],
+    /// to stop components whose markdown contains themselves
+    #[props(default = 8)]
+    max_nesting_depth: usize,
+
+    /// render definition lists, written as a term followed by lines starting with `: `.
+    /// The definitions can continue with indented lines, including other paragraphs
+    #[props(default)]
+    definition_lists: bool,
+
+    /// render block quotes starting with `[!NOTE]`, `[!TIP]`, `[!IMPORTANT]`, `[!WARNING]` or `[!CAUTION]`
+    /// as GitHub-style alerts: a `div.markdown-alert.markdown-alert-note` (for a note)
+    /// with a `p.markdown-alert-title` before the content
+    #[props(default)]
+    alerts: bool,
+
+    /// replace shortcodes like `:smile:` in text with their emoji.
+    /// The built-in shortcodes need the `emoji` feature,
+    /// unknown shortcodes and the text of code are left as they are
+    #[props(default)]
+    emoji: bool,
+
+    /// turn the urls (`https://example.com`, `www.example.com`) and email addresses
+    /// written in text into links, like GFM does. They are rendered like other links,
+    /// with `render_links` and `on_link_click` if set
+    #[props(default)]
+    autolinks: bool,
+
+    /// render `==text==` as highlighted text, in a `mark` element.
+    /// The delimiters must be on the same line
+    #[props(default)]
+    mark: bool,
+
+    /// more shortcodes for `emoji`, without colons, with the url of their image.
+    /// They take precedence over the built-in ones
+    custom_emoji: Option<BTreeMap<String, String>>,
+
+    frontmatter: Option<UseState<String>>,
+
+    /// called with the raw frontmatter on each render of a document that has one.
+    /// Can be used together with `frontmatter`
+    on_frontmatter: Option<EventHandler<'a, String>>,
+
+    /// insert line break opportunities (`<wbr>`) in words longer than this
+    /// number of characters, so that long urls or hashes do not overflow.
+    /// Applies to text and link text, never to code or link targets.
+    break_long_words: Option<usize>,
+
+    /// vertical offset in pixels kept above anchored elements
+    /// (headings, footnotes and footnote references) when navigating to them, for example to account for a sticky header.
+    /// It is emitted as `scroll-margin-top`, so native fragment navigation respects it.
+    scroll_offset: Option<f64>,
+
+    /// the css selector of the element the document scrolls in, see `scroll_state`
+    scroll_container: Option<String>,
+
+    /// set to the block at the top of `scroll_container` as it scrolls, a few times per second.
+    /// When the document is mounted, the container is scrolled back to it.
+    /// The blocks get their `data-sourcepos` attribute, see `source_positions`.
+    /// Without a container or javascript, nothing is done
+    scroll_state: Option<UseState<ScrollState>>,
+
+    /// cache for the results of expensive sub-renders: highlighted code and math.
+    /// Share one between several `Markdown` components to reuse their work.
+    cache: Option<Rc<dyn RenderCache>>,
+
+    /// where the markdown is rendered.
+    /// If unset, a [`RenderEnv`] provided as context is used,
+    /// and the client is assumed otherwise.
+    render_env: Option<RenderEnv>,
+
+    /// keep the numbers written by the author for ordered list items,
+    /// instead of numbering them sequentially from the start of the list.
+    #[props(default = false)]
+    respect_item_numbers: bool,
+
+    /// emit roles and labels for the elements screen readers can not describe:
+    /// math, the footnotes section and the table of contents.
+    /// Decorative elements like heading anchors and the icons of copy buttons
+    /// are always hidden from screen readers
+    accessibility: Option<A11yConfig>,
+
+    /// render each block of raw html as a whole, inside a single `div`,
+    /// instead of rendering every html tag separately.
+    /// This allows markup spanning several lines, like tables,
+    /// to be rendered correctly.
+    ///
+    /// **Unsafe for untrusted input**: the html is inserted as is,
+    /// scripts and event attributes included.
+    #[props(default = false)]
+    trusted_html: bool,
+
+    /// the kinds of elements that can be rendered.
+    /// See [`ElementAllowlist`] for how disallowed elements are rendered.
+    allowed_elements: Option<ElementAllowlist>,
+
+    /// only produce phrasing content, so that the markdown can be
+    /// rendered inside a button, a label or any inline element.
+    /// Block elements degrade as follows:
+    /// - headings render as bold text
+    /// - code blocks render as inline code
+    /// - list items render as spans separated by a space
+    /// - paragraphs, block quotes, lists and tables render as spans
+    /// - horizontal rules are skipped
+    #[props(default = false)]
+    inline_mode: bool,
+
+    /// how soft line breaks (a single newline inside a paragraph) are rendered.
+    /// `hard_line_breaks: true` is equivalent to [`SoftBreakMode::Br`].
+    #[props(default)]
+    soft_break: SoftBreakMode,
+
+    /// rendered instead of the whole markdown when rendering panics.
+    /// By default, the panic message is shown in debug builds
+    /// and a generic message in release builds.
+    /// With the `debug` feature, the message is also added to the debug info.
+    ///
+    /// Panics are caught with `catch_unwind`, which does nothing when they abort:
+    /// with `panic = "abort"` and on `wasm32-unknown-unknown`, the web target,
+    /// a panic stops the app and this is never rendered.
+    /// Custom components should return an error instead of panicking,
+    /// it is rendered in their place on every target, see `render_component_error`.
+    error_boundary: Option<HtmlCallback<'a, RenderFailure>>,
+
+    /// called with the href of a link when the user shows an intent
+    /// to follow it (hover or focus), for example to prefetch it.
+    /// It is called once per href and per render.
+    on_link_intent: Option<EventHandler<'a, String>>,
+
+    /// called with the href of a link when it first scrolls into view,
+    /// for example to prefetch it. It is called once per href.
+    /// Needs javascript, it is never called on other renderers
+    on_link_visible: Option<EventHandler<'a, String>>,
+
+    /// called when an element is double clicked, like `on_click`
+    on_double_click: Option<EventHandler<'a, MarkdownMouseEvent>>,
+
+    /// called when the context menu of an element is requested, usually with a right click.
+    /// When it is set, the context menu of the browser is not displayed
+    on_context_menu: Option<EventHandler<'a, MarkdownMouseEvent>>,
+
+    /// called when the mouse enters an element, with its range in the source.
+    /// The event does not bubble: entering a nested element calls it
+    /// for each element entered, each time with the range of that element
+    on_mouse_enter: Option<EventHandler<'a, MarkdownMouseEvent>>,
+
+    /// called when the mouse leaves an element, see `on_mouse_enter`
+    on_mouse_leave: Option<EventHandler<'a, MarkdownMouseEvent>>,
+
+    /// called when a link is clicked, for example to route internal links in the app.
+    /// The browser follows the link after it, unless
+    /// [`prevent_default`](LinkClickEvent::prevent_default) is called.
+    /// Links rendered by `render_links` are not concerned
+    on_link_click: Option<EventHandler<'a, LinkClickEvent>>,
+
+    /// split text in segments annotated with their reading,
+    /// rendered as `<ruby>` elements (for example furigana over kanji).
+    /// Only text is annotated, never code, urls or component attributes.
+    /// Applied after `emoji` and before `break_long_words`
+    ruby_annotator: Option<Rc<dyn Fn(&str) -> Vec<RubySegment>>>,
+
+    /// show a drag handle on each top level block, to reorder them.
+    /// Dropping a block calls `on_block_reorder`,
+    /// the source is not modified by the component.
+    #[props(default = false)]
+    block_drag: bool,
+
+    /// called when a block is dropped onto another one with its drag handle
+    on_block_reorder: Option<EventHandler<'a, BlockReorderEvent>>,
+
+    /// where copy features write to.
+    /// If unset, `navigator.clipboard` is used on web,
+    /// and copy features are hidden on renderers without javascript.
+    clipboard: Option<Rc<dyn ClipboardSink>>,
+
+    /// show a `button.md-copy` copying the code of each code block,
+    /// if there is a clipboard to copy to
+    #[props(default = false)]
+    copy_buttons: bool,
+
+    /// show an edit button on each top level block, calling `on_block_edit_request`.
+    /// The source is not modified by the component.
+    #[props(default = false)]
+    editable_blocks: bool,
+
+    /// called when the edit button of a block is clicked
+    on_block_edit_request: Option<EventHandler<'a, BlockEditRequest>>,
+
+    /// convert `\r\n` line endings to `\n` before parsing.
+    /// The ranges reported in events still refer to the original `src`.
+    #[props(default = false)]
+    normalize_newlines: bool,
+
+    /// render long documents progressively: the first blocks immediately,
+    /// and the next ones in later renders, so that input is not blocked.
+    /// Each chunk is rendered once, by its own component,
+    /// and its references resolve against the whole document.
+    /// Rendering starts over when `src` changes.
+    chunked_render: Option<ChunkConfig>,
+
+    /// highlight inline code followed by a language annotation,
+    /// like `` `let x = 1;`{.rust} ``, with the `theme`.
+    /// The annotation is not displayed.
+    #[props(default = false)]
+    inline_code_languages: bool,
+
+    /// called once per app if features were disabled
+    /// because the renderer can not evaluate javascript,
+    /// with the list of these features and what they do instead
+    on_degraded: Option<EventHandler<'a, DegradationReport>>,
+
+    /// remove the stylesheets mounted for this component from the document
+    /// when it is the last mounted component that needs them.
+    /// By default they stay until the app is closed
+    #[props(default = false)]
+    unmount_unused: bool,
+
+    /// html for code blocks highlighted ahead of time,
+    /// placed inside `<pre><code>` as is instead of highlighting the code with syntect.
+    /// Blocks for which it returns `None` are highlighted as usual,
+    /// so syntect is not used if it always returns some html.
+    /// The html is trusted, it must not come from the document.
+    prehighlighted_code: Option<Rc<dyn Fn(&CodeBlockDescription) -> Option<String>>>,
+
+    /// display code blocks as plain text first, and highlight them after the document is displayed,
+    /// a few at a time. Only the colors change, the text and the copy buttons are the same.
+    /// Ignored on the server
+    #[props(default = false)]
+    defer_highlighting: bool,
+
+    /// elements displayed at the end of some lines of code blocks, by line number starting from 1,
+    /// like `← note the lifetime here`. They are not part of the copied code.
+    /// Blocks with annotations are highlighted line by line, without `prehighlighted_code`
+    code_line_annotations: Option<Rc<dyn Fn(&'a ScopeState, &CodeBlockDescription) -> BTreeMap<usize, Element<'a>>>>,
+
+    /// how tables are rendered when `allowed_elements` excludes them,
+    /// or in inline mode
+    #[props(default)]
+    table_fallback: TableFallback,
+
+    /// whether clicks propagate outside of the clicked element.
+    /// With `delegate_clicks`, they only leave the document with `AlwaysPropagate`
+    #[props(default)]
+    click_propagation: ClickPropagation,
+
+    /// the kinds of elements whose clicks are reported to `on_click`, every kind if unset
+    click_targets: Option<ElementFilter>,
+
+    /// receive the clicks of the whole document with a single listener on its root,
+    /// instead of a listener per element, when the renderer can evaluate javascript.
+    /// This makes renders of long documents with `on_click` cheaper, but `on_click`
+    /// is only called in the next render, once javascript found the clicked element,
+    /// and the propagation of a click is decided before: see `click_propagation`
+    #[props(default = false)]
+    delegate_clicks: bool,
+
+    /// add the range of each element in the markdown source
+    /// as its `data-md-start` and `data-md-end` attributes,
+    /// see [`selection_to_source_range`]
+    #[props(default)]
+    source_attributes: bool,
+
+    /// add the range of each block element in the markdown source
+    /// as its `data-sourcepos` attribute, like `12-40`,
+    /// to map the source to the rendered blocks (for synced scrolling for example)
+    #[props(default)]
+    source_positions: bool,
+
+    /// give headings their id from the outline, for links like `[see below](#installation)`.
+    /// The id is the `{#id}` suffix of the heading if it has one,
+    /// else the GitHub style slug of its title, followed by `-1`, `-2`... for duplicates.
+    /// Not applied to headings rendered by `render_headings`
+    #[props(default)]
+    heading_ids: bool,
+
+    /// the slug of a heading title, to replace the default GitHub style one
+    slugify: Option<Rc<dyn Fn(&str) -> String>>,
+
+    /// like `heading_ids`, and append an `a.md-anchor` link to the id
+    /// to the content of headings. The link is hidden from screen readers,
+    /// style `:hover > .md-anchor` to only show it on hover.
+    /// Clicking it, or pressing Enter on it, follows it and copies the url of the heading to `clipboard`,
+    /// then calls `on_anchor_copied`. Clicks with a modifier or the middle button only open the link
+    #[props(default)]
+    heading_anchors: bool,
+
+    /// the url of the document, the heading anchors copy it followed by `#id`.
+    /// If unset, the current location is used on web
+    canonical_url: Option<String>,
+
+    /// called with the url copied by a heading anchor.
+    /// Without `canonical_url` on renderers without javascript, nothing is copied
+    /// and it is called with the id of the heading
+    on_anchor_copied: Option<EventHandler<'a, String>>,
+
+    /// called with the headings inserted, removed, retitled or moved
+    /// since the previous render. On the first render, every heading is inserted
+    on_outline_change: Option<EventHandler<'a, OutlineDelta>>,
+
+    /// called once the document is displayed, after the first render
+    /// and after each render for a new `src`
+    on_render_complete: Option<EventHandler<'a, RenderStats>>,
+
+    /// render character references like `&copy;` as the character they stand for.
+    /// If false, they are displayed as written.
+    /// Headings ids and plain text always use the decoded characters,
+    /// and code is always displayed as written
+    #[props(default = true)]
+    decode_entities: bool,
+}
+
+/// the kind of a top level block
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum BlockKind {
+    Paragraph,
+    Heading(u8),
+    BlockQuote,
+    List,
+    Table,
+    Other,
+}
+
+impl BlockKind {
+    fn of(e: &HtmlElement) -> Self {
+        match e {
+            HtmlElement::Paragraph => Self::Paragraph,
+            HtmlElement::Heading(level) => Self::Heading(*level as u8),
+            HtmlElement::BlockQuote => Self::BlockQuote,
+            HtmlElement::Ul | HtmlElement::Ol(_) => Self::List,
+            HtmlElement::Table => Self::Table,
+            _ => Self::Other,
+        }
+    }
+}
+
+/// a code block, as written in the markdown source.
+/// See `render_code_blocks` and `prehighlighted_code`
+#[derive(Clone, PartialEq, Debug)]
+pub struct CodeBlockDescription {
+    /// the first word of the info string of fenced blocks,
+    /// `None` for indented blocks.
+    /// For fenced blocks without info string, the language given by `detect_language`
+    pub language: Option<String>,
+    /// whether the language was detected rather than written by the author
+    pub language_detected: bool,
+    pub code: String,
+    /// the range of the block in the markdown source
+    pub range: Range<usize>,
+}
+
+/// a table of the document, see `render_tables`
+pub struct TableDescription<'a> {
+    pub alignments: Vec<Alignment>,
+    /// the rendered content of the header cells
+    pub headers: Vec<Element<'a>>,
+    /// the rendered content of the cells of each row
+    pub rows: Vec<Vec<Element<'a>>>,
+    /// the range of the table in the markdown source
+    pub range: Option<Range<usize>>,
+}
+
+/// how a link is written in the markdown source
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum LinkKind {
+    /// `[text](url)`
+    Inline,
+    /// `[text][label]`, `[label][]` or `[label]`
+    Reference,
+    /// `<https://example.com>`
+    Autolink,
+    /// `<someone@example.com>`
+    Email,
+    /// `[[target]]` or `[[target|label]]`
+    Wikilink,
+}
+
+/// a link, with how it is written in the source. See `render_links_with_details`
+pub struct LinkDetails<'a> {
+    pub link: LinkDescription<Element<'a>>,
+    pub kind: LinkKind,
+    /// the range of the link in the markdown source, if it could be found
+    pub range: Option<Range<usize>>,
+    /// for wikilinks, the target and the label, if different from the target
+    pub wikilink: Option<(String, Option<String>)>,
+    /// whether it is a wikilink to a missing page, see `wikilink_exists`
+    pub missing: bool,
+}
+
+/// a click on a link, see `on_link_click`
+#[derive(Clone, Debug)]
+pub struct LinkClickEvent {
+    /// the target of the link, after wikilinks are resolved
+    pub href: String,
+    /// the text of the link, as plain text
+    pub text: String,
+    pub wikilink: bool,
+    pub mouse_event: MouseEvent,
+    /// the markdown source of the render, see [`MarkdownMouseEvent::source`]
+    pub source: Rc<str>,
+    prevented: Rc<Cell<bool>>,
+}
+
+impl LinkClickEvent {
+    /// do not follow the link
+    pub fn prevent_default(&self) {
+        self.prevented.set(true)
+    }
+
+    pub fn default_prevented(&self) -> bool {
+        self.prevented.get()
+    }
+}
+
+/// a custom component failed to render, see `render_component_error`
+#[derive(Clone, PartialEq, Debug)]
+pub struct ComponentError {
+    /// the name of the component
+    pub name: String,
+    pub message: String,
+    /// the offset of the opening tag in the markdown source
+    pub offset: Option<usize>,
+    /// the line of the opening tag, starting at 1
+    pub line: Option<usize>,
+    /// the attribute the error is about, if the message names one
+    pub attribute: Option<String>,
+}
+
+impl std::fmt::Display for ComponentError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "<{}>", self.name)?;
+        if let Some(line) = self.line {
+            write!(f, " at line {line}")?;
+        }
+        write!(f, ": {}", self.message)
+    }
+}
+
+/// a math expression, see `render_math`
+#[derive(Clone, PartialEq, Debug)]
+pub struct MathDescription {
+    /// the TeX source, without delimiters
+    pub tex: String,
+    /// whether it is display math (`$$...$$`) rather than inline math
+    pub display: bool,
+    /// the range of the expression in the markdown source
+    pub range: Option<Range<usize>>,
+}
+
+/// a reference to a footnote, see `render_footnote_reference`
+#[derive(Clone, PartialEq, Debug)]
+pub struct FootnoteReferenceDescription {
+    pub label: String,
+    /// the number of the footnote, in the order of first reference, starting from 1
+    pub index: usize,
+    /// an id for this reference, distinct for each reference to the same footnote,
+    /// to link back from the definition
+    pub id: String,
+    /// the range of the reference in the markdown source
+    pub range: Range<usize>,
+}
+
+/// the definition of a footnote, see `render_footnote_definition`
+pub struct FootnoteDefinitionDescription<'a> {
+    pub label: String,
+    /// the number of the footnote, `None` if it is never referenced
+    pub index: Option<usize>,
+    /// the rendered body of the definition
+    pub content: Element<'a>,
+    /// the ids of the references to this footnote, in document order
+    pub back_references: Vec<String>,
+    /// the range of the definition in the markdown source
+    pub range: Range<usize>,
+}
+
+/// an item of a task list, see `render_tasklist_item`
+pub struct TaskListItemDescription<'a> {
+    pub checked: bool,
+    /// the rendered content of the item, without the checkbox
+    pub label: Element<'a>,
+    /// the position of the item among the task items of the document
+    pub index: usize,
+    /// the range of the `[ ]` or `[x]` marker in the markdown source
+    pub marker_range: Range<usize>,
+}
+
+/// a click on the checkbox of a task list item, see `on_checkbox_toggle`
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct CheckboxToggleEvent {
+    /// the position of the item among the task items of the document
+    pub index: usize,
+    /// the state of the checkbox after the click
+    pub checked: bool,
+    /// the range of the `[ ]` or `[x]` marker in the markdown source
+    pub marker_range: Range<usize>,
+    /// the markdown source of the render, see [`MarkdownMouseEvent::source`]
+    pub source: Rc<str>,
+}
+
+/// counts of the elements of a rendered document, see `on_render_complete`
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub struct RenderStats {
+    /// blocks at the root of the document
+    pub blocks: usize,
+    pub headings: usize,
+    pub links: usize,
+    pub images: usize,
+    pub code_blocks: usize,
+    /// words of the text, code excluded
+    pub words: usize,
+}
+
+/// a block quote of the document, see `render_blockquotes`
+pub struct BlockquoteDescription<'a> {
+    /// the rendered content of the quote, including nested quotes
+    pub content: Element<'a>,
+    /// the range of the quote in the markdown source
+    pub range: Option<Range<usize>>,
+    /// the kind of alert if `alerts` is set and the quote starts with a marker like `[!NOTE]`,
+    /// which is then left out of the content
+    pub alert: Option<AlertKind>,
+}
+
+/// the kind of a GitHub-style alert, see `alerts`
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum AlertKind {
+    Note,
+    Tip,
+    Important,
+    Warning,
+    Caution,
+}
+
+impl AlertKind {
+    /// the kind of alert of the marker `[!name]`, ignoring case
+    pub(crate) fn of(name: &str) -> Option<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "note" => Some(Self::Note),
+            "tip" => Some(Self::Tip),
+            "important" => Some(Self::Important),
+            "warning" => Some(Self::Warning),
+            "caution" => Some(Self::Caution),
+            _ => None,
+        }
+    }
+
+    /// the lowercase name of the alert, as used in its class
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Note => "note",
+            Self::Tip => "tip",
+            Self::Important => "important",
+            Self::Warning => "warning",
+            Self::Caution => "caution",
+        }
+    }
+
+    /// the title displayed above the content of the alert
+    pub fn title(self) -> &'static str {
+        match self {
+            Self::Note => "Note",
+            Self::Tip => "Tip",
+            Self::Important => "Important",
+            Self::Warning => "Warning",
+            Self::Caution => "Caution",
+        }
+    }
+}
+
+/// a heading of the document, see `render_headings`
+pub struct HeadingDescription<'a> {
+    /// from 1 to 6
+    pub level: u8,
+    /// the rendered content of the heading
+    pub content: Element<'a>,
+    /// the content as plain text
+    pub text: String,
+    /// the range of the heading in the markdown source
+    pub range: Option<Range<usize>>,
+}
+
+/// an image of the document, see `render_images`
+pub struct ImageDescription<'a> {
+    pub url: String,
+    /// the alt text, flattened to plain text
+    pub alt: String,
+    /// the alt text with its formatting, for example for a caption.
+    /// Nested images are flattened to their alt text
+    pub alt_content: Element<'a>,
+    pub title: String,
+    /// the range of the image in the markdown source, if it could be found
+    pub range: Option<Range<usize>>,
+}
+
+/// the user asked to edit a top level block
+#[derive(Clone, Debug)]
+pub struct BlockEditRequest {
+    pub kind: BlockKind,
+    /// the range of the block in the markdown source
+    pub range: Range<usize>,
+    /// the text of the block, without formatting
+    pub text: String,
+    /// the markdown source of the render, see [`MarkdownMouseEvent::source`]
+    pub source: Rc<str>,
+}
+
+/// a top level block was dragged to a new place
+#[derive(Clone, Debug)]
+pub struct BlockReorderEvent {
+    /// the range of the moved block in the markdown source
+    pub moved: Range<usize>,
+    /// the offset in the markdown source where the block should be inserted
+    pub insert_at: usize,
+    /// the markdown source of the render, see [`MarkdownMouseEvent::source`]
+    pub source: Rc<str>,
+}
+
+/// why rendering the markdown failed
+#[derive(Clone, Debug)]
+pub struct RenderFailure {
+    /// the panic message
+    pub message: String,
+    /// the markdown source that failed to render
+    pub src: String,
+}
+
+/// how soft line breaks are rendered
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum SoftBreakMode {
+    /// as a space, like most markdown renderers
+    #[default]
+    Space,
+    /// as a line break (`<br>`), like in chat messages
+    Br,
+    /// as nothing, for languages written without spaces like chinese or japanese
+    Nothing,
+}
+
+/// whether clicks on rendered elements reach the handlers of enclosing elements
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum ClickPropagation {
+    /// decided per element by the renderer,
+    /// so that only the innermost element reports a click
+    #[default]
+    Default,
+    /// clicks always propagate, for example to select an enclosing message
+    AlwaysPropagate,
+    /// clicks never propagate outside of the clicked element
+    AlwaysStop,
+}
+
+impl ClickPropagation {
+    /// whether the click on an element stops there, given the choice of the renderer
+    fn stops(self, default: bool) -> bool {
+        match self {
+            Self::Default => default,
+            Self::AlwaysPropagate => false,
+            Self::AlwaysStop => true,
+        }
+    }
+}
+
+/// the environment a render happens in.
+/// Features relying on javascript evaluation are skipped on the server,
+/// and the static content is rendered instead.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum RenderEnv {
+    /// a renderer with a live document: web, desktop, liveview
+    #[default]
+    Client,
+    /// server side rendering or prerendering
+    Server,
+}
+
+#[derive(Clone, Debug)]
+pub struct MarkdownMouseEvent {
+    /// the original mouse event triggered when a text element was clicked on
+    pub mouse_event: MouseEvent,
+
+    /// the corresponding range in the markdown source, as a slice of [`u8`][u8]
+    pub position: Range<usize>,
+
+    /// the markdown source that produced the clicked element.
+    /// Unlike the `src` prop at the time the handler runs,
+    /// `&source[position.clone()]` is always valid.
+    pub source: Rc<str>,
+
+    /// whether propagation of the event is stopped for this element
+    /// by default, regardless of `click_propagation`
+    pub default_stops_propagation: bool,
+
+    /// the kind of the clicked element
+    pub tag: ElementTag,
+}
+
+/// the kind of an element rendered from markdown, see [`MarkdownMouseEvent`]
+#[derive(Clone, PartialEq, Eq, Debug, Default)]
+pub enum ElementTag {
+    Paragraph,
+    Heading { level: u8 },
+    BlockQuote,
+    List { ordered: bool },
+    ListItem,
+    CodeBlock,
+    /// inline code
+    Code,
+    Table,
+    TableHead,
+    TableRow,
+    TableCell,
+    Emphasis,
+    Strong,
+    Strikethrough,
+    Link { href: String },
+    Image { src: String },
+    /// the checkbox of a task list item
+    Checkbox { checked: bool },
+    /// a horizontal rule
+    Rule,
+    /// a definition list
+    DefinitionList,
+    /// a term of a definition list
+    DefinitionTerm,
+    /// the definition of a term
+    DefinitionDetails,
+    /// `==highlighted==` text
+    Mark,
+    /// raw html, or math
+    Html,
+    #[default]
+    Other,
+}
+
+impl ElementTag {
+    fn of(e: &HtmlElement) -> Self {
+        match e {
+            HtmlElement::Paragraph => Self::Paragraph,
+            HtmlElement::Heading(level) => Self::Heading { level: *level as u8 },
+            HtmlElement::BlockQuote => Self::BlockQuote,
+            HtmlElement::Ul => Self::List { ordered: false },
+            HtmlElement::Ol(_) => Self::List { ordered: true },
+            HtmlElement::Li => Self::ListItem,
+            HtmlElement::Pre => Self::CodeBlock,
+            HtmlElement::Code => Self::Code,
+            HtmlElement::Table => Self::Table,
+            HtmlElement::Thead => Self::TableHead,
+            HtmlElement::Trow => Self::TableRow,
+            HtmlElement::Tcell => Self::TableCell,
+            HtmlElement::Italics => Self::Emphasis,
+            HtmlElement::Bold => Self::Strong,
+            HtmlElement::StrikeThrough => Self::Strikethrough,
+            HtmlElement::Div | HtmlElement::Span => Self::Other,
+        }
+    }
+}
+
+/// the kind of a collapsible section
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum SectionKind {
+    /// a `::: details` directive
+    Details,
+}
+
+/// a collapsible section opened or closed, see `on_section_toggle`
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct SectionToggleEvent {
+    /// the slug of the title of the section, like heading ids,
+    /// or `section-` followed by its offset in the source if it has no title
+    pub id: String,
+    pub kind: SectionKind,
+    /// whether the section is open after the toggle
+    pub open: bool,
+}
+
+/// an event handler created by the renderer,
+/// along with the source range of the element it is attached to
+pub struct MdHandler<'a, T> {
+    handler: EventHandler<'a, T>,
+    position: Option<Range<usize>>,
+    /// the index of its click target in the render state
+    target: Option<usize>,
+}
+
+impl<T> Default for MdHandler<'_, T> {
+    fn default() -> Self {
+        Self {
+            handler: Default::default(),
+            position: None,
+            target: None,
+        }
+    }
+}
+
+impl<'a, T> MdHandler<'a, T> {
+    pub fn call(&self, event: T) {
+        self.handler.call(event)
+    }
+
+    /// the range in the markdown source of the element this handler is attached to
+    pub fn position(&self) -> Option<Range<usize>> {
+        self.position.clone()
+    }
+}
+
+#[derive(Clone, Copy)]
+pub struct MdContext<'a> {
+    cx: &'a Scoped<'a, MdProps<'a>>,
+    state: &'a RenderState,
+    /// the source being rendered, with normalized newlines if requested
+    src: &'a str,
+    /// the cells of the table being rendered, for `render_tables`
+    table: &'a RefCell<TableBuffer<'a>>,
+    /// the footnote definitions rendered so far,
+    /// listed at the end of the document
+    footnotes: &'a RefCell<Vec<FootnoteDefinitionDescription<'a>>>,
+}
+
+/// the rendered cells of a table, until the whole table is rendered
+#[derive(Default)]
+struct TableBuffer<'a> {
+    /// the cells of the row being rendered
+    cells: Vec<Element<'a>>,
+    headers: Vec<Element<'a>>,
+    rows: Vec<Vec<Element<'a>>>,
+}
+
+/// state shared by all the elements of a single render of the markdown
+#[derive(Default)]
+pub(crate) struct RenderState {
+    /// snapshot of the source, shared by all the handlers of this render
+    source: Rc<str>,
+    index: OnceCell<Rc<DocumentIndex>>,
+    /// number of raw html events already rendered
+    html_cursor: Cell<usize>,
+    /// number of images already rendered
+    image_cursor: Cell<usize>,
+    /// number of links already rendered
+    link_cursor: Cell<usize>,
+    /// number of component tags already rendered
+    component_cursor: Cell<usize>,
+    /// the targets of the wikilinks to missing pages
+    broken_links: RefCell<Vec<String>>,
+    /// number of character references already rendered
+    entity_cursor: Cell<usize>,
+    /// number of task list checkboxes already rendered
+    task_cursor: Cell<usize>,
+    /// a footnote reference was just rendered by `render_footnote_reference`,
+    /// and must not be wrapped in a link
+    footnote_rendered: Cell<bool>,
+    /// the links that scrolled into view, for `on_link_visible`, kept between renders
+    visibility: Rc<visibility::LinkVisibility>,
+    /// the scroll position reported to `scroll_state`, kept between renders
+    scroll: Rc<scroll::ScrollSync>,
+    /// the code blocks highlighted after being displayed, kept between renders
+    deferred_highlights: Rc<highlight::DeferredHighlights>,
+    /// the hrefs `on_link_intent` was called with
+    link_intents: RefCell<HashSet<String>>,
+    /// the elements click handlers were created for, in creation order
+    click_targets: RefCell<Vec<ClickTarget>>,
+    /// the clicks received by the root, kept between renders
+    clicks: Rc<delegate::DelegatedClicks>,
+    /// the clicks of the elements are received by the root instead of their own listeners,
+    /// see `click_root`
+    delegates_clicks: Cell<bool>,
+    /// the block being dragged, kept between renders
+    dragged_block: Cell<Option<Range<usize>>>,
+    /// the urls copied by heading anchors, for `on_anchor_copied`, kept between renders
+    copied_anchors: Rc<RefCell<Vec<String>>>,
+    /// the ids of the open collapsible sections, kept between renders.
+    /// `None` until `initial_open_sections` is read
+    open_sections: Rc<RefCell<Option<HashSet<String>>>>,
+    /// the javascript evaluator of the renderer, if it has one
+    eval: Option<Rc<dyn EvalProvider>>,
+    /// where carriage returns were removed when normalizing newlines,
+    /// as offsets in the normalized source
+    removed_cr: Rc<[usize]>,
+    /// the rewrites of the normalized source into the rendered one
+    /// (`:::` directives, definition lists... as tags, code blocks as placeholders), in order:
+    /// offsets in the rewritten source and the difference with the source before them
+    rewrites: Rc<[Vec<(usize, isize)>]>,
+    /// the code blocks replaced by placeholders in the rendered source, in order
+    code_blocks: Rc<[code::CodeBlock]>,
+    /// the stylesheets requested, kept between renders
+    links: links::LinkUser,
+    /// the stylesheet of the theme was rendered in the document,
+    /// since there is no document to mount it in
+    theme_css_rendered: Cell<bool>,
+    /// the outline reported to `on_outline_change`, kept between renders
+    outline: RefCell<Option<Vec<OutlineHeading>>>,
+    /// the outline of this render
+    current_outline: OnceCell<Rc<Vec<OutlineHeading>>>,
+    /// the registry provided by an ancestor, if any
+    provided_registry: Option<ComponentRegistry>,
+    /// the loads of async components, kept between renders
+    loads: loading::AsyncLoads,
+    /// how many markdown renders this one is nested in
+    depth: usize,
+    /// the components that failed to render and the content that is not displayed,
+    /// for the debug info
+    #[cfg(feature="debug")]
+    debug_messages: RefCell<Vec<String>>,
+    nested: NestedStates,
+}
+
+/// the states of the markdown rendered by components with [`MdRenderer`]
+/// during a render, in a list that can grow while they are borrowed
+#[derive(Default)]
+struct NestedStates {
+    state: OnceCell<Box<RenderState>>,
+    next: OnceCell<Box<NestedStates>>,
+}
+
+impl NestedStates {
+    fn alloc(&self, state: RenderState) -> &RenderState {
+        let mut slot = self;
+        loop {
+            if slot.state.get().is_none() {
+                return slot.state.get_or_init(|| Box::new(state))
+            }
+            slot = slot.next.get_or_init(Default::default);
+        }
+    }
+}
+
+/// renders markdown like the [`Markdown`] component calling a custom component,
+/// see [`CustomComponents::register_with_renderer`]
+#[derive(Clone, Copy)]
+pub struct MdRenderer<'a> {
+    context: MdContext<'a>,
+}
+
+impl<'a> MdRenderer<'a> {
+    /// render `src` with the options of the calling `Markdown`.
+    /// Past `max_nesting_depth` nested renders, an error is displayed instead
+    pub fn render(self, src: &str) -> Element<'a> {
+        self.context.render_nested(src)
+    }
+
+    /// how many renders of markdown the component is nested in, 1 for a top level component
+    pub fn depth(self) -> usize {
+        self.context.state.depth + 1
+    }
+
+    /// the result of the load of an async component, see [`CustomComponents::register_async`]
+    pub(crate) fn load(self, name: &str, attributes: &BTreeMap<String, String>, load: &loading::LoadFn) -> Option<loading::Loaded> {
+        self.context.state.loads.get(self.context.cx.scope, name, attributes, load)
+    }
+
+    pub(crate) fn async_placeholder(self) -> Element<'a> {
+        let cx = self.context.cx;
+        match &cx.props.components.async_placeholder {
+            Some(placeholder) => placeholder(cx.scope),
+            None => cx.render(rsx!{ span { class: "md-loading" } }),
+        }
+    }
+}
+
+#[derive(Clone)]
+struct ClickTarget {
+    position: Range<usize>,
+    stop_propagation: bool,
+    /// known once the handler is attached to its element
+    tag: ElementTag,
+}
+
+impl RenderState {
+    fn reset(&mut self, src: &str, eval: Option<Rc<dyn EvalProvider>>) {
+        *self = Self {
+            source: src.into(),
+            dragged_block: Cell::new(self.dragged_block.take()),
+            copied_anchors: self.copied_anchors.clone(),
+            open_sections: self.open_sections.clone(),
+            visibility: self.visibility.clone(),
+            clicks: self.clicks.clone(),
+            scroll: self.scroll.clone(),
+            deferred_highlights: self.deferred_highlights.clone(),
+            links: std::mem::take(&mut self.links),
+            outline: RefCell::new(self.outline.take()),
+            loads: self.loads.clone(),
+            eval,
+            ..Self::default()
+        }
+    }
+
+    /// reset the state for a render of the chunk at `offset` of the document rendered with `parent`,
+    /// see [`ChunkConfig`]. It shares the index and the rewrites of the whole document,
+    /// and its lookups in the index start at the chunk
+    fn reset_chunk(&mut self, parent: &RenderState, offset: usize) {
+        let cursors = parent.index.get().map(|index| index.cursors_at(offset));
+        let cursor = |f: fn(&index::Cursors) -> usize| Cell::new(cursors.as_ref().map_or(0, f));
+        *self = Self {
+            source: parent.source.clone(),
+            index: parent.index.clone(),
+            html_cursor: cursor(|c| c.html),
+            image_cursor: cursor(|c| c.images),
+            link_cursor: cursor(|c| c.links),
+            component_cursor: cursor(|c| c.tags),
+            entity_cursor: cursor(|c| c.entities),
+            task_cursor: cursor(|c| c.tasks),
+            visibility: parent.visibility.clone(),
+            copied_anchors: parent.copied_anchors.clone(),
+            open_sections: parent.open_sections.clone(),
+            deferred_highlights: parent.deferred_highlights.clone(),
+            eval: parent.eval.clone(),
+            removed_cr: parent.removed_cr.clone(),
+            rewrites: parent.rewrites.clone(),
+            code_blocks: parent.code_blocks.clone(),
+            current_outline: parent.current_outline.clone(),
+            provided_registry: parent.provided_registry.clone(),
+            links: std::mem::take(&mut self.links),
+            loads: self.loads.clone(),
+            ..Self::default()
+        }
+    }
+
+    /// the event reported for a mouse event on the `target`-th click target,
+    /// whose propagation is stopped according to `propagation`
+    fn mouse_event(&self, target: usize, propagation: ClickPropagation, e: MouseEvent) -> MarkdownMouseEvent {
+        let target = self.click_targets.borrow()[target].clone();
+        self.target_event(target, propagation, e)
+    }
+
+    /// the event of a click on `target`, stopping its propagation if needed
+    fn target_event(&self, target: ClickTarget, propagation: ClickPropagation, e: MouseEvent) -> MarkdownMouseEvent {
+        let ClickTarget { position, stop_propagation, tag } = target;
+
+        if propagation.stops(stop_propagation) {
+            e.stop_propagation()
+        }
+
+        MarkdownMouseEvent {
+            position: self.original_range(position),
+            source: self.source.clone(),
+            default_stops_propagation: stop_propagation,
+            tag,
+            mouse_event: e
+        }
+    }
+
+    /// report a click received by the root to `on_click`,
+    /// from its innermost target until one stops its propagation, like their own handlers would
+    fn report_click(&self, on_click: &EventHandler<MarkdownMouseEvent>, click: delegate::ResolvedClick, propagation: ClickPropagation) {
+        for target in click.targets {
+            let Some(target) = self.click_targets.borrow().get(target).cloned() else { return };
+            let stops = propagation.stops(target.stop_propagation);
+            on_click.call(self.target_event(target, propagation, click.event.clone()));
+            if stops {
+                return
+            }
+        }
+    }
+
+    /// the offset in the original source of an offset in the rendered source
+    fn original_offset(&self, offset: usize) -> usize {
+        rewrite::original_offset(&self.removed_cr, &self.rewrites, offset)
+    }
+
+    /// the range in the original source of a range in the rendered source
+    fn original_range(&self, range: Range<usize>) -> Range<usize> {
+        self.original_offset(range.start)..self.original_offset(range.end)
+    }
+}
+
+impl<'a> MdContext<'a> {
+    fn new(cx: &'a Scoped<'a, MdProps<'a>>, state: &'a RenderState, src: &'a str) -> Self {
+        // the buffers are emptied by each table and each document,
+        // so nothing is left to drop in the bump
+        let table = cx.bump().alloc(RefCell::new(TableBuffer::default()));
+        let footnotes = cx.bump().alloc(RefCell::new(Vec::new()));
+        Self { cx, state, src, table, footnotes }
+    }
+
+    /// the document index, built on first use in each render
+    fn index(self) -> &'a DocumentIndex {
+        let props = self.cx.props;
+        self.state.index.get_or_init(|| Rc::new(DocumentIndex::new(
+            self.src,
+            self.parse_options(),
+            props.wikilinks,
+        )))
+    }
+
+    /// the environment this render happens in
+    pub fn render_env(self) -> RenderEnv {
+        self.cx.props.render_env
+            .or_else(|| self.cx.consume_context::<RenderEnv>())
+            .unwrap_or_default()
+    }
+
+    /// `view` wrapped with the interactive elements around it, if `position` is a top level block
+    fn with_block_chrome(self, kind: BlockKind, position: Option<Range<usize>>, view: Element<'a>) -> Element<'a> {
+        let props = self.cx.props;
+        if !props.block_drag && !props.editable_blocks {
+            return view
+        }
+        match position {
+            Some(position) if self.index().is_top_level_block(&position) => self.block_chrome(kind, position, view),
+            _ => view,
+        }
+    }
+
+    /// wrap a top level block with the interactive elements around it
+    fn block_chrome(self, kind: BlockKind, position: Range<usize>, block: Element<'a>) -> Element<'a> {
+        let props = self.cx.props;
+        let state = self.state;
+        let target = position.start;
+
+        let drag_handle = props.block_drag.then(|| {
+            let dragged = position.clone();
+            rsx!{
+                span {
+                    class: "md-drag-handle",
+                    draggable: "true",
+                    ondragstart: move |_| state.dragged_block.set(Some(dragged.clone())),
+                    "⠿"
+                }
+            }
+        });
+
+        let edit_button = props.editable_blocks.then(|| rsx!{
+            button {
+                class: "md-edit-button",
+                onclick: move |e| {
+                    e.stop_propagation();
+                    self.request_block_edit(kind, position.clone())
+                },
+                "✎"
+            }
+        });
+
+        if !props.block_drag {
+            return self.cx.render(rsx!{
+                div { class: "md-block", edit_button, block }
+            })
+        }
+
+        self.cx.render(rsx!{
+            div {
+                class: "md-block",
+                prevent_default: "ondragover ondrop",
+                ondragover: move |_| {},
+                ondrop: move |_| self.drop_block(target),
+                drag_handle,
+                edit_button,
+                block
+            }
+        })
+    }
+
+    /// the dragged block was dropped onto the block starting at `target`
+    fn drop_block(self, target: usize) {
+        match self.state.dragged_block.take() {
+            Some(moved) if moved.start != target => {
+                if let Some(f) = &self.cx.props.on_block_reorder {
+                    f.call(BlockReorderEvent {
+                        moved: self.state.original_range(moved),
+                        insert_at: self.state.original_offset(target),
+                        source: self.state.source.clone(),
+                    })
+                }
+            }
+            _ => (),
+        }
+    }
+
+    fn request_block_edit(self, kind: BlockKind, range: Range<usize>) {
+        let Some(f) = &self.cx.props.on_block_edit_request else {
+            return
+        };
+        let range = self.state.original_range(range);
+        let source = &self.state.source[range.clone()];
+        let text = parse_to_tree(source, &self.cx.props.parse_config(), |_| false)
+            .plain_text();
+        f.call(BlockEditRequest { kind, range, text, source: self.state.source.clone() })
+    }
+
+    /// the options the markdown is parsed with
+    fn parse_options(self) -> Options {
+        parse_options(self.cx.props)
+    }
+
+    /// whether the document can have footnotes
+    fn footnotes_enabled(self) -> bool {
+        self.parse_options().contains(Options::ENABLE_FOOTNOTES)
+    }
+
+    /// the headings of the document, with their ids
+    fn outline(self) -> Rc<Vec<OutlineHeading>> {
+        let index = self.index();
+        let slug: &dyn Fn(&str) -> String = match &self.cx.props.slugify {
+            Some(slugify) => &**slugify,
+            None => &outline::slugify,
+        };
+        self.state.current_outline.get_or_init(|| Rc::new(outline::outline(
+            index.headings()
+                .map(|(level, title, range)| {
+                    let id = index.explicit_heading_id(&range);
+                    (level, title, id, self.state.original_range(range))
+                }),
+            slug,
+        ))).clone()
+    }
+
+    /// the id of the heading at `position`
+    fn heading_id(self, position: &Range<usize>) -> Option<String> {
+        let range = self.state.original_range(position.clone());
+        self.outline().iter()
+            .find(|heading| heading.range == range)
+            .map(|heading| heading.id.clone())
+    }
+
+    /// call `on_outline_change` if the outline changed since the previous render
+    fn report_outline(self, on_outline_change: &EventHandler<'a, OutlineDelta>) {
+        let new = Vec::clone(&self.outline());
+        let previous = self.state.outline.replace(Some(new.clone()));
+        let changes = outline::diff(previous.as_deref().unwrap_or_default(), &new);
+        if !changes.is_empty() {
+            on_outline_change.call(OutlineDelta { changes, outline: new })
+        }
+    }
+
+    /// buffer a part of a table, and render the whole table with `render_tables` once it is complete
+    fn buffer_table(self, render_tables: &HtmlCallback<'a, TableDescription<'a>>, e: HtmlElement, inside: Element<'a>, position: Option<&Range<usize>>) -> Element<'a> {
+        let mut buffer = self.table.borrow_mut();
+        match e {
+            HtmlElement::Tcell => buffer.cells.push(inside),
+            HtmlElement::Trow => {
+                let cells = std::mem::take(&mut buffer.cells);
+                buffer.rows.push(cells)
+            }
+            HtmlElement::Thead => {
+                // the header cells may already be grouped in a row
+                buffer.headers = match buffer.cells.is_empty() {
+                    true => buffer.rows.pop().unwrap_or_default(),
+                    false => std::mem::take(&mut buffer.cells),
+                }
+            }
+            HtmlElement::Table => {
+                let TableBuffer { headers, rows, .. } = std::mem::take(&mut *buffer);
+                drop(buffer);
+                let alignments = position
+                    .and_then(|p| self.index().table(p))
+                    .map(|table| table.alignments.clone())
+                    .unwrap_or_default();
+                return render_tables(self.cx.scope, TableDescription {
+                    alignments,
+                    headers,
+                    rows,
+                    range: position.map(|p| self.state.original_range(p.clone())),
+                })
+            }
+            _ => (),
+        }
+        None
+    }
+
+    /// the next link to `url` in the document index
+    fn next_link(self, url: &str) -> Option<&'a LinkEntry> {
+        let (i, entry) = self.index().link(self.state.link_cursor.get(), url)?;
+        self.state.link_cursor.set(i + 1);
+        Some(entry)
+    }
+
+    /// the target and the label, if different from the target, of a wikilink
+    fn wikilink_parts(self, entry: &LinkEntry) -> Option<(String, Option<String>)> {
+        if entry.kind != LinkKind::Wikilink {
+            return None
+        }
+        let inner = self.src[entry.range.clone()].trim_start_matches('[').trim_end_matches(']');
+        Some(match inner.split_once('|') {
+            Some((target, label)) => (target.trim().to_string(), Some(label.trim().to_string())),
+            None => (inner.trim().to_string(), None),
+        })
+    }
+
+    /// the href to link to for `url`, resolved with `resolve_wikilink` for wikilinks
+    fn resolve_href(self, url: String, entry: Option<&LinkEntry>) -> String {
+        let resolve = self.cx.props.resolve_wikilink.as_ref();
+        match (resolve, entry.and_then(|e| self.wikilink_parts(e))) {
+            (Some(resolve), Some((target, _))) => resolve(&target),
+            _ => url,
+        }
+    }
+
+    /// whether `entry` is a wikilink to a missing page.
+    /// Its target is then recorded for `broken_links`
+    fn is_missing(self, entry: Option<&LinkEntry>) -> bool {
+        let Some(exists) = &self.cx.props.wikilink_exists else {
+            return false
+        };
+        match entry.and_then(|e| self.wikilink_parts(e)) {
+            Some((target, _)) if !exists(&target) => {
+                self.state.broken_links.borrow_mut().push(target);
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// `link` with the details found in the document index
+    fn link_details(self, link: LinkDescription<Element<'a>>, entry: Option<&LinkEntry>) -> LinkDetails<'a> {
+        let Some(entry) = entry else {
+            return LinkDetails { link, kind: LinkKind::Inline, range: None, wikilink: None, missing: false }
+        };
+        LinkDetails {
+            link,
+            kind: entry.kind,
+            range: Some(self.state.original_range(entry.range.clone())),
+            wikilink: self.wikilink_parts(entry),
+            missing: self.is_missing(Some(entry)),
+        }
+    }
+
+    /// the default rendering of a link to the already resolved `href`
+    fn anchor(self, children: Element<'a>, href: String, entry: Option<&LinkEntry>) -> Element<'a> {
+        let class = self.is_missing(entry).then(|| {
+            self.cx.props.missing_wikilink_class.as_deref().unwrap_or("wikilink-missing")
+        });
+
+        let props = self.cx.props;
+        let on_link_click = props.on_link_click.as_ref();
+        // links are reported to `on_click` like the elements rendered with a handler
+        let click_target = entry
+            .filter(|_| props.on_click.is_some() && props.click_targets.map_or(true, |f| f.links))
+            .map(|entry| ClickTarget {
+                position: entry.range.clone(),
+                stop_propagation: true,
+                tag: ElementTag::Link { href: href.clone() },
+            });
+        // the links are found by the observer with this attribute
+        let visible = props.on_link_visible.as_ref().map(|_| self.state.visibility.id.as_str());
+        if props.on_link_intent.is_none() && on_link_click.is_none() && click_target.is_none() {
+            return self.cx.render(
+                rsx!{a {href: "{href}", class: class, "data-md-visible": visible, children}}
+            )
+        }
+
+        let (hovered, focused, clicked) = (href.clone(), href.clone(), href.clone());
+        let text = entry.map(|e| e.text.clone()).unwrap_or_default();
+        let wikilink = entry.is_some_and(|e| e.kind == LinkKind::Wikilink);
+        // the browser can only be stopped before the handler runs,
+        // so the link is followed by the handler instead
+        let prevent_default = if on_link_click.is_some() { "onclick" } else { "" };
+        self.cx.render(
+            rsx!{a {
+                href: "{href}",
+                class: class,
+                "data-md-visible": visible,
+                prevent_default: prevent_default,
+                onmouseenter: move |_| self.link_intent(&hovered),
+                onfocus: move |_| self.link_intent(&focused),
+                onclick: move |mouse_event| {
+                    if let (Some(on_click), Some(target)) = (&props.on_click, &click_target) {
+                        let propagation = props.click_propagation;
+                        on_click.call(self.state.target_event(target.clone(), propagation, mouse_event.clone()))
+                    }
+                    if let Some(on_link_click) = on_link_click {
+                        self.link_click(on_link_click, LinkClickEvent {
+                            href: clicked.clone(),
+                            text: text.clone(),
+                            wikilink,
+                            mouse_event,
+                            source: self.state.source.clone(),
+                            prevented: Default::default(),
+                        })
+                    }
+                },
+                children
+            }}
+        )
+    }
+
+    /// call `on_link_click`, and follow the link if it did not prevent it
+    fn link_click(self, on_link_click: &EventHandler<'a, LinkClickEvent>, event: LinkClickEvent) {
+        let prevented = event.prevented.clone();
+        let modifiers = event.mouse_event.modifiers();
+        let new_tab = modifiers.contains(Modifiers::CONTROL) || modifiers.contains(Modifiers::META);
+        let href = clipboard::js_string(&event.href);
+        on_link_click.call(event);
+        if prevented.get() {
+            return
+        }
+        let script = match new_tab {
+            true => format!("window.open({href}, '_blank')"),
+            false => format!("window.location.assign({href})"),
+        };
+        if let Some(eval) = &self.state.eval {
+            // navigation is best effort, like the anchor without a handler
+            let _ = eval.new_evaluator(script);
+        }
+    }
+
+    fn try_render_component(self, name: &str, tag: Option<&TagEntry>, input: rust_web_markdown::MdComponentProps<Element<'a>>) -> Result<Element<'a>, ComponentCreationError> {
+        if self.cx.props.directives && name == directive::FALLBACK_TAG {
+            return Ok(self.directive_fallback(tag, input))
+        }
+        let components = &self.cx.props.components;
+        let Some(component) = components.get(name) else {
+            if let Some(component) = self.registry().and_then(|r| r.get(name, components.ignores_case())) {
+                return component(self.cx.scope, input)
+            }
+            return match &components.fallback {
+                Some(fallback) => fallback(self.cx.scope, name, input),
+                None => Err(format!("unknown component <{name}>").into()),
+            }
+        };
+        if let Some(meta) = &component.meta {
+            meta.validate(name, &input)?;
+        }
+        (component.render)(self.cx.scope, input, MdRenderer { context: self })
+    }
+
+    /// the next tag of a component named `name`
+    fn component_tag(self, name: &str) -> Option<&'a TagEntry> {
+        let (i, tag) = self.index().tag(self.state.component_cursor.get(), name)?;
+        self.state.component_cursor.set(i + 1);
+        Some(tag)
+    }
+
+    /// check or uncheck the `index`-th task item in `source`
+    fn toggle_task(self, source: &UseState<String>, index: usize) {
+        // the source may have been edited since this render, by a previous toggle for example,
+        // so the marker is looked for in the current one
+        let current = source.current();
+        let tasks = extract_tasks(&current, &self.cx.props.parse_config());
+        let Some(task) = tasks.get(index) else { return };
+        let mut toggled = current.to_string();
+        toggled.replace_range(task.marker_range.clone(), if task.checked { "[ ]" } else { "[x]" });
+        source.set(toggled)
+    }
+
+    /// a handler calling `handler`, if it is set, like clicks on the `target`-th click target call `on_click`
+    fn mouse_handler(self, handler: Option<&'a EventHandler<'a, MarkdownMouseEvent>>, target: Option<usize>) -> impl Fn(MouseEvent) + 'a {
+        let state = self.state;
+        let propagation = self.cx.props.click_propagation;
+        move |e| {
+            if let (Some(handler), Some(target)) = (handler, target) {
+                handler.call(state.mouse_event(target, propagation, e))
+            }
+        }
+    }
+
+    /// a handler calling `on_hover`, if it is set, with the position of the element
+    fn hover_handler(self, on_hover: Option<&'a EventHandler<'a, MarkdownMouseEvent>>, position: Option<Range<usize>>, tag: ElementTag) -> impl Fn(MouseEvent) + 'a {
+        let state = self.state;
+        move |mouse_event| {
+            if let (Some(on_hover), Some(position)) = (on_hover, &position) {
+                on_hover.call(MarkdownMouseEvent {
+                    mouse_event,
+                    position: state.original_range(position.clone()),
+                    source: state.source.clone(),
+                    default_stops_propagation: false,
+                    tag: tag.clone(),
+                })
+            }
+        }
+    }
+
+    /// record the kind of element `handler` is attached to, for its click events
+    fn set_click_tag(self, handler: Option<&MdHandler<'a, MouseEvent>>, tag: ElementTag) {
+        if let Some(target) = handler.and_then(|h| h.target) {
+            self.state.click_targets.borrow_mut()[target].tag = tag
+        }
+    }
+
+    /// the click handler of an element of kind `tag`, if there is an `on_click`
+    /// and the clicks of this kind are reported according to `click_targets`
+    fn click_handler(self, handler: Option<MdHandler<'a, MouseEvent>>, tag: &ElementTag) -> Option<MdHandler<'a, MouseEvent>> {
+        let props = self.cx.props;
+        handler.filter(|_| props.on_click.is_some() && props.click_targets.map_or(true, |f| f.matches(tag)))
+    }
+
+    /// how an element receives the clicks for `handler`: with its own listener calling it,
+    /// or with a `data-md-target` attribute for the listener of the root, see `click_root`
+    fn click_listener(self, handler: Option<MdHandler<'a, MouseEvent>>) -> (Option<MdHandler<'a, MouseEvent>>, Option<String>) {
+        match handler {
+            Some(handler) if self.state.delegates_clicks.get() => (None, handler.target.map(|t| t.to_string())),
+            handler => (handler, None),
+        }
+    }
+
+    /// `view` in the element receiving the clicks of the whole document
+    /// instead of a listener per element, with `delegate_clicks`
+    fn click_root(self, view: Element<'a>) -> Element<'a> {
+        if !self.state.delegates_clicks.get() {
+            return view
+        }
+        if let Some(eval) = &self.state.eval {
+            self.state.clicks.listen(eval)
+        }
+        let id = &self.state.clicks.id;
+        let propagation = self.cx.props.click_propagation;
+        let onclick = move |e: MouseEvent| {
+            // the targets are only known later, when the event has left the root:
+            // it stops there unless clicks always propagate
+            if propagation != ClickPropagation::AlwaysPropagate {
+                e.stop_propagation()
+            }
+            if let Some(eval) = &self.state.eval {
+                self.state.clicks.resolve(self.cx.scope, eval, e)
+            }
+        };
+        self.cx.render(rsx!{
+            div { style: "display: contents", "data-md-root": "{id}", onclick: onclick, view }
+        })
+    }
+
+    /// the registry of the components, from the props or provided by an ancestor
+    fn registry(self) -> Option<&'a ComponentRegistry> {
+        self.cx.props.registry.as_ref().or(self.state.provided_registry.as_ref())
+    }
+
+    /// render `src` in place of a component, see [`MdRenderer`]
+    fn render_nested(self, src: &str) -> Element<'a> {
+        let depth = self.state.depth + 1;
+        let max = self.cx.props.max_nesting_depth;
+        if depth > max {
+            return self.component_error(ComponentError {
+                name: "Markdown".to_string(),
+                message: format!("markdown can not be nested more than {max} times"),
+                offset: None,
+                line: None,
+                attribute: None,
+            })
+        }
+
+        let (code_blocks, rewritten) = code::rewrite_code_blocks(src, self.parse_options(), self.cx.props.wikilinks);
+        let (rendered, rewrites) = match rewritten {
+            Some((rewritten, shifts)) => (self.cx.bump().alloc_str(&rewritten), vec![shifts]),
+            None => (self.cx.bump().alloc_str(src), Vec::new()),
+        };
+        let state = self.state.nested.alloc(RenderState {
+            source: src.into(),
+            eval: self.state.eval.clone(),
+            provided_registry: self.state.provided_registry.clone(),
+            loads: self.state.loads.clone(),
+            depth,
+            rewrites: rewrites.into(),
+            code_blocks: code_blocks.into(),
+            ..RenderState::default()
+        });
+        MdContext::new(self.cx, state, rendered).render_document(rendered)
+    }
+
+    /// a `:::` container without a component of its name
+    fn directive_fallback(self, tag: Option<&TagEntry>, input: rust_web_markdown::MdComponentProps<Element<'a>>) -> Element<'a> {
+        let name = input.attributes.get("name").map_or("", String::as_str);
+        let title = input.attributes.get("title").filter(|t| !t.is_empty());
+        if name == "details" && !self.cx.props.inline_mode {
+            return self.details_section(tag, title.map(String::as_str), input.children)
+        }
+        if self.cx.props.inline_mode {
+            return self.cx.render(rsx!{
+                span {
+                    class: "{name}",
+                    title.map(|title| rsx!{ b { class: "md-directive-title", "{title}" } " " }),
+                    input.children
+                }
+            })
+        }
+        self.cx.render(rsx!{
+            div {
+                class: "{name}",
+                title.map(|title| rsx!{ p { class: "md-directive-title", "{title}" } }),
+                input.children
+            }
+        })
+    }
+
+    /// the kind of element the source was rewritten as the tag `name` for, if any
+    fn rewritten_tag(self, name: &str) -> Option<ElementTag> {
+        let props = self.cx.props;
+        match name {
+            definition::LIST_TAG if props.definition_lists => Some(ElementTag::DefinitionList),
+            definition::TERM_TAG if props.definition_lists => Some(ElementTag::DefinitionTerm),
+            definition::DETAILS_TAG if props.definition_lists => Some(ElementTag::DefinitionDetails),
+            mark::TAG if props.mark => Some(ElementTag::Mark),
+            _ => None,
+        }
+    }
+
+    /// an element the source was rewritten as a tag for, see `rewritten_tag`
+    fn rewritten_element(self, element_tag: ElementTag, tag: Option<&TagEntry>, children: Element<'a>) -> Element<'a> {
+        let position = tag.map(|tag| tag.range.start..tag.children.as_ref().map_or(tag.range.end, |c| c.end));
+        let handler = position.map(|p| self.make_md_handler(p, true));
+        self.set_click_tag(handler.as_ref(), element_tag.clone());
+        let (onclick, target) = self.click_listener(self.click_handler(handler, &element_tag));
+        let inline = self.cx.props.inline_mode;
+        let children = match (&element_tag, inline) {
+            (ElementTag::DefinitionList | ElementTag::DefinitionTerm | ElementTag::DefinitionDetails, true) =>
+                self.cx.render(rsx!{children, " "}),
+            _ => children,
+        };
+
+        macro_rules! element {
+            ($tag:ident) => {
+                match onclick {
+                    Some(onclick) => rsx!{$tag {onclick: move |e| onclick.call(e), children}},
+                    None => rsx!{$tag {"data-md-target": target, children}},
+                }
+            };
+        }
+        self.cx.render(match (element_tag, inline) {
+            (ElementTag::DefinitionList, false) => element!(dl),
+            (ElementTag::DefinitionTerm, false) => element!(dt),
+            (ElementTag::DefinitionDetails, false) => element!(dd),
+            (ElementTag::DefinitionList | ElementTag::DefinitionDetails, true) => element!(span),
+            (ElementTag::DefinitionTerm, true) => element!(b),
+            _ => element!(mark),
+        })
+    }
+
+    /// the view of `node`, a part of the alt text of an image, see [`ImageDescription::alt_content`]
+    fn alt_view(self, node: &MdNode) -> Element<'a> {
+        match &node.kind {
+            NodeKind::Text(text) => self.cx.render(rsx!{"{text}"}),
+            NodeKind::Code(code) => self.cx.render(rsx!{ code { "{code}" } }),
+            NodeKind::SoftBreak | NodeKind::HardBreak => self.cx.render(rsx!{" "}),
+            NodeKind::Image { .. } => {
+                let alt = node.plain_text();
+                self.cx.render(rsx!{"{alt}"})
+            }
+            NodeKind::Emphasis => self.cx.render(rsx!{
+                em { for child in &node.children { self.alt_view(child) } }
+            }),
+            NodeKind::Strong => self.cx.render(rsx!{
+                strong { for child in &node.children { self.alt_view(child) } }
+            }),
+            NodeKind::Strikethrough => self.cx.render(rsx!{
+                del { for child in &node.children { self.alt_view(child) } }
+            }),
+            NodeKind::Mark => self.cx.render(rsx!{
+                mark { for child in &node.children { self.alt_view(child) } }
+            }),
+            _ => self.cx.render(rsx!{
+                for child in &node.children { self.alt_view(child) }
+            }),
+        }
+    }
+
+    /// the element displayed in place of a component that failed to render
+    fn component_error(self, error: ComponentError) -> Element<'a> {
+        if let Some(render_component_error) = &self.cx.props.render_component_error {
+            return render_component_error(self.cx.scope, error)
+        }
+        self.cx.render(rsx!{
+            span {
+                class: "md-component-error",
+                style: "color: red; border: 1px solid red; padding: 0 0.2em",
+                "{error}"
+            }
+        })
+    }
+
+    /// the table at `position` as text, see [`TableFallback`]
+    fn table_fallback(self, position: Option<&Range<usize>>) -> Element<'a> {
+        let table = self.index().table(position?)?;
+        let inline = self.cx.props.inline_mode;
+        match self.cx.props.table_fallback {
+            TableFallback::Hidden => {
+                #[cfg(feature="debug")]
+                {
+                    let range = self.state.original_range(table.range.clone());
+                    self.state.debug_messages.borrow_mut()
+                        .push(format!("the table at {}..{} is not allowed, it is skipped", range.start, range.end));
+                }
+                None
+            }
+            TableFallback::DefinitionList if !inline && table.has_headers() => self.cx.render(rsx!{
+                dl {
+                    class: "md-table-fallback",
+                    table.rows.iter().map(|row| rsx!{
+                        div {
+                            row.iter().zip(&table.headers).map(|(cell, header)| rsx!{
+                                dt { "{header}" }
+                                dd { "{cell}" }
+                            })
+                        }
+                    })
+                }
+            }),
+            _ if inline => {
+                let lines = table.linearized();
+                let last = lines.len().saturating_sub(1);
+                self.cx.render(rsx!{
+                    span {
+                        class: "md-table-fallback",
+                        lines.into_iter().enumerate().map(|(i, line)| rsx!{
+                            "{line}"
+                            if i != last { rsx!{ br {} } }
+                        })
+                    }
+                })
+            }
+            _ => self.cx.render(rsx!{
+                div {
+                    class: "md-table-fallback",
+                    table.linearized().into_iter().map(|line| rsx!{ p { "{line}" } })
+                }
+            }),
+        }
+    }
+
+    /// whether `text` is the content of an inline code span
+    fn is_code_text(self, text: &str) -> bool {
+        index::offset_in(self.src, text)
+            .is_some_and(|offset| self.index().in_code(offset))
+    }
+
+    /// the view of `text` if it is part of a language-annotated code span,
+    /// or starts with the annotation of one
+    fn annotated_code_text(self, text: &str) -> Option<Element<'a>> {
+        let offset = index::offset_in(self.src, text)?;
+        let index = self.index();
+
+        if let Some(len) = index.language_annotation(offset) {
+            let rest = &text[len.min(text.len())..];
+            return Some(self.cx.render(rsx!{rest}))
+        }
+
+        let language = index.code_language(offset)?;
+        let class = format!("language-{language}");
+        let highlighted = highlight::highlight(
+            text,
+            language,
+            self.cx.props.theme.as_deref(),
+            self.cx.props.highlight_classes,
+            self.cache(),
+        );
+        Some(match highlighted {
+            Some(html) => self.with_theme_stylesheet(self.cx.render(rsx!{
+                span { class: "{class}", dangerous_inner_html: "{html}" }
+            })),
+            None => self.cx.render(rsx!{
+                span { class: "{class}", "{text}" }
+            }),
+        })
+    }
+
+    /// call `on_link_intent`, if it was not already called for `href` in this render
+    fn link_intent(self, href: &str) {
+        if let Some(on_link_intent) = &self.cx.props.on_link_intent {
+            if self.state.link_intents.borrow_mut().insert(href.to_string()) {
+                on_link_intent.call(href.to_string())
+            }
+        }
+    }
+
+    fn allowed_elements(self) -> ElementAllowlist {
+        self.cx.props.allowed_elements.unwrap_or_default()
+    }
+
+    /// where to copy text to, if there is a way to
+    pub fn clipboard(self) -> Option<Rc<dyn ClipboardSink>> {
+        if let Some(clipboard) = &self.cx.props.clipboard {
+            return Some(clipboard.clone())
+        }
+        match &self.state.eval {
+            Some(eval) if self.capabilities().eval => Some(Rc::new(clipboard::EvalClipboard(eval.clone()))),
+            _ => None,
+        }
+    }
+
+    /// what the renderer can do
+    pub fn capabilities(self) -> Capabilities {
+        capabilities::probe(self.state.eval.as_ref(), self.render_env())
+    }
+
+    /// the cache for expensive sub-renders, if the app provided one
+    pub fn cache(self) -> Option<&'a dyn RenderCache> {
+        self.cx.props.cache.as_deref()
+    }
+
+    /// the code block replaced by the placeholder at `position`, if any
+    fn code_block_at(self, position: &Range<usize>) -> Option<&'a code::CodeBlock> {
+        let blocks = &self.state.code_blocks;
+        let i = blocks.binary_search_by_key(&position.start, |block| block.placeholder.start).ok()?;
+        Some(&blocks[i])
+    }
+
+    /// a code block rendered by `render_code_blocks`,
+    /// or with the html given by `prehighlighted_code`, or highlighted with syntect
+    fn code_block(self, block: &code::CodeBlock, attributes: ElementAttributes<MdHandler<'a, MouseEvent>>) -> Element<'a> {
+        let props = self.cx.props;
+        let detected = match (&block.language, &props.detect_language) {
+            (None, Some(detect)) if block.fenced => detect(&block.code),
+            _ => None,
+        };
+        let description = CodeBlockDescription {
+            language_detected: detected.is_some(),
+            language: detected.or_else(|| block.language.clone()),
+            code: block.code.clone(),
+            range: self.state.original_range(block.placeholder.clone()),
+        };
 
-    /// 
-    render_links: Option<HtmlCallback<'a, LinkDescription<Element<'a>>>>,
+        if let Some(render_code_blocks) = &props.render_code_blocks {
+            let view = render_code_blocks(self.cx.scope, description);
+            return self.with_block_chrome(BlockKind::Other, Some(block.placeholder.clone()), view)
+        }
+        let annotations = props.code_line_annotations.as_ref()
+            .map(|annotate| annotate(self.cx.scope, &description))
+            .filter(|annotations| !annotations.is_empty());
+        if let Some(annotations) = annotations {
+            return self.annotated_code_block(block, description, annotations, attributes)
+        }
+        let defer = props.defer_highlighting && self.render_env() == RenderEnv::Client;
+        let html = props.prehighlighted_code.as_ref()
+            .and_then(|hook| hook(&description))
+            .map(Rc::<str>::from)
+            .or_else(|| {
+                let language = description.language.as_deref()?;
+                match defer {
+                    true => self.state.deferred_highlights
+                        .get(self.cx.scope, &description.code, language, props.theme.as_deref(), props.highlight_classes, props.cache.clone()),
+                    false => highlight::highlight(&description.code, language, props.theme.as_deref(), props.highlight_classes, self.cache()),
+                }
+            });
+        let class = description.language.map(|l| format!("language-{l}")).unwrap_or_default();
+        let code = match html {
+            Some(html) => self.cx.render(rsx!{
+                code { class: "{class}", dangerous_inner_html: "{html}" }
+            }),
+            None => self.cx.render(rsx!{
+                code { class: "{class}", "{description.code}" }
+            }),
+        };
+        let attributes = ElementAttributes {
+            classes: Vec::new(),
+            style: highlight::background(props.theme.as_deref()),
+            on_click: attributes.on_click,
+        };
+        let pre = self.el_with_attributes(HtmlElement::Pre, code, attributes);
+        self.with_theme_stylesheet(self.with_copy_button(pre, &block.code))
+    }
 
-    /// the name of the theme used for syntax highlighting.
-    /// Only the default themes of [syntect::Theme] are supported
-    theme: Option<String>,
+    /// a code block rendered line by line, each line followed by its annotation if it has one
+    fn annotated_code_block(self, block: &code::CodeBlock, description: CodeBlockDescription, mut annotations: BTreeMap<usize, Element<'a>>, attributes: ElementAttributes<MdHandler<'a, MouseEvent>>) -> Element<'a> {
+        let props = self.cx.props;
+        let lines = description.language.as_deref()
+            .and_then(|language| highlight::highlight_lines(&description.code, language, props.theme.as_deref(), props.highlight_classes))
+            .unwrap_or_else(|| description.code.split_inclusive('\n').map(html::escape_attribute).collect());
+        let lines = lines.into_iter().enumerate().map(|(i, mut line)| {
+            // the newline is the last character of the text, so the last one of the html
+            let newline = line.rfind('\n').map(|n| line.remove(n)).is_some();
+            let annotation = annotations.remove(&(i + 1)).map(|annotation| rsx!{
+                span { class: "md-line-annotation", style: "user-select: none", annotation }
+            });
+            rsx!{
+                span { class: "md-line", dangerous_inner_html: "{line}" }
+                annotation
+                newline.then(|| rsx!{ "\n" })
+            }
+        });
+        let class = description.language.map(|l| format!("language-{l}")).unwrap_or_default();
+        let code = self.cx.render(rsx!{
+            code { class: "{class}", lines }
+        });
+        let attributes = ElementAttributes {
+            classes: Vec::new(),
+            style: highlight::background(props.theme.as_deref()),
+            on_click: attributes.on_click,
+        };
+        let pre = self.el_with_attributes(HtmlElement::Pre, code, attributes);
+        self.with_theme_stylesheet(self.with_copy_button(pre, &block.code))
+    }
 
-    /// wether to enable wikilinks support.
-    /// Wikilinks look like [[shortcut link]] or [[url|name]]
-    #[props(default = false)]
-    wikilinks: bool,
+    /// `view` after a button copying `text`, if `copy_buttons` is set and there is a clipboard
+    fn with_copy_button(self, view: Element<'a>, text: &str) -> Element<'a> {
+        let props = self.cx.props;
+        if !props.copy_buttons || props.inline_mode {
+            return view
+        }
+        let Some(clipboard) = self.clipboard() else {
+            return view
+        };
+        let text = text.to_string();
+        self.cx.render(rsx!{
+            div {
+                class: "md-copyable",
+                button {
+                    class: "md-copy",
+                    r#type: "button",
+                    aria_label: "copy",
+                    onclick: move |_| clipboard.copy(&text),
+                    span { class: "md-copy-icon", aria_hidden: "true", "⧉" }
+                    " Copy"
+                }
+                view
+            }
+        })
+    }
 
-    /// wether to convert soft breaks to hard breaks.
-    #[props(default = false)]
-    hard_line_breaks: bool,
+    /// `view` after the stylesheet of the theme, for code highlighted with `highlight_classes`.
+    /// The stylesheet is mounted in the document if possible, else it is rendered once per render
+    fn with_theme_stylesheet(self, view: Element<'a>) -> Element<'a> {
+        if !self.cx.props.highlight_classes {
+            return view
+        }
+        let Some((id, css)) = highlight::theme_css(self.cx.props.theme.as_deref()) else {
+            return view
+        };
+        if let (true, Some(eval)) = (self.capabilities().eval, &self.state.eval) {
+            self.state.links.mount_style(eval, id, &css);
+            return view
+        }
+        if self.state.theme_css_rendered.replace(true) {
+            return view
+        }
+        self.cx.render(rsx!{
+            style { id: "{id}", dangerous_inner_html: "{css}" }
+            view
+        })
+    }
 
-    /// pulldown_cmark options.
-    /// See [`Options`][pulldown_cmark_wikilink::Options] for reference.
-    parse_options: Option<Options>,
+    /// the style keeping `scroll_offset` above an element navigated to, if it is set
+    fn scroll_margin(self) -> Option<String> {
+        self.cx.props.scroll_offset.map(|offset| format!("scroll-margin-top: {offset}px"))
+    }
 
-    #[props(default)]
-    components: CustomComponents<'a>,
+    /// the id of the `occurrence`-th reference to the footnote `label`
+    fn footnote_reference_id(label: &str, occurrence: usize) -> String {
+        match occurrence {
+            0 => format!("fnref-{label}"),
+            n => format!("fnref-{label}-{n}"),
+        }
+    }
 
-    frontmatter: Option<UseState<String>>,
-}
+    /// the description of the footnote reference containing the text at `offset`, if any
+    fn footnote_reference(self, offset: usize) -> Option<FootnoteReferenceDescription> {
+        let index = self.index();
+        let i = index.footnote_reference(offset)?;
+        let (range, label) = index.footnote_reference_at(i);
+        Some(FootnoteReferenceDescription {
+            label: label.to_string(),
+            index: index.footnote_number(label)?,
+            id: Self::footnote_reference_id(label, index.footnote_occurrence(i)),
+            range: self.state.original_range(range),
+        })
+    }
 
-#[derive(Clone, Debug)]
-pub struct MarkdownMouseEvent {
-    /// the original mouse event triggered when a text element was clicked on
-    pub mouse_event: MouseEvent,
+    fn footnote_definition(self, label: &str, range: Range<usize>, content: Element<'a>) -> FootnoteDefinitionDescription<'a> {
+        let index = self.index();
+        FootnoteDefinitionDescription {
+            label: label.to_string(),
+            index: index.footnote_number(label),
+            content,
+            back_references: (0..index.footnote_reference_count(label))
+                .map(|n| Self::footnote_reference_id(label, n))
+                .collect(),
+            range: self.state.original_range(range),
+        }
+    }
 
-    /// the corresponding range in the markdown source, as a slice of [`u8`][u8]
-    pub position: Range<usize>,
+    /// render `src`, followed by the footnotes it defines
+    fn render_document(self, src: &'a str) -> Element<'a> {
+        let document = self.document_view(src);
+        self.click_root(document)
+    }
 
-    // TODO: add a clonable tag for the type of the element
-    // pub tag: pulldown_cmark::Tag<'a>,
-}
+    fn document_view(self, src: &'a str) -> Element<'a> {
+        #[cfg(feature="debug")]
+        if self.footnotes_enabled() {
+            let unused = self.index().unused_footnotes()
+                .map(|label| format!("the footnote [^{label}] is never referenced, so it is not displayed"));
+            self.state.debug_messages.borrow_mut().extend(unused);
+        }
+        let content = render_markdown(self, src);
+        let mut footnotes = self.footnotes.take();
+        // footnotes never referenced are not displayed, like on GitHub
+        footnotes.retain(|f| f.index.is_some());
+        if footnotes.is_empty() {
+            return content
+        }
+        footnotes.sort_by_key(|f| f.index);
 
-#[derive(Clone, Copy)]
-pub struct MdContext<'a>(pub &'a Scoped<'a, MdProps<'a>>);
+        let scroll_margin = self.scroll_margin();
+        let items = footnotes.into_iter().map(|footnote| {
+            let scroll_margin = scroll_margin.clone();
+            let n = footnote.index.unwrap_or_default();
+            let content = footnote.content;
+            // numbered when there are several references, like `↩︎¹ ↩︎²`
+            let numbered = footnote.back_references.len() > 1;
+            let back_references = footnote.back_references.into_iter().enumerate().map(move |(i, id)| {
+                let n = i + 1;
+                rsx!{
+                    " "
+                    a {
+                        class: "footnote-backref",
+                        href: "#{id}",
+                        aria_label: "back to reference {n}",
+                        "↩\u{fe0e}",
+                        numbered.then(|| rsx!{ sup { "{n}" } })
+                    }
+                }
+            });
+            match self.cx.props.inline_mode {
+                true => rsx!{
+                    span { id: "fn-{n}", style: scroll_margin, " ", sup { "{n}" } " ", content, back_references }
+                },
+                false => rsx!{
+                    li { id: "fn-{n}", style: scroll_margin, value: n as i64, content, back_references }
+                },
+            }
+        });
+        match self.cx.props.inline_mode {
+            true => self.cx.render(rsx!{
+                content,
+                span { class: "footnotes", items }
+            }),
+            false => {
+                let label = self.cx.props.accessibility.as_ref().map(|a11y| a11y.footnotes_label.as_str());
+                self.cx.render(rsx!{
+                    content,
+                    section {
+                        class: "footnotes",
+                        role: label.map(|_| "doc-endnotes"),
+                        aria_label: label,
+                        ol { items }
+                    }
+                })
+            }
+        }
+    }
 
+    /// copy the url of the heading with the id `id`, if there is a clipboard, and call `on_anchor_copied`.
+    /// Without `canonical_url`, it is resolved against the current location with javascript
+    fn copy_anchor(self, id: &str) {
+        let props = self.cx.props;
+        let clipboard = self.clipboard();
+        if let Some(canonical_url) = &props.canonical_url {
+            let base = canonical_url.split('#').next().unwrap_or_default();
+            let url = format!("{base}#{id}");
+            if let Some(clipboard) = clipboard {
+                clipboard.copy(&url)
+            }
+            if let Some(on_anchor_copied) = &props.on_anchor_copied {
+                on_anchor_copied.call(url)
+            }
+            return
+        }
 
-/// component store.
-/// It is called when therer is a `<CustomComponent>` inside the markdown source.
-/// It is basically a hashmap but more efficient for a small number of items
-pub struct CustomComponents<'a>(BTreeMap<&'static str, 
-                                   Box<dyn Fn(&'a ScopeState, MdComponentProps<'a>) -> Result<Element<'a>, ComponentCreationError>>
->);
+        let location = clipboard.as_ref()
+            .and(self.state.eval.as_ref())
+            .and_then(|eval| eval.new_evaluator("return window.location.href".into()).ok());
+        let (Some(clipboard), Some(location)) = (clipboard, location) else {
+            // there is no url to copy
+            if let Some(on_anchor_copied) = &props.on_anchor_copied {
+                on_anchor_copied.call(id.to_string())
+            }
+            return
+        };
+        // the handler is called by the render this schedules
+        let copied = self.state.copied_anchors.clone();
+        let update = self.cx.schedule_update();
+        let id = id.to_string();
+        self.cx.spawn(async move {
+            let location = location.join().await.ok();
+            let base = location.as_ref()
+                .and_then(|l| l.as_str())
+                .and_then(|l| l.split('#').next())
+                .unwrap_or_default();
+            let url = format!("{base}#{id}");
+            clipboard.copy(&url);
+            copied.borrow_mut().push(url);
+            update()
+        });
+    }
 
-impl Default for CustomComponents<'_> {
-    fn default() -> Self {
-        Self (Default::default())
+    /// the `data-sourcepos` attribute of a block at `position`, if `source_positions` is set
+    fn sourcepos(self, position: &Range<usize>) -> Option<String> {
+        let props = self.cx.props;
+        let scroll_sync = props.scroll_container.is_some() && props.scroll_state.is_some();
+        (props.source_positions || scroll_sync).then(|| {
+            let range = self.state.original_range(position.clone());
+            format!("{}-{}", range.start, range.end)
+        })
     }
-}
 
-impl<'a> CustomComponents<'a> 
-{
-    pub fn new() -> Self {
-        Self(Default::default())
+    /// a `::: details` directive, as a `details` element whose open state is kept between renders
+    fn details_section(self, tag: Option<&TagEntry>, title: Option<&str>, children: Element<'a>) -> Element<'a> {
+        let id = match (title, &self.cx.props.slugify) {
+            (Some(title), Some(slugify)) => slugify(title),
+            (Some(title), None) => outline::slugify(title),
+            (None, _) => format!("section-{}", tag.map_or(0, |tag| self.state.original_offset(tag.range.start))),
+        };
+        let open = self.state.open_sections.borrow_mut()
+            .get_or_insert_with(|| self.cx.props.initial_open_sections.iter().flatten().cloned().collect())
+            .contains(&id);
+        let summary = title.unwrap_or("Details");
+        let state = self.state;
+        let props = self.cx.props;
+        // the state is toggled here rather than by the browser, so that it survives renders
+        let onclick = move |_| {
+            let open = {
+                let mut sections = state.open_sections.borrow_mut();
+                let sections = sections.get_or_insert_with(HashSet::new);
+                match sections.remove(&id) {
+                    true => false,
+                    false => sections.insert(id.clone()),
+                }
+            };
+            if let Some(on_section_toggle) = &props.on_section_toggle {
+                on_section_toggle.call(SectionToggleEvent { id: id.clone(), kind: SectionKind::Details, open })
+            }
+            self.cx.needs_update()
+        };
+        self.cx.render(rsx!{
+            details {
+                class: "details",
+                open: open.then_some("true"),
+                summary { prevent_default: "onclick", onclick: onclick, "{summary}" }
+                children
+            }
+        })
     }
 
-    /// register a new component.
-    /// The function `component` takes a context and props of type `MdComponentProps`
-    /// and returns html
-    pub fn register<F>(&mut self, name: &'static str, component: F)
-        where F: Fn(&'a ScopeState, MdComponentProps<'a>) -> Result<Element<'a>, ComponentCreationError> + 'static
-    {
-        self.0.insert(name, Box::new(component));
+    /// the html of a math expression from `cache`, or its `rendered` html, stored for the next time
+    fn cached_math(cache: &dyn RenderCache, key: CacheKey, rendered: String) -> String {
+        if let Some(html) = cache.get(&key) {
+            return html.to_string()
+        }
+        cache.put(key, rendered.as_str().into());
+        rendered
     }
 }
 
+
 impl<'a> Context<'a, 'a> for MdContext<'a> {
     type View = Element<'a>;
 
-    type Handler<T: 'a> = EventHandler<'a, T>;
+    type Handler<T: 'a> = MdHandler<'a, T>;
 
     type MouseEvent = MouseEvent;
 
     #[cfg(feature="debug")]
-    fn send_debug_info(self, info: Vec<String>) {
-        let debug = use_shared_state::<debug::EventInfo>(self.0).unwrap();
+    fn send_debug_info(self, mut info: Vec<String>) {
+        info.extend(self.state.debug_messages.take());
+        let debug = use_shared_state::<debug::EventInfo>(self.cx).unwrap();
         // to avoid re-rendering the parent component
         // if not needed
         if *debug.read().0 != info {
@@ -127,153 +2434,590 @@ impl<'a> Context<'a, 'a> for MdContext<'a> {
         }
     }
 
-    fn el_with_attributes(self, e: HtmlElement, inside: Self::View, attributes: ElementAttributes<EventHandler<'a, MouseEvent>>) -> Self::View {
+    fn el_with_attributes(self, e: HtmlElement, inside: Self::View, attributes: ElementAttributes<MdHandler<'a, MouseEvent>>) -> Self::View {
+        let tag = ElementTag::of(&e);
+        self.set_click_tag(attributes.on_click.as_ref(), tag.clone());
         let class = attributes.classes.join(" ");
-        let style = attributes.style.unwrap_or_default();
-        let onclick = attributes.on_click.unwrap_or_default();
-        let onclick = move |e| onclick.call(e);
+        let mut style = attributes.style.unwrap_or_default();
+        if let (HtmlElement::Heading(_), Some(margin)) = (&e, self.scroll_margin()) {
+            push_style(&mut style, &margin);
+        }
+        let position = attributes.on_click.as_ref().and_then(MdHandler::position);
+
+        let item_value = match (&e, &attributes.on_click) {
+            (HtmlElement::Li, Some(handler)) if self.cx.props.respect_item_numbers => handler
+                .position()
+                .and_then(|p| self.index().item_number(p.start))
+                .map(|n| n as i64),
+            _ => None,
+        };
+
+        let task = match (&e, &position) {
+            (HtmlElement::Li, Some(p)) if self.cx.props.render_tasklist_item.is_some() => self.index().task(p),
+            _ => None,
+        };
+        let inside = match (task, &self.cx.props.render_tasklist_item) {
+            (Some((index, checked, marker_range)), Some(render_tasklist_item)) => {
+                render_tasklist_item(self.cx.scope, TaskListItemDescription {
+                    checked,
+                    label: inside,
+                    index,
+                    marker_range: self.state.original_range(marker_range),
+                })
+            }
+            _ => inside,
+        };
+
+        let kind = BlockKind::of(&e);
+
+        let allowed = self.allowed_elements();
+        if matches!(e, HtmlElement::Table) && (!allowed.tables || self.cx.props.inline_mode) {
+            return self.table_fallback(position.as_ref())
+        }
+        let e = match e {
+            HtmlElement::Heading(_) if !allowed.headings => HtmlElement::Bold,
+            HtmlElement::BlockQuote if !allowed.block_quotes => HtmlElement::Div,
+            HtmlElement::Thead | HtmlElement::Trow | HtmlElement::Tcell
+                if !allowed.tables => return None,
+            e => e,
+        };
+
+        let alert = match (&e, &position) {
+            (HtmlElement::BlockQuote, Some(p)) if self.cx.props.alerts => self.index().alert(p),
+            _ => None,
+        };
+        let (e, inside) = match e {
+            _ if !self.cx.props.inline_mode => (e, inside),
+            HtmlElement::Heading(_) => (HtmlElement::Bold, inside),
+            // the code is already inside a `code` element
+            HtmlElement::Pre => (HtmlElement::Span, inside),
+            HtmlElement::Li => (HtmlElement::Span, self.cx.render(rsx!{inside, " "})),
+            HtmlElement::Div | HtmlElement::Paragraph | HtmlElement::BlockQuote
+                | HtmlElement::Ul | HtmlElement::Ol(_)
+                | HtmlElement::Thead
+                | HtmlElement::Trow | HtmlElement::Tcell => (HtmlElement::Span, inside),
+            e => (e, inside),
+        };
+
+        if let (Some(render_tables), HtmlElement::Table | HtmlElement::Thead | HtmlElement::Trow | HtmlElement::Tcell)
+            = (&self.cx.props.render_tables, &e)
+        {
+            let table = self.buffer_table(render_tables, e, inside, position.as_ref());
+            return self.with_block_chrome(kind, position, table)
+        }
+
+        if let (HtmlElement::Heading(level), Some(render_headings)) = (&e, &self.cx.props.render_headings) {
+            let text = position.as_ref()
+                .and_then(|p| self.index().heading_title(p))
+                .unwrap_or_default()
+                .to_string();
+            let heading = render_headings(self.cx.scope, HeadingDescription {
+                level: *level as u8,
+                content: inside,
+                text,
+                range: position.clone().map(|p| self.state.original_range(p)),
+            });
+            return self.with_block_chrome(kind, position, heading)
+        }
+
+        if let (HtmlElement::BlockQuote, Some(render_blockquotes)) = (&e, &self.cx.props.render_blockquotes) {
+            let quote = render_blockquotes(self.cx.scope, BlockquoteDescription {
+                content: inside,
+                range: position.clone().map(|p| self.state.original_range(p)),
+                alert,
+            });
+            return self.with_block_chrome(kind, position, quote)
+        }
+        if let Some(alert) = alert {
+            let name = alert.name();
+            let title = alert.title();
+            if self.cx.props.inline_mode {
+                return self.cx.render(rsx!{
+                    span {
+                        class: "markdown-alert markdown-alert-{name}",
+                        b { class: "markdown-alert-title", "{title}" }
+                        " "
+                        inside
+                    }
+                })
+            }
+            let quote = self.cx.render(rsx!{
+                div {
+                    class: "markdown-alert markdown-alert-{name}",
+                    p { class: "markdown-alert-title", "{title}" }
+                    inside
+                }
+            });
+            return self.with_block_chrome(kind, position, quote)
+        }
+
+        let heading_id = match (&e, &position) {
+            (HtmlElement::Heading(_), Some(p)) if self.cx.props.heading_ids || self.cx.props.heading_anchors => self.heading_id(p),
+            _ => None,
+        };
+        let inside = match &heading_id {
+            Some(id) if self.cx.props.heading_anchors => {
+                let copied = id.clone();
+                // Enter on a link is a click too
+                let onclick = move |e: MouseEvent| {
+                    let modifiers = e.modifiers();
+                    let new_tab = modifiers.intersects(Modifiers::CONTROL | Modifiers::META | Modifiers::SHIFT)
+                        || e.trigger_button().is_some_and(|b| b != MouseButton::Primary);
+                    if !new_tab {
+                        self.copy_anchor(&copied)
+                    }
+                };
+                self.cx.render(rsx!{
+                    inside,
+                    a {
+                        class: "md-anchor",
+                        href: "#{id}",
+                        aria_hidden: "true",
+                        onclick: onclick,
+                        "#"
+                    }
+                })
+            }
+            _ => inside,
+        };
+
+        if let Some(p) = position.as_ref().filter(|_| self.footnotes_enabled()) {
+            if let Some(label) = self.index().footnote_definition(p) {
+                let definition = self.footnote_definition(label, p.clone(), inside);
+                return match &self.cx.props.render_footnote_definition {
+                    Some(render_footnote_definition) => render_footnote_definition(self.cx.scope, definition),
+                    // rendered at the end of the document
+                    None => {
+                        self.footnotes.borrow_mut().push(definition);
+                        None
+                    }
+                }
+            }
+        }
+
+        let is_block = matches!(e,
+            HtmlElement::Div | HtmlElement::Paragraph | HtmlElement::BlockQuote
+            | HtmlElement::Ul | HtmlElement::Ol(_) | HtmlElement::Li | HtmlElement::Heading(_)
+            | HtmlElement::Table | HtmlElement::Thead | HtmlElement::Trow | HtmlElement::Tcell
+            | HtmlElement::Pre
+        );
+        let sourcepos = position.as_ref()
+            .filter(|_| is_block)
+            .and_then(|p| self.sourcepos(p));
+
+        let target = attributes.on_click.as_ref().and_then(|h| h.target);
+        let (onclick, md_target) = self.click_listener(self.click_handler(attributes.on_click, &tag));
+        let props = self.cx.props;
+        let mouse_listeners = props.on_double_click.is_some() || props.on_context_menu.is_some()
+            || props.on_mouse_enter.is_some() || props.on_mouse_leave.is_some();
+        let ondblclick = self.mouse_handler(props.on_double_click.as_ref(), target);
+        let oncontextmenu = self.mouse_handler(props.on_context_menu.as_ref(), target);
+        let prevent_default = if props.on_context_menu.is_some() { "oncontextmenu" } else { "" };
+        let source_range = position.clone()
+            .filter(|_| props.source_attributes)
+            .map(|p| self.state.original_range(p));
+        let md_start = source_range.as_ref().map(|r| r.start.to_string());
+        let md_end = source_range.map(|r| r.end.to_string());
+        let enter = self.hover_handler(props.on_mouse_enter.as_ref(), position.clone(), tag.clone());
+        let leave = self.hover_handler(props.on_mouse_leave.as_ref(), position.clone(), tag);
+
+        // the listeners are only added with a handler,
+        // a large document would otherwise get thousands of useless ones
+        macro_rules! element {
+            ($tag:ident $(, $name:ident: $value:expr)*) => {
+                match (onclick, mouse_listeners) {
+                    (Some(onclick), true) => rsx!{$tag {onclick: move |e| onclick.call(e), ondblclick: ondblclick, oncontextmenu: oncontextmenu, prevent_default: prevent_default, onmouseenter: enter, onmouseleave: leave, style: "{style}", class: "{class}", "data-md-start": md_start, "data-md-end": md_end, "data-sourcepos": sourcepos, $($name: $value,)* inside } },
+                    (Some(onclick), false) => rsx!{$tag {onclick: move |e| onclick.call(e), style: "{style}", class: "{class}", "data-md-start": md_start, "data-md-end": md_end, "data-sourcepos": sourcepos, $($name: $value,)* inside } },
+                    (None, true) => rsx!{$tag {ondblclick: ondblclick, oncontextmenu: oncontextmenu, prevent_default: prevent_default, onmouseenter: enter, onmouseleave: leave, style: "{style}", class: "{class}", "data-md-start": md_start, "data-md-end": md_end, "data-sourcepos": sourcepos, "data-md-target": md_target, $($name: $value,)* inside } },
+                    (None, false) => rsx!{$tag {style: "{style}", class: "{class}", "data-md-start": md_start, "data-md-end": md_end, "data-sourcepos": sourcepos, "data-md-target": md_target, $($name: $value,)* inside } },
+                }
+            };
+        }
 
         let vnode = match e {
-            HtmlElement::Div => rsx!{div {onclick:onclick, style: "{style}", class: "{class}", inside } },
-            HtmlElement::Span => rsx!{span {onclick: onclick, style: "{style}", class: "{class}", inside } },
-            HtmlElement::Paragraph => rsx!{p {onclick: onclick, style: "{style}", class: "{class}", inside } },
-            HtmlElement::BlockQuote => rsx!{blockquote {onclick: onclick, style: "{style}", class: "{class}", inside } },
-            HtmlElement::Ul => rsx!{ul {onclick: onclick, style: "{style}", class: "{class}", inside } },
-            HtmlElement::Ol(x) => rsx!{ol {onclick: onclick, style: "{style}", class: "{class}", start: x as i64, inside } },
-            HtmlElement::Li => rsx!{li {onclick: onclick, style: "{style}", class: "{class}", inside } },
-            HtmlElement::Heading(1) => rsx!{h1 {onclick: onclick, style: "{style}", class: "{class}", inside } },
-            HtmlElement::Heading(2) => rsx!{h2 {onclick: onclick, style: "{style}", class: "{class}", inside } },
-            HtmlElement::Heading(3) => rsx!{h3 {onclick: onclick, style: "{style}", class: "{class}", inside } },
-            HtmlElement::Heading(4) => rsx!{h4 {onclick: onclick, style: "{style}", class: "{class}", inside } },
-            HtmlElement::Heading(5) => rsx!{h5 {onclick: onclick, style: "{style}", class: "{class}", inside } },
-            HtmlElement::Heading(6) => rsx!{h6 {onclick: onclick, style: "{style}", class: "{class}", inside } },
+            HtmlElement::Div => element!(div),
+            HtmlElement::Span => element!(span),
+            HtmlElement::Paragraph => element!(p),
+            HtmlElement::BlockQuote => element!(blockquote),
+            HtmlElement::Ul => element!(ul),
+            HtmlElement::Ol(x) => element!(ol, start: x as i64),
+            HtmlElement::Li => element!(li, value: item_value),
+            HtmlElement::Heading(1) => element!(h1, id: heading_id),
+            HtmlElement::Heading(2) => element!(h2, id: heading_id),
+            HtmlElement::Heading(3) => element!(h3, id: heading_id),
+            HtmlElement::Heading(4) => element!(h4, id: heading_id),
+            HtmlElement::Heading(5) => element!(h5, id: heading_id),
+            HtmlElement::Heading(6) => element!(h6, id: heading_id),
             HtmlElement::Heading(_) => panic!(),
-            HtmlElement::Table => rsx!{table {onclick: onclick, style: "{style}", class: "{class}", inside } },
-            HtmlElement::Thead => rsx!{thead {onclick: onclick, style: "{style}", class: "{class}", inside } },
-            HtmlElement::Trow => rsx!{tr {onclick: onclick, style: "{style}", class: "{class}", inside } },
-            HtmlElement::Tcell => rsx!{td {onclick: onclick, style: "{style}", class: "{class}", inside } },
-            HtmlElement::Italics => rsx!{i {onclick: onclick, style: "{style}", class: "{class}", inside } },
-            HtmlElement::Bold => rsx!{b {onclick: onclick, style: "{style}", class: "{class}", inside } },
-            HtmlElement::StrikeThrough => rsx!{s {onclick: onclick, style: "{style}", class: "{class}", inside } },
-            HtmlElement::Pre => rsx!{p {onclick: onclick, style: "{style}", class: "{class}", inside } },
-            HtmlElement::Code => rsx!{code {onclick: onclick, style: "{style}", class: "{class}", inside } },
+            HtmlElement::Table => element!(table),
+            HtmlElement::Thead => element!(thead),
+            HtmlElement::Trow => element!(tr),
+            HtmlElement::Tcell => element!(td),
+            HtmlElement::Italics => element!(i),
+            HtmlElement::Bold => element!(b),
+            HtmlElement::StrikeThrough => element!(s),
+            HtmlElement::Pre => element!(pre),
+            HtmlElement::Code => element!(code),
         };
 
-        let r: Element<'a> = self.0.render(vnode);
-        r
+        let r: Element<'a> = self.cx.render(vnode);
+        self.with_block_chrome(kind, position, r)
     }
 
-    fn el_span_with_inner_html(self, inner_html: String, attributes: ElementAttributes<EventHandler<'a, MouseEvent>>) -> Self::View {
-        let class = attributes.classes.join(" ");
-        let style = attributes.style.unwrap_or_default();
-        let onclick = move |e| {
-            if let Some(f) = &attributes.on_click {
-                f.call(e)
+    fn el_span_with_inner_html(self, inner_html: String, attributes: ElementAttributes<MdHandler<'a, MouseEvent>>) -> Self::View {
+        self.set_click_tag(attributes.on_click.as_ref(), ElementTag::Html);
+        // code blocks are replaced by placeholders, to be rendered here
+        let position = attributes.on_click.as_ref().and_then(MdHandler::position);
+        if let Some(block) = position.as_ref().and_then(|p| self.code_block_at(p)) {
+            return self.code_block(block, attributes)
+        }
+
+        // `<br>` is the only way to break a line in a table cell,
+        // it has to be a real element and not a span
+        if html::parse_tag(&inner_html).is_some_and(|tag| tag.name.eq_ignore_ascii_case("br")) {
+            return self.el_br()
+        }
+
+        if self.cx.props.trusted_html {
+            let html_event = self.index()
+                .html_event(self.src, self.state.html_cursor.get(), &inner_html);
+
+            if let Some((i, part)) = html_event {
+                self.state.html_cursor.set(i + 1);
+                return match part {
+                    HtmlPart::RunStart(range) => {
+                        let html = &self.src[range];
+                        self.cx.render(rsx!{
+                            div { dangerous_inner_html: "{html}" }
+                        })
+                    }
+                    HtmlPart::Merged => None,
+                }
             }
-        };
-        self.0.render(rsx!{
-            span {
-                dangerous_inner_html: "{inner_html}",
-                style: "{style}",
-                class: "{class}",
-                onclick: onclick
+        }
+
+        let is_math = attributes.classes.iter().any(|c| c.starts_with("math"));
+        let display = attributes.classes.iter().any(|c| c.contains("display"));
+        if let (true, Some(render_math)) = (is_math, &self.cx.props.render_math) {
+            let tex = match &position {
+                Some(p) => self.src[p.clone()].trim().trim_matches('$').to_string(),
+                None => inner_html,
+            };
+            return render_math(self.cx.scope, MathDescription {
+                tex,
+                display,
+                range: position.map(|p| self.state.original_range(p)),
+            })
+        }
+        if let (true, false, Some(p)) = (is_math, self.cx.props.math, &position) {
+            let tex = &self.src[p.clone()];
+            return self.cx.render(rsx!{
+                code { class: "math-source", "{tex}" }
+            })
+        }
+        let inner_html = match (is_math, self.cache(), &position) {
+            (true, Some(cache), Some(p)) => {
+                let tex = self.src[p.clone()].trim().trim_matches('$').to_string();
+                Self::cached_math(cache, CacheKey::Math { tex, display }, inner_html)
             }
-        })
+            _ => inner_html,
+        };
+        let (role, label) = match &self.cx.props.accessibility {
+            Some(a11y) if is_math => (Some("img"), Some(a11y.math_label.as_str())),
+            _ => (None, None),
+        };
+
+        let class = attributes.classes.join(" ");
+        let style = attributes.style.unwrap_or_default();
+        let (on_click, target) = self.click_listener(self.click_handler(attributes.on_click, &ElementTag::Html));
+        match on_click {
+            Some(on_click) => self.cx.render(rsx!{
+                span {
+                    dangerous_inner_html: "{inner_html}",
+                    style: "{style}",
+                    class: "{class}",
+                    role: role,
+                    aria_label: label,
+                    onclick: move |e| on_click.call(e)
+                }
+            }),
+            None => self.cx.render(rsx!{
+                span {
+                    dangerous_inner_html: "{inner_html}",
+                    style: "{style}",
+                    class: "{class}",
+                    role: role,
+                    aria_label: label,
+                    "data-md-target": target,
+                }
+            }),
+        }
     }
 
-    fn el_hr(self, attributes: ElementAttributes<EventHandler<'a, MouseEvent>>) -> Self::View {
+    fn el_hr(self, attributes: ElementAttributes<MdHandler<'a, MouseEvent>>) -> Self::View {
+        self.set_click_tag(attributes.on_click.as_ref(), ElementTag::Rule);
+        if !self.allowed_elements().rules || self.cx.props.inline_mode {
+            return None
+        }
         let class = attributes.classes.join(" ");
         let style = attributes.style.unwrap_or_default();
-        let onclick = move |e| {
-            if let Some(f) = &attributes.on_click {
-                f.call(e)
-            }
-        };
-        self.0.render(rsx!(hr {onclick: onclick, style: "{style}", class: "{class}"}))
+        let (on_click, target) = self.click_listener(self.click_handler(attributes.on_click, &ElementTag::Rule));
+        match on_click {
+            Some(on_click) => self.cx.render(rsx!(hr {onclick: move |e| on_click.call(e), style: "{style}", class: "{class}"})),
+            None => self.cx.render(rsx!(hr {style: "{style}", class: "{class}", "data-md-target": target})),
+        }
     }
 
     fn el_br(self)-> Self::View {
-        self.0.render(rsx!(br {}))
+        self.cx.render(rsx!(br {}))
     }
 
     fn el_fragment(self, children: Vec<Self::View>) -> Self::View {
-        self.0.render(
+        self.cx.render(
             rsx!{children.into_iter()}
         )
     }
 
     fn el_a(self, children: Self::View, href: String) -> Self::View {
-        self.0.render(
-            rsx!{a {href: "{href}", children}}
-        )
+        // the custom footnote reference replaces the link
+        if self.state.footnote_rendered.take() && href.starts_with('#') {
+            return children
+        }
+
+        let entry = self.next_link(&href);
+        let href = self.resolve_href(href, entry);
+        self.anchor(children, href, entry)
     }
 
     fn el_img(self, src: String, alt: String) -> Self::View {
-        self.0.render(
+        // the alt text may contain markdown, which must be flattened to plain text
+        let entry = self.index().image(self.state.image_cursor.get(), &src);
+        let (alt, title, position, alt_range) = match entry {
+            Some((i, image)) => {
+                self.state.image_cursor.set(i + 1);
+                (image.alt.clone(), image.title.clone(), Some(image.range.clone()), image.alt_range.clone())
+            }
+            None => (alt, String::new(), None, None),
+        };
+        let range = position.clone().map(|p| self.state.original_range(p));
+
+        if !self.allowed_elements().images {
+            return self.cx.render(rsx!{"{alt}"})
+        }
+        if let Some(render_images) = &self.cx.props.render_images {
+            let alt_content = match alt_range {
+                Some(alt_range) => {
+                    let tree = tree::parse_rewritten(&self.src[alt_range], &self.cx.props.parse_config(), |_| false);
+                    self.alt_view(&tree)
+                }
+                None => None,
+            };
+            return render_images(self.cx.scope, ImageDescription { url: src, alt, alt_content, title, range })
+        }
+
+        // images are reported to `on_click` like links
+        let props = self.cx.props;
+        let click_target = position
+            .filter(|_| props.on_click.is_some() && props.click_targets.map_or(true, |f| f.images))
+            .map(|position| ClickTarget {
+                position,
+                stop_propagation: true,
+                tag: ElementTag::Image { src: src.clone() },
+            });
+        let Some(target) = click_target else {
+            return self.cx.render(
+                rsx!(
+                    img {src: "{src}", alt: "{alt}"}
+                )
+            )
+        };
+        self.cx.render(
             rsx!(
-                img {src: "{src}", alt: "{alt}"}
+                img {
+                    src: "{src}",
+                    alt: "{alt}",
+                    onclick: move |mouse_event| {
+                        if let Some(on_click) = &props.on_click {
+                            let propagation = props.click_propagation;
+                            on_click.call(self.state.target_event(target.clone(), propagation, mouse_event))
+                        }
+                    },
+                }
             )
         )
     }
 
     fn el_text(self, text: CowStr<'a>) -> Self::View {
-        self.0.render(rsx!{text.as_ref()})
+        // the renderer emits soft breaks as whitespace that is not part of the source
+        let is_soft_break = matches!(text.as_ref(), " " | "\n")
+            && index::offset_in(self.src, &text).is_none();
+        if is_soft_break && self.cx.props.soft_break == SoftBreakMode::Nothing {
+            return None
+        }
+
+        let props = self.cx.props;
+        // only the text right before a link can be a footnote reference wrapped in it
+        self.state.footnote_rendered.set(false);
+        let reference = index::offset_in(self.src, &text)
+            .filter(|_| self.footnotes_enabled())
+            .and_then(|offset| self.footnote_reference(offset));
+        if let Some(reference) = reference {
+            self.state.footnote_rendered.set(true);
+            return match &props.render_footnote_reference {
+                Some(render_footnote_reference) => render_footnote_reference(self.cx.scope, reference),
+                None => {
+                    let scroll_margin = self.scroll_margin();
+                    self.cx.render(rsx!{
+                        sup {
+                            class: "footnote-reference",
+                            a { id: "{reference.id}", style: scroll_margin, href: "#fn-{reference.index}", "{reference.index}" }
+                        }
+                    })
+                }
+            }
+        }
+
+        // the marker of an alert is replaced by its title
+        let in_alert_marker = props.alerts && index::offset_in(self.src, &text)
+            .is_some_and(|offset| self.index().in_alert_marker(offset));
+        if in_alert_marker {
+            return None
+        }
+
+        if !props.decode_entities && !is_soft_break && index::offset_in(self.src, &text).is_none() {
+            let entity = self.index().entity(self.state.entity_cursor.get(), &text);
+            if let Some((i, range)) = entity {
+                self.state.entity_cursor.set(i + 1);
+                let written = &self.src[range];
+                return self.cx.render(rsx!{written})
+            }
+        }
+
+        if props.inline_code_languages && !is_soft_break {
+            if let Some(view) = self.annotated_code_text(&text) {
+                return view
+            }
+        }
+
+        if let (Some(transform), false) = (&props.transform_text, is_soft_break) {
+            let offset = index::offset_in(self.src, &text)
+                .filter(|offset| !self.index().in_code(*offset));
+            if let Some(offset) = offset {
+                let range = self.state.original_range(offset..offset + text.len());
+                if let Some(view) = transform(self.cx.scope, &text, range) {
+                    return view
+                }
+            }
+        }
+
+        // emoji, ruby annotations and line breaks are applied one after the other
+        let transformed = props.emoji || props.break_long_words.is_some() || props.ruby_annotator.is_some();
+        if is_soft_break || !transformed || self.is_code_text(&text) {
+            return self.cx.render(rsx!{text.as_ref()})
+        }
+
+        let in_autolink = index::offset_in(self.src, &text)
+            .is_some_and(|offset| self.index().in_autolink(offset));
+        let annotator = props.ruby_annotator.as_deref().filter(|_| !in_autolink);
+        let custom = |name: &str| props.custom_emoji.as_ref()?.get(name).map(String::as_str);
+        let emoji_pieces = props.emoji
+            .then(|| emoji::split_shortcodes(&text, custom))
+            .flatten()
+            .unwrap_or_else(|| vec![EmojiPiece::Text(&text)]);
+        self.cx.render(rsx!{
+            emoji_pieces.into_iter().map(|piece| match piece {
+                EmojiPiece::Text(text) => {
+                    let pieces = text::text_pieces(text, annotator, props.break_long_words);
+                    rsx!{
+                        pieces.into_iter().map(|piece| match piece {
+                            TextPiece::Text(text) => rsx!{ "{text}" },
+                            TextPiece::Ruby { base, reading } => rsx!{ ruby { "{base}" rt { "{reading}" } } },
+                            TextPiece::Break => rsx!{ wbr {} },
+                        })
+                    }
+                }
+                EmojiPiece::Emoji(emoji) => rsx!{ "{emoji}" },
+                EmojiPiece::Custom { shortcode, url } => rsx!{
+                    img { class: "emoji", src: "{url}", alt: "{shortcode}", title: "{shortcode}" }
+                },
+            })
+        })
     }
 
     fn mount_dynamic_link(self, rel: &str, href: &str, integrity: &str, crossorigin: &str) {
-        // let create_eval = use_eval(self.0);
-
-        // let eval = create_eval(
-        //     r#"
-        //     // https://stackoverflow.com/a/18510577
-        //     let rel = await dioxus.recv();
-        //     let href = await dioxus.recv();
-        //     let integrity = await dioxus.recv();
-        //     let crossorigin = await dioxus.recv();
-        //     var newstyle = document.createElement("link"); // Create a new link Tag
-
-        //     newstyle.setAttribute("rel", rel);
-        //     newstyle.setAttribute("type", "text/css");
-        //     newstyle.setAttribute("href", href); 
-        //     newstyle.setAttribute("crossorigin", crossorigin); 
-        //     newstyle.setAttribute("integrity", integrity); 
-        //     document.getElementsByTagName("head")[0].appendChild(newstyle);
-        //     "#,
-        // )
-        // .unwrap();
-
-        // // You can send messages to JavaScript with the send method
-        // eval.send(rel.into()).unwrap();
-        // eval.send(href.into()).unwrap();
-        // eval.send(integrity.into()).unwrap();
-        // eval.send(crossorigin.into()).unwrap();
-    }
-
-
-    fn el_input_checkbox(self, checked: bool, attributes: ElementAttributes<EventHandler<'a, MouseEvent>>) -> Self::View {
+        // there is no document to mount the link in,
+        // or no katex output to style
+        if !self.capabilities().eval || !self.cx.props.math {
+            return
+        }
+
+        if let Some(eval) = &self.state.eval {
+            self.state.links.mount(eval, rel, href, integrity, crossorigin)
+        }
+    }
+
+
+    fn el_input_checkbox(self, checked: bool, attributes: ElementAttributes<MdHandler<'a, MouseEvent>>) -> Self::View {
+        self.set_click_tag(attributes.on_click.as_ref(), ElementTag::Checkbox { checked });
+        let task = self.state.task_cursor.get();
+        self.state.task_cursor.set(task + 1);
+        // the checkbox is rendered with the rest of the item
+        if self.cx.props.render_tasklist_item.is_some() {
+            return None
+        }
         let class = attributes.classes.join(" ");
         let style = attributes.style.unwrap_or_default();
+        // the tasks of nested markdown are not in the source, they can not be toggled there
+        let nested = self.state.depth > 0;
+        let on_checkbox_toggle = self.cx.props.on_checkbox_toggle.as_ref().filter(|_| !nested);
+        let toggle = on_checkbox_toggle
+            .and_then(|_| self.index().task_at(task))
+            .map(|(checked, marker)| CheckboxToggleEvent {
+                index: task,
+                checked: !checked,
+                marker_range: self.state.original_range(marker),
+                source: self.state.source.clone(),
+            });
+        let task_list_sync = self.cx.props.task_list_sync.as_ref().filter(|_| !nested);
+        let (on_click, target) = self.click_listener(self.click_handler(attributes.on_click, &ElementTag::Checkbox { checked }));
+        let has_handler = on_click.is_some() || on_checkbox_toggle.is_some() || task_list_sync.is_some();
         let onclick = move |e| {
-            if let Some(f) = &attributes.on_click {
+            if let Some(f) = &on_click {
                 f.call(e)
             }
+            if let (Some(on_checkbox_toggle), Some(toggle)) = (on_checkbox_toggle, &toggle) {
+                on_checkbox_toggle.call(toggle.clone())
+            }
+            if let Some(source) = task_list_sync {
+                self.toggle_task(source, task)
+            }
         };
-        self.0.render(rsx!(input {
+        if !has_handler {
+            return self.cx.render(rsx!(input {
+                r#type: "checkbox", 
+                checked: checked, 
+                style: "{style}", 
+                class: "{class}",
+                "data-md-target": target,
+            }))
+        }
+        self.cx.render(rsx!(input {
             r#type: "checkbox", 
             checked: checked, 
             style: "{style}", 
             class: "{class}",
+            "data-md-target": target,
             onclick: onclick
         }))
     }
 
     fn props(self) -> rust_web_markdown::MarkdownProps<'a> {
-        let props = self.0.props;
+        let props = self.cx.props;
 
         rust_web_markdown::MarkdownProps {
-            hard_line_breaks: props.hard_line_breaks,
+            hard_line_breaks: props.hard_line_breaks || props.soft_break == SoftBreakMode::Br,
             wikilinks: props.wikilinks,
-            parse_options: props.parse_options.as_ref(),
+            parse_options: Some(&*self.cx.bump().alloc(self.parse_options())),
             theme: props.theme.as_deref(),
         }
 
@@ -284,48 +3028,318 @@ impl<'a> Context<'a, 'a> for MdContext<'a> {
     }
 
     fn make_md_handler(self, position: std::ops::Range<usize>, stop_propagation: bool) -> Self::Handler<MouseEvent> {
-        let on_click = self.0.props.on_click.as_ref();
+        let on_click = self.cx.props.on_click.as_ref();
+        let propagation = self.cx.props.click_propagation;
+        let state = self.state;
 
-        self.0.event_handler(move |e: MouseEvent| {
-            if stop_propagation{
-                e.stop_propagation()
-            }
+        // the handler only captures the index of its target,
+        // the rest is looked up in the render state when clicked
+        let mut targets = state.click_targets.borrow_mut();
+        let target = targets.len();
+        targets.push(ClickTarget {
+            position: position.clone(),
+            stop_propagation,
+            tag: ElementTag::Other,
+        });
 
-            let report = MarkdownMouseEvent {
-                position: position.clone(),
-                mouse_event: e
-            };
+        let handler = match state.delegates_clicks.get() {
+            // the element gets no listener, see `click_root`
+            true => EventHandler::default(),
+            false => self.cx.event_handler(move |e: MouseEvent| {
+                let report = state.mouse_event(target, propagation, e);
+                on_click.map(|x| x.call(report));
+            }),
+        };
 
-            on_click.map(|x| x.call(report));
-        })
+        MdHandler {
+            handler,
+            position: Some(position),
+            target: Some(target),
+        }
     }
 
     fn set_frontmatter(self, frontmatter: String) {
-        self.0.props.frontmatter.as_ref().map(|x| x.set(frontmatter));
+        if let Some(on_frontmatter) = &self.cx.props.on_frontmatter {
+            on_frontmatter.call(frontmatter.clone())
+        }
+        self.cx.props.frontmatter.as_ref().map(|x| x.set(frontmatter));
     }
 
     fn has_custom_links(self) -> bool {
-        self.0.props.render_links.is_some()
+        self.cx.props.render_links.is_some() || self.cx.props.render_links_with_details.is_some()
     }
 
-    fn render_links(self, link: LinkDescription<Self::View>) 
+    fn render_links(self, mut link: LinkDescription<Self::View>) 
         -> Result<Self::View, String> {
-        // TODO: remove the unwrap call
-        Ok(self.0.props.render_links.as_ref().unwrap()(self.0.scope, link))
+        let entry = self.next_link(&link.url);
+        link.url = self.resolve_href(link.url, entry);
+        let props = self.cx.props;
+        Ok(match (&props.render_links_with_details, &props.render_links) {
+            (Some(render_links), _) => render_links(self.cx.scope, self.link_details(link, entry)),
+            (None, Some(render_links)) => render_links(self.cx.scope, link),
+            // has_custom_links was true when the renderer checked,
+            // but the default rendering is always available
+            (None, None) => self.anchor(link.content, link.url, entry),
+        })
     }
 
     fn has_custom_component(self, name: &str) -> bool {
-        self.0.props.components.0.get(name).is_some()
+        self.cx.props.directives && name == directive::FALLBACK_TAG
+            || self.rewritten_tag(name).is_some()
+            || self.cx.props.components.handles(name)
+            || self.registry().is_some_and(|r| r.get(name, self.cx.props.components.ignores_case()).is_some())
+    }
+
+    fn render_custom_component(self, name: &str, mut input: rust_web_markdown::MdComponentProps<Self::View>) -> Result<Self::View, ComponentCreationError> {
+        let tag = self.component_tag(name);
+        if let Some(element_tag) = self.rewritten_tag(name) {
+            return Ok(self.rewritten_element(element_tag, tag, input.children))
+        }
+        let inline = tag.is_some_and(|tag| tag.inline);
+        // `<Alert dismissible/>` is read as `dismissible="true"`
+        for flag in tag.iter().flat_map(|tag| &tag.flags) {
+            if let Some(value) = input.attributes.get_mut(flag).filter(|v| v.is_empty()) {
+                *value = "true".into()
+            }
+        }
+        // like in html, `title="&quot;a&quot;"` is `"a"`
+        for value in input.attributes.values_mut() {
+            if let std::borrow::Cow::Owned(unescaped) = html::unescape_attribute(value) {
+                *value = unescaped
+            }
+        }
+        let children_source = tag
+            .and_then(|tag| tag.children.clone())
+            .map(|range| self.state.original_range(range));
+        let call = components::ComponentCall {
+            children_source,
+            outline: self.outline(),
+            toc_label: self.cx.props.accessibility.as_ref().map(|a11y| a11y.toc_label.clone()),
+        };
+        let result = components::with_call(call, || {
+            self.try_render_component(name, tag, input)
+        });
+        // a broken component must not prevent the rest of the document from rendering
+        let view = match result {
+            Ok(view) => view,
+            Err(error) => {
+                let message = error.to_string();
+                let offset = tag.map(|tag| self.state.original_offset(tag.range.start));
+                let error = ComponentError {
+                    name: name.to_string(),
+                    attribute: quoted_name(&message).map(String::from),
+                    message,
+                    offset,
+                    line: offset.map(|offset| self.state.source[..offset].matches('\n').count() + 1),
+                };
+                #[cfg(feature="debug")]
+                self.state.debug_messages.borrow_mut().push(error.to_string());
+                self.component_error(error)
+            }
+        };
+        // keep the text flow of the paragraph around the component
+        Ok(match inline {
+            true => self.cx.render(rsx!{
+                span { class: "md-component-inline", view }
+            }),
+            false => view,
+        })
+    }
+}
+
+/// the first name quoted with backticks or single quotes in `message`
+fn quoted_name(message: &str) -> Option<&str> {
+    message.split(['`', '\''])
+        .skip(1)
+        .step_by(2)
+        .find(|word| html::is_name(word))
+}
+
+/// append a css declaration to an inline style
+fn push_style(style: &mut String, declaration: &str) {
+    if !style.is_empty() && !style.trim_end().ends_with(';') {
+        style.push(';');
+    }
+    style.push_str(declaration);
+}
+
+impl MdProps<'_> {
+    /// how the markdown is parsed with these props,
+    /// to parse it like the renderer with [`parse_to_tree`]
+    pub fn parse_config(&self) -> ParseConfig {
+        ParseConfig {
+            options: parse_options(self),
+            wikilinks: self.wikilinks,
+            normalize_newlines: self.normalize_newlines,
+            directives: self.directives,
+            definition_lists: self.definition_lists,
+            mark: self.mark,
+            autolinks: self.autolinks,
+        }
     }
+}
+
+/// the options the markdown is parsed with when `parse_options` is not set:
+/// every option, but the ones turned on by the extension props
+fn default_parse_options() -> Options {
+    Options::all()
+        - Options::ENABLE_SMART_PUNCTUATION
+        - Options::ENABLE_TABLES
+        - Options::ENABLE_STRIKETHROUGH
+        - Options::ENABLE_TASKLISTS
+        - Options::ENABLE_FOOTNOTES
+}
 
-    fn render_custom_component(self, name: &str, input: rust_web_markdown::MdComponentProps<Self::View>) -> Result<Self::View, ComponentCreationError> {
-        let f = self.0.props.components.0.get(name).unwrap();
-        f(self.0.scope, input)
+/// the options the markdown is parsed with, given the props
+fn parse_options(props: &MdProps) -> Options {
+    let mut options = props.parse_options.unwrap_or_else(default_parse_options);
+    for (enabled, option) in [
+        (props.smart_punctuation, Options::ENABLE_SMART_PUNCTUATION),
+        (props.tables, Options::ENABLE_TABLES),
+        (props.strikethrough, Options::ENABLE_STRIKETHROUGH),
+        (props.task_lists, Options::ENABLE_TASKLISTS),
+        (props.footnotes, Options::ENABLE_FOOTNOTES),
+    ] {
+        if enabled {
+            options.insert(option)
+        }
     }
+    options
 }
 
 #[allow(non_snake_case)]
 pub fn Markdown<'a>(cx: &'a Scoped<MdProps<'a>>) -> Element<'a> {
-    let context = MdContext(cx);
-    render_markdown(context, cx.props.src)
+    let eval = cx.use_hook(|| cx.consume_context::<Rc<dyn EvalProvider>>()).clone();
+    let state = cx.use_hook(RenderState::default);
+    // the clicks received by the root hit the targets of the previous render
+    let clicks = state.clicks.next_render();
+    if let Some(on_click) = &cx.props.on_click {
+        clicks.into_iter().for_each(|click| state.report_click(on_click, click, cx.props.click_propagation));
+    }
+    state.reset(cx.props.src, eval);
+    state.links.unmount_unused.set(cx.props.unmount_unused);
+    if let Some(on_link_visible) = &cx.props.on_link_visible {
+        state.visibility.take().into_iter().for_each(|href| on_link_visible.call(href));
+    }
+    let copied_anchors = state.copied_anchors.take();
+    if let Some(on_anchor_copied) = &cx.props.on_anchor_copied {
+        copied_anchors.into_iter().for_each(|url| on_anchor_copied.call(url));
+    }
+
+    // set by a task once a new source is displayed,
+    // `on_render_complete` is called by the render it schedules.
+    // Without the handler, nothing is scheduled, so a new source is rendered once
+    let completed = cx.use_hook(|| Rc::new(Cell::new(false)));
+    let displayed = cx.use_hook(|| Cell::new(None));
+    if cx.props.on_render_complete.is_some() {
+        let hash = content_hash(cx.props.src);
+        if displayed.replace(Some(hash)) != Some(hash) {
+            let completed = completed.clone();
+            let update = cx.schedule_update();
+            cx.spawn(async move {
+                completed.set(true);
+                update()
+            });
+        }
+    }
+
+    let provided_registry = cx.use_hook(|| cx.consume_context::<ComponentRegistry>());
+    state.provided_registry = provided_registry.clone();
+    let subscription = cx.use_hook(|| None::<components::RegistrySubscription>);
+    match cx.props.registry.as_ref().or(provided_registry.as_ref()) {
+        Some(registry) if subscription.as_ref().is_some_and(|s| s.registry.same(registry)) => (),
+        Some(registry) => *subscription = Some(registry.subscribe(cx)),
+        None => *subscription = None,
+    }
+
+    // positions are reported in the original source, undoing each rewrite
+    let config = cx.props.parse_config();
+    let mut source = rewrite::RewrittenSource::new(cx.props.src, &config, |name| {
+        cx.props.components.handles(name)
+            || cx.props.registry.as_ref().or(provided_registry.as_ref())
+                .is_some_and(|r| r.get(name, cx.props.components.ignores_case()).is_some())
+    });
+    // last, so that the code blocks are the ones the renderer would see
+    let (code_blocks, rewritten) = code::rewrite_code_blocks(&source.src, config.options, config.wikilinks);
+    source.apply(|_| rewritten);
+    let src: &'a str = match source.src {
+        std::borrow::Cow::Borrowed(src) => src,
+        std::borrow::Cow::Owned(src) => cx.bump().alloc_str(&src),
+    };
+    state.removed_cr = source.removed_cr.into();
+    state.rewrites = source.rewrites.into();
+    state.code_blocks = code_blocks.into();
+
+    let context = MdContext::new(cx, state, src);
+    let delegates_clicks = cx.props.delegate_clicks && cx.props.on_click.is_some() && context.capabilities().eval;
+    context.state.delegates_clicks.set(delegates_clicks);
+
+    if let Some(on_outline_change) = &cx.props.on_outline_change {
+        context.report_outline(on_outline_change);
+    }
+
+    if cx.props.on_link_visible.is_some() && context.capabilities().eval {
+        if let Some(eval) = &state.eval {
+            state.visibility.observe(cx.scope, eval)
+        }
+    }
+
+    if let (Some(container), Some(scroll_state)) = (&cx.props.scroll_container, &cx.props.scroll_state) {
+        if context.capabilities().eval {
+            if let Some(eval) = &state.eval {
+                state.scroll.observe(cx.scope, eval, container, scroll_state)
+            }
+        }
+    }
+
+    if let Some(on_degraded) = &cx.props.on_degraded {
+        capabilities::report(context.capabilities(), |report| on_degraded.call(report));
+    }
+
+    context.state.loads.start_render();
+    let rendered = std::panic::catch_unwind(
+        std::panic::AssertUnwindSafe(|| match cx.props.chunked_render {
+            Some(config) => context.render_chunked(config),
+            None => context.render_document(src),
+        })
+    );
+    context.state.loads.finish_render(cx);
+
+    if completed.take() {
+        if let Some(on_render_complete) = &cx.props.on_render_complete {
+            on_render_complete.call(context.index().stats())
+        }
+    }
+
+    if let Some(broken_links) = &cx.props.broken_links {
+        let broken = context.state.broken_links.take();
+        // setting the same value would render again forever
+        if *broken_links.get() != broken {
+            broken_links.set(broken)
+        }
+    }
+
+    rendered.unwrap_or_else(|panic| {
+        let message = panic.downcast_ref::<&str>().map(|s| s.to_string())
+            .or_else(|| panic.downcast_ref::<String>().cloned())
+            .unwrap_or_default();
+
+        let failure = RenderFailure {
+            message,
+            src: cx.props.src.to_string(),
+        };
+        // the renderer stopped before reporting its debug info
+        #[cfg(feature="debug")]
+        context.send_debug_info(vec![format!("failed to render markdown: {}", failure.message)]);
+
+        match &cx.props.error_boundary {
+            Some(boundary) => boundary(cx.scope, failure),
+            None if cfg!(debug_assertions) => render!{
+                div { class: "markdown-error", "failed to render markdown: {failure.message}" }
+            },
+            None => render!{
+                div { class: "markdown-error", "failed to render markdown" }
+            },
+        }
+    })
 }
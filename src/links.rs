@@ -0,0 +1,182 @@
+use std::cell::{Cell, RefCell};
+use std::rc::Rc;
+
+use dioxus::html::prelude::EvalProvider;
+
+use crate::clipboard::js_string;
+
+/// a `<link>` mounted in the head of the document by `mount_dynamic_link`
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct MountedLink {
+    pub rel: String,
+    pub href: String,
+    pub integrity: String,
+    pub crossorigin: String,
+    /// number of mounted `Markdown` instances that requested it
+    pub users: usize,
+}
+
+impl MountedLink {
+    fn key(&self) -> (&str, &str) {
+        (&self.href, &self.integrity)
+    }
+}
+
+thread_local! {
+    // links of the app, in the order they were mounted,
+    // so that the css cascade does not depend on render order
+    static REGISTRY: RefCell<Vec<MountedLink>> = RefCell::new(Vec::new());
+    // ids of the `<style>` elements mounted by `mount_style`, with their number of users
+    static STYLES: RefCell<Vec<(String, usize)>> = RefCell::new(Vec::new());
+}
+
+/// the links currently in the registry, in mount order
+pub(crate) fn mounted_links() -> Vec<MountedLink> {
+    REGISTRY.with(|registry| registry.borrow().clone())
+}
+
+/// the links requested by one `Markdown` instance.
+/// They are released when it is dropped, with the instance
+#[derive(Default)]
+pub(crate) struct LinkUser {
+    /// href and integrity of the links requested
+    keys: RefCell<Vec<(String, String)>>,
+    /// ids of the styles requested
+    styles: RefCell<Vec<String>>,
+    eval: RefCell<Option<Rc<dyn EvalProvider>>>,
+    /// remove links from the document when their last user is dropped
+    pub unmount_unused: Cell<bool>,
+}
+
+impl LinkUser {
+    /// mount a link if no other instance did, and register this instance as a user
+    pub fn mount(&self, eval: &Rc<dyn EvalProvider>, rel: &str, href: &str, integrity: &str, crossorigin: &str) {
+        let key = (href.to_string(), integrity.to_string());
+        if self.keys.borrow().contains(&key) {
+            return
+        }
+        self.keys.borrow_mut().push(key);
+        self.eval.replace(Some(eval.clone()));
+
+        REGISTRY.with(|registry| {
+            let mut registry = registry.borrow_mut();
+            if let Some(link) = registry.iter_mut().find(|link| link.key() == (href, integrity)) {
+                link.users += 1;
+                return
+            }
+
+            let script = format!(
+                r#"if (!document.querySelector("link[href=" + CSS.escape({href}) + "]")) {{
+                    let link = document.createElement("link");
+                    link.setAttribute("rel", {rel});
+                    link.setAttribute("type", "text/css");
+                    link.setAttribute("href", {href});
+                    link.setAttribute("crossorigin", {crossorigin});
+                    link.setAttribute("integrity", {integrity});
+                    document.head.appendChild(link);
+                }}"#,
+                href = js_string(href),
+                rel = js_string(rel),
+                crossorigin = js_string(crossorigin),
+                integrity = js_string(integrity),
+            );
+            // mounting is best effort, the content is still readable without the stylesheet
+            let _ = eval.new_evaluator(script);
+
+            registry.push(MountedLink {
+                rel: rel.into(),
+                href: href.into(),
+                integrity: integrity.into(),
+                crossorigin: crossorigin.into(),
+                users: 1,
+            });
+        })
+    }
+}
+
+impl LinkUser {
+    /// add a `<style>` element with the id `id` to the head of the document if no other instance did,
+    /// and register this instance as a user.
+    /// Like links, it is removed with its last user if `unmount_unused` is set
+    pub fn mount_style(&self, eval: &Rc<dyn EvalProvider>, id: &str, css: &str) {
+        if self.styles.borrow().iter().any(|style| style == id) {
+            return
+        }
+        self.styles.borrow_mut().push(id.to_string());
+        self.eval.replace(Some(eval.clone()));
+
+        STYLES.with(|styles| {
+            let mut styles = styles.borrow_mut();
+            if let Some((_, users)) = styles.iter_mut().find(|(style, _)| style == id) {
+                *users += 1;
+                return
+            }
+
+            let script = format!(
+                r#"if (!document.getElementById({id})) {{
+                    let style = document.createElement("style");
+                    style.id = {id};
+                    style.textContent = {css};
+                    document.head.appendChild(style);
+                }}"#,
+                id = js_string(id),
+                css = js_string(css),
+            );
+            let _ = eval.new_evaluator(script);
+            styles.push((id.to_string(), 1));
+        })
+    }
+}
+
+impl Drop for LinkUser {
+    fn drop(&mut self) {
+        let eval = self.eval.get_mut().take();
+        let unmount = self.unmount_unused.get();
+
+        STYLES.with(|styles| {
+            let mut styles = styles.borrow_mut();
+            for id in self.styles.get_mut().drain(..) {
+                let Some(i) = styles.iter().position(|(style, _)| *style == id) else {
+                    continue
+                };
+                let users = &mut styles[i].1;
+                *users = users.saturating_sub(1);
+                if *users > 0 || !unmount {
+                    continue
+                }
+
+                styles.remove(i);
+                if let Some(eval) = &eval {
+                    let script = format!(
+                        r#"document.getElementById({})?.remove()"#,
+                        js_string(&id),
+                    );
+                    let _ = eval.new_evaluator(script);
+                }
+            }
+        });
+
+        REGISTRY.with(|registry| {
+            let mut registry = registry.borrow_mut();
+            for (href, integrity) in self.keys.get_mut().drain(..) {
+                let Some(i) = registry.iter().position(|link| link.key() == (&href, &integrity)) else {
+                    continue
+                };
+                let link = &mut registry[i];
+                link.users = link.users.saturating_sub(1);
+                if link.users > 0 || !unmount {
+                    continue
+                }
+
+                registry.remove(i);
+                if let Some(eval) = &eval {
+                    let script = format!(
+                        r#"document.querySelectorAll("link[href=" + CSS.escape({}) + "]").forEach(link => link.remove())"#,
+                        js_string(&href),
+                    );
+                    let _ = eval.new_evaluator(script);
+                }
+            }
+        })
+    }
+}
@@ -0,0 +1,79 @@
+use std::any::Any;
+use std::cell::RefCell;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::future::Future;
+use std::pin::Pin;
+use std::rc::Rc;
+
+use dioxus::prelude::*;
+
+/// the value loaded by an async component, or the error message
+pub(crate) type Loaded = Result<Rc<dyn Any>, String>;
+
+/// starts the load of an async component, given the attributes of its tag
+pub(crate) type LoadFn = dyn Fn(BTreeMap<String, String>) -> Pin<Box<dyn Future<Output=Loaded>>>;
+
+/// a use of an async component: its name and attributes
+type LoadKey = (String, BTreeMap<String, String>);
+
+enum Load {
+    Pending(TaskId),
+    Done(Loaded),
+}
+
+/// the loads of the async components of a document, kept between renders.
+/// Clones refer to the same loads, so that nested markdown shares them
+#[derive(Clone, Default)]
+pub(crate) struct AsyncLoads {
+    loads: Rc<RefCell<HashMap<LoadKey, Load>>>,
+    /// the loads used by the current render
+    used: Rc<RefCell<HashSet<LoadKey>>>,
+}
+
+impl AsyncLoads {
+    /// the result of the load of the component `name` with `attributes`,
+    /// or `None` if it is not done yet. The load is started with `load` if needed,
+    /// and `cx` is rendered again when it is done
+    pub fn get(&self, cx: &ScopeState, name: &str, attributes: &BTreeMap<String, String>, load: &LoadFn) -> Option<Loaded> {
+        let key = (name.to_string(), attributes.clone());
+        self.used.borrow_mut().insert(key.clone());
+        match self.loads.borrow().get(&key) {
+            Some(Load::Done(result)) => return Some(result.clone()),
+            Some(Load::Pending(_)) => return None,
+            None => (),
+        }
+
+        let future = load(attributes.clone());
+        let loads = Rc::downgrade(&self.loads);
+        let update = cx.schedule_update();
+        let task_key = key.clone();
+        let task = cx.spawn(async move {
+            let result = future.await;
+            // the load was cancelled if the tag is not in the document anymore
+            let Some(loads) = loads.upgrade() else { return };
+            if let Some(load) = loads.borrow_mut().get_mut(&task_key) {
+                *load = Load::Done(result);
+                update()
+            }
+        });
+        self.loads.borrow_mut().insert(key, Load::Pending(task));
+        None
+    }
+
+    /// forget the loads used by the previous render
+    pub fn start_render(&self) {
+        self.used.borrow_mut().clear()
+    }
+
+    /// cancel and forget the loads of the components not rendered anymore
+    pub fn finish_render(&self, cx: &ScopeState) {
+        let used = self.used.borrow();
+        self.loads.borrow_mut().retain(|key, load| {
+            let keep = used.contains(key);
+            if let (false, Load::Pending(task)) = (keep, &*load) {
+                cx.remove_future(*task)
+            }
+            keep
+        })
+    }
+}
@@ -0,0 +1,108 @@
+use pulldown_cmark_wikilink::Options;
+
+use crate::rewrite::{Protected, Rewriter};
+
+/// the tag `==highlighted==` text is rewritten to
+pub(crate) const TAG: &str = "md-mark";
+
+/// rewrite the `==highlighted==` spans of `src` as tags.
+/// Spans do not cross lines, and are not looked for in code, raw html and link destinations,
+/// found by parsing `src` with `options`.
+///
+/// Returns the new source, and where the length of the source changed:
+/// offsets in the new source with the total change of length before them.
+/// `None` if there is no highlighted span
+pub(crate) fn rewrite_marks(src: &str, options: Options, wikilinks: bool) -> Option<(String, Vec<(usize, isize)>)> {
+    if !src.contains("==") {
+        return None
+    }
+    let protected = Protected::new(src, options, wikilinks, false);
+    let mut rewriter = Rewriter::default();
+    let mut line_start = 0;
+
+    for line in src.split_inclusive('\n') {
+        let skip = |i: usize| protected.end_at(line_start + i).map(|end| (end - line_start).min(line.len()));
+        let mut kept = 0;
+        for (open, close) in mark_pairs(line, skip) {
+            rewriter.keep(&line[kept..open]);
+            rewriter.replace(2, &format!("<{TAG}>"));
+            rewriter.keep(&line[open + 2..close]);
+            rewriter.replace(2, &format!("</{TAG}>"));
+            kept = close + 2;
+        }
+        rewriter.keep(&line[kept..]);
+        line_start += line.len();
+    }
+
+    rewriter.finish()
+}
+
+/// the positions of the opening and closing `==` of the highlighted spans of `line`.
+/// The opening one must be followed by a non-whitespace character
+/// and the closing one preceded by one, an opening `==` without closing one is left as is.
+/// `skip` gives the end of the protected part at an offset of the line, if any
+fn mark_pairs(line: &str, skip: impl Fn(usize) -> Option<usize>) -> Vec<(usize, usize)> {
+    let bytes = line.as_bytes();
+    let mut pairs = Vec::new();
+    let mut open = None;
+
+    let mut i = 0;
+    while i < bytes.len() {
+        if let Some(end) = skip(i) {
+            i = end;
+            continue
+        }
+        match bytes[i] {
+            b'\\' => i += 2,
+            b'=' => {
+                let n = bytes[i..].iter().take_while(|c| **c == b'=').count();
+                if n == 2 {
+                    let before = line[..i].chars().next_back();
+                    let after = line[i + 2..].chars().next();
+                    match open {
+                        None if after.is_some_and(|c| !c.is_whitespace()) => open = Some(i),
+                        Some(start) if before.is_some_and(|c| !c.is_whitespace()) => {
+                            pairs.push((start, i));
+                            open = None
+                        }
+                        _ => (),
+                    }
+                }
+                i += n
+            }
+            _ => i += 1,
+        }
+    }
+    pairs
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rewrite(src: &str) -> String {
+        rewrite_marks(src, Options::all(), false).map_or(src.to_string(), |(rewritten, _)| rewritten)
+    }
+
+    #[test]
+    fn nested_list_content_is_highlighted() {
+        assert_eq!(
+            rewrite("- item\n    - nested ==mark==\n"),
+            "- item\n    - nested <md-mark>mark</md-mark>\n",
+        );
+    }
+
+    #[test]
+    fn code_is_left_as_is() {
+        let src = "`a ==b==` c\n\n    ==indented==\n\n```\n==fenced==\n```\n";
+        assert_eq!(rewrite(src), src);
+    }
+
+    #[test]
+    fn link_destinations_are_left_as_is() {
+        assert_eq!(
+            rewrite("[==text==](https://x.y/?a==b==)"),
+            "[<md-mark>text</md-mark>](https://x.y/?a==b==)",
+        );
+    }
+}
@@ -0,0 +1,169 @@
+use core::ops::Range;
+use std::collections::HashMap;
+
+/// a heading of the document
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct OutlineHeading {
+    /// the `{#id}` given to the heading, or the slug of the title,
+    /// followed by `-1`, `-2`... if an earlier heading has the same slug
+    pub id: String,
+    pub level: u8,
+    /// the title, as plain text
+    pub title: String,
+    /// the range of the heading in the markdown source
+    pub range: Range<usize>,
+}
+
+/// a change of the outline between two renders
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum OutlineChange {
+    /// a heading was inserted at `index` in the new outline
+    Inserted { index: usize, heading: OutlineHeading },
+    Removed { id: String },
+    /// the title or the level of the heading with `id` changed.
+    /// The id of the new heading changes with its title
+    Retitled { id: String, heading: OutlineHeading },
+    /// a heading moved to `index` in the new outline
+    Moved { id: String, index: usize },
+}
+
+/// the changes of the outline since the previous render, see `on_outline_change`
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct OutlineDelta {
+    pub changes: Vec<OutlineChange>,
+    /// the whole new outline
+    pub outline: Vec<OutlineHeading>,
+}
+
+/// an id for a heading titled `title`, like `hello-world` for `Hello, World!`
+pub(crate) fn slugify(title: &str) -> String {
+    title.trim()
+        .chars()
+        .filter_map(|c| match c {
+            c if c.is_alphanumeric() || c == '_' || c == '-' => Some(c.to_lowercase().next().unwrap_or(c)),
+            c if c.is_whitespace() => Some('-'),
+            _ => None,
+        })
+        .collect()
+}
+
+/// the outline of headings given as `(level, title, explicit id, range)`, with unique ids.
+/// Explicit ids are kept as is, other headings get the `slug` of their title
+pub(crate) fn outline<'s>(
+    headings: impl IntoIterator<Item=(u8, &'s str, Option<&'s str>, Range<usize>)>,
+    slug: &dyn Fn(&str) -> String,
+) -> Vec<OutlineHeading> {
+    let mut seen: HashMap<String, usize> = HashMap::new();
+    headings.into_iter()
+        .map(|(level, title, explicit_id, range)| {
+            let id = match explicit_id {
+                Some(id) => {
+                    seen.entry(id.to_string()).or_insert(1);
+                    id.to_string()
+                }
+                None => {
+                    let slug = slug(title);
+                    let count = seen.entry(slug.clone()).or_insert(0);
+                    let id = match *count {
+                        0 => slug,
+                        n => format!("{slug}-{n}"),
+                    };
+                    *count += 1;
+                    id
+                }
+            };
+            OutlineHeading { id, level, title: title.to_string(), range }
+        })
+        .collect()
+}
+
+/// the changes from `old` to `new`.
+///
+/// Headings keeping their id are matched, and the longest sequence of them
+/// keeping their order is considered unmoved.
+/// Between two unmoved headings, removed and inserted headings are paired in order
+/// as retitled headings.
+pub(crate) fn diff(old: &[OutlineHeading], new: &[OutlineHeading]) -> Vec<OutlineChange> {
+    let new_index: HashMap<&str, usize> = new.iter()
+        .enumerate()
+        .map(|(i, h)| (h.id.as_str(), i))
+        .collect();
+    let old_ids: HashMap<&str, usize> = old.iter()
+        .enumerate()
+        .map(|(i, h)| (h.id.as_str(), i))
+        .collect();
+
+    // indices in `new` of the old headings that are kept, in old order
+    let kept: Vec<(usize, usize)> = old.iter()
+        .enumerate()
+        .filter_map(|(i, h)| Some((i, *new_index.get(h.id.as_str())?)))
+        .collect();
+    let unmoved = longest_increasing(&kept.iter().map(|(_, j)| *j).collect::<Vec<_>>());
+    let anchors: Vec<(usize, usize)> = unmoved.iter().map(|k| kept[*k]).collect();
+
+    let mut changes = Vec::new();
+
+    for (k, (i, j)) in kept.iter().enumerate() {
+        if !unmoved.contains(&k) {
+            changes.push(OutlineChange::Moved { id: old[*i].id.clone(), index: *j })
+        }
+        let (before, after) = (&old[*i], &new[*j]);
+        if before.title != after.title || before.level != after.level {
+            changes.push(OutlineChange::Retitled { id: before.id.clone(), heading: after.clone() })
+        }
+    }
+
+    // the gap between two anchors a heading is in, from its index
+    let gap = |index: usize, side: fn(&(usize, usize)) -> usize| anchors.partition_point(|a| side(a) < index);
+
+    let mut removed: Vec<(usize, &OutlineHeading)> = old.iter()
+        .enumerate()
+        .filter(|(_, h)| !new_index.contains_key(h.id.as_str()))
+        .map(|(i, h)| (gap(i, |a| a.0), h))
+        .collect();
+    let inserted = new.iter()
+        .enumerate()
+        .filter(|(_, h)| !old_ids.contains_key(h.id.as_str()));
+
+    for (j, heading) in inserted {
+        let g = gap(j, |a| a.1);
+        match removed.iter().position(|(rg, _)| *rg == g) {
+            Some(r) => {
+                let (_, old_heading) = removed.remove(r);
+                changes.push(OutlineChange::Retitled { id: old_heading.id.clone(), heading: heading.clone() })
+            }
+            None => changes.push(OutlineChange::Inserted { index: j, heading: heading.clone() }),
+        }
+    }
+    for (_, heading) in removed {
+        changes.push(OutlineChange::Removed { id: heading.id.clone() })
+    }
+
+    changes
+}
+
+/// the positions in `values` of a longest strictly increasing subsequence
+fn longest_increasing(values: &[usize]) -> Vec<usize> {
+    // for each length, the position of the smallest value ending a subsequence of that length
+    let mut tails: Vec<usize> = Vec::new();
+    let mut previous = vec![None; values.len()];
+    for (i, value) in values.iter().enumerate() {
+        let length = tails.partition_point(|t| values[*t] < *value);
+        previous[i] = length.checked_sub(1).map(|l| tails[l]);
+        if length == tails.len() {
+            tails.push(i)
+        }
+        else {
+            tails[length] = i
+        }
+    }
+
+    let mut positions = Vec::with_capacity(tails.len());
+    let mut current = tails.last().copied();
+    while let Some(i) = current {
+        positions.push(i);
+        current = previous[i];
+    }
+    positions.reverse();
+    positions
+}
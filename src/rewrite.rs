@@ -0,0 +1,179 @@
+use core::ops::Range;
+use std::borrow::Cow;
+
+use pulldown_cmark_wikilink::{Event, Tag, ParserOffsetIter, Options};
+
+use crate::{autolink, definition, directive, mark, text};
+use crate::tree::ParseConfig;
+
+/// where a rewrite changed the length of a source:
+/// offsets in the new source with the total change of length before them
+pub(crate) type Shifts = Vec<(usize, isize)>;
+
+/// builds a rewritten source, recording where its length changed:
+/// offsets in the new source with the total change of length before them
+#[derive(Default)]
+pub(crate) struct Rewriter {
+    rewritten: String,
+    shifts: Vec<(usize, isize)>,
+}
+
+impl Rewriter {
+    pub fn keep(&mut self, s: &str) {
+        self.rewritten.push_str(s)
+    }
+
+    /// write `inserted` in place of the next `consumed` bytes of the source
+    pub fn replace(&mut self, consumed: usize, inserted: &str) {
+        self.rewritten.push_str(inserted);
+        let shift = self.shifts.last().map_or(0, |(_, s)| *s) + consumed as isize - inserted.len() as isize;
+        self.shifts.push((self.rewritten.len(), shift));
+    }
+
+    /// the length of the new source written so far
+    pub fn position(&self) -> usize {
+        self.rewritten.len()
+    }
+
+    pub fn insert(&mut self, inserted: &str) {
+        self.replace(0, inserted)
+    }
+
+    /// whether something was replaced or inserted
+    pub fn changed(&self) -> bool {
+        !self.shifts.is_empty()
+    }
+
+    /// the new source and its shifts, `None` if nothing changed
+    pub fn finish(self) -> Option<(String, Vec<(usize, isize)>)> {
+        self.changed().then_some((self.rewritten, self.shifts))
+    }
+}
+
+/// the offset in a source before a rewrite of an `offset` in the rewritten source,
+/// given where the rewrite changed the length of the source
+pub(crate) fn unshift(shifts: &[(usize, isize)], offset: usize) -> usize {
+    match shifts.partition_point(|(p, _)| *p <= offset) {
+        0 => offset,
+        n => offset.saturating_add_signed(shifts[n - 1].1),
+    }
+}
+
+/// the offset in a source before newlines were normalized and `rewrites` applied, in order,
+/// of an `offset` in the rewritten source
+pub(crate) fn original_offset(removed_cr: &[usize], rewrites: &[Shifts], offset: usize) -> usize {
+    let offset = rewrites.iter().rev().fold(offset, |offset, shifts| unshift(shifts, offset));
+    offset + removed_cr.partition_point(|p| *p < offset)
+}
+
+/// a source as the renderer parses it, with its newlines normalized
+/// and the extensions rewritten as markdown and tags
+pub(crate) struct RewrittenSource<'s> {
+    pub src: Cow<'s, str>,
+    /// where carriage returns were removed when normalizing newlines,
+    /// as offsets in the normalized source
+    pub removed_cr: Vec<usize>,
+    /// the rewrites of the normalized source, in order
+    pub rewrites: Vec<Shifts>,
+}
+
+impl<'s> RewrittenSource<'s> {
+    /// `src` rewritten like the renderer does with `config`, but for its code blocks,
+    /// which the renderer replaces last. `is_component` tells which directives are components
+    pub fn new(src: &'s str, config: &ParseConfig, is_component: impl Fn(&str) -> bool) -> Self {
+        let mut source = Self { src: Cow::Borrowed(src), removed_cr: Vec::new(), rewrites: Vec::new() };
+        if let Some((normalized, removed_cr)) = config.normalize_newlines.then(|| text::normalize_newlines(src)).flatten() {
+            source.src = Cow::Owned(normalized);
+            source.removed_cr = removed_cr;
+        }
+        if config.directives {
+            source.apply(|src| directive::rewrite_directives(src, &is_component));
+        }
+        if config.definition_lists {
+            source.apply(definition::rewrite_definition_lists);
+        }
+        if config.mark {
+            source.apply(|src| mark::rewrite_marks(src, config.options, config.wikilinks));
+        }
+        if config.autolinks {
+            source.apply(|src| autolink::rewrite_autolinks(src, config.options, config.wikilinks));
+        }
+        source
+    }
+
+    /// rewrite the source with `rewrite`, which returns `None` if nothing changed
+    pub fn apply(&mut self, rewrite: impl FnOnce(&str) -> Option<(String, Shifts)>) {
+        if let Some((rewritten, shifts)) = rewrite(&*self.src) {
+            self.src = Cow::Owned(rewritten);
+            self.rewrites.push(shifts);
+        }
+    }
+
+    /// the range in the original source of a range in the rewritten one
+    pub fn original_range(&self, range: Range<usize>) -> Range<usize> {
+        let offset = |offset| original_offset(&self.removed_cr, &self.rewrites, offset);
+        offset(range.start)..offset(range.end)
+    }
+}
+
+/// the fence of the code block opened or closed by `line`, if any:
+/// the three characters starting it
+pub(crate) fn fence(line: &str) -> Option<&str> {
+    let trimmed = line.trim();
+    (trimmed.starts_with("```") || trimmed.starts_with("~~~")).then(|| &trimmed[..3])
+}
+
+/// the parts of a source that rewrites of its text must leave as is:
+/// code, raw html, and the destinations of links and images
+pub(crate) struct Protected {
+    /// sorted and disjoint
+    ranges: Vec<Range<usize>>,
+}
+
+impl Protected {
+    /// the protected parts of `src`, parsed like the renderer does.
+    /// With `whole_links`, the text of links is protected too.
+    /// Images are always protected, their alt text is plain text
+    pub fn new(src: &str, options: Options, wikilinks: bool, whole_links: bool) -> Self {
+        let mut ranges: Vec<Range<usize>> = Vec::new();
+        // the links we are in: their range and the end of their text so far
+        let mut links: Vec<(Range<usize>, usize)> = Vec::new();
+
+        for (event, range) in ParserOffsetIter::new_ext(src, options, wikilinks) {
+            match &event {
+                Event::Start(Tag::Link(..)) if !whole_links => {
+                    links.push((range.clone(), range.start + 1));
+                    continue
+                }
+                Event::End(Tag::Link(..)) if !whole_links => {
+                    // what follows the text: the destination and the title
+                    if let Some((link, text_end)) = links.pop() {
+                        ranges.push(text_end..link.end)
+                    }
+                }
+                Event::Start(Tag::Link(..) | Tag::Image(..) | Tag::CodeBlock(_))
+                    | Event::Code(_) | Event::Html(_) => ranges.push(range.clone()),
+                _ => (),
+            }
+            if let Some((_, text_end)) = links.last_mut() {
+                *text_end = (*text_end).max(range.end)
+            }
+        }
+
+        ranges.sort_by_key(|r| r.start);
+        let mut merged: Vec<Range<usize>> = Vec::with_capacity(ranges.len());
+        for range in ranges {
+            match merged.last_mut() {
+                Some(last) if range.start <= last.end => last.end = last.end.max(range.end),
+                _ => merged.push(range),
+            }
+        }
+        Self { ranges: merged }
+    }
+
+    /// the end of the protected part containing `offset`, if any
+    pub fn end_at(&self, offset: usize) -> Option<usize> {
+        let i = self.ranges.partition_point(|r| r.end <= offset);
+        self.ranges.get(i).filter(|r| r.start <= offset).map(|r| r.end)
+    }
+}
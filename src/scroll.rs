@@ -0,0 +1,74 @@
+use std::cell::Cell;
+use std::rc::Rc;
+
+use dioxus::prelude::*;
+use dioxus::html::prelude::EvalProvider;
+
+use crate::clipboard::js_string;
+
+/// where a document is scrolled to, see `scroll_state`
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub struct ScrollState {
+    /// the offset in the markdown source of the block at the top of the scroll container
+    pub offset: usize,
+}
+
+/// reports the scroll position of a document, and restores it when the document is mounted
+#[derive(Default)]
+pub(crate) struct ScrollSync {
+    observing: Cell<bool>,
+}
+
+impl ScrollSync {
+    /// restore `state` in the element matching the css selector `container`,
+    /// and report the block at its top as it scrolls, the first time it is called.
+    /// Blocks are found by their `data-sourcepos` attribute
+    pub fn observe(&self, cx: &ScopeState, eval: &Rc<dyn EvalProvider>, container: &str, state: &UseState<ScrollState>) {
+        if self.observing.replace(true) {
+            return
+        }
+
+        let script = format!(
+            r#"const container = document.querySelector({container});
+            const restore = {restore};
+            const start = block => Number(block.getAttribute("data-sourcepos").split("-")[0]);
+            if (container && restore > 0) {{
+                // once the blocks are in the document
+                requestAnimationFrame(() => {{
+                    const block = [...container.querySelectorAll("[data-sourcepos]")].find(b => start(b) >= restore);
+                    if (block) {{
+                        container.scrollTop += block.getBoundingClientRect().top - container.getBoundingClientRect().top;
+                    }}
+                }});
+            }}
+            let scheduled = false;
+            container?.addEventListener("scroll", () => {{
+                if (scheduled) return;
+                scheduled = true;
+                setTimeout(() => {{
+                    scheduled = false;
+                    const top = container.getBoundingClientRect().top;
+                    const block = [...container.querySelectorAll("[data-sourcepos]")]
+                        .find(b => b.getBoundingClientRect().bottom > top);
+                    if (block) dioxus.send(start(block));
+                }}, 100);
+            }}, {{ passive: true }});"#,
+            container = js_string(container),
+            restore = state.get().offset,
+        );
+        // without javascript, the position is just never reported
+        let Ok(observer) = eval.new_evaluator(script) else {
+            return
+        };
+        let state = state.clone();
+        cx.spawn(async move {
+            while let Ok(offset) = observer.recv().await {
+                let Some(offset) = offset.as_u64() else { continue };
+                let scrolled = ScrollState { offset: offset as usize };
+                if *state.get() != scrolled {
+                    state.set(scrolled)
+                }
+            }
+        });
+    }
+}
@@ -0,0 +1,14 @@
+use core::ops::Range;
+
+/// the range of the markdown source covered by a selection,
+/// given the source ranges of the elements containing its anchor and its focus.
+/// They are read from the `data-md-start` and `data-md-end` attributes
+/// of the elements, see `source_attributes`.
+///
+/// The result is only as precise as these elements: it covers both of them entirely,
+/// not just the selected characters, and text outside of any element with a range
+/// (like the content of raw html) is not mapped.
+/// The anchor and the focus can be in any order
+pub fn selection_to_source_range(anchor: Range<usize>, focus: Range<usize>) -> Range<usize> {
+    anchor.start.min(focus.start)..anchor.end.max(focus.end)
+}
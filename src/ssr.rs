@@ -0,0 +1,39 @@
+use dioxus::prelude::*;
+
+use crate::{Markdown, RenderEnv};
+
+#[derive(Props, PartialEq)]
+struct RootProps {
+    src: String,
+    /// add the markers `HydratedMarkdown` needs
+    hydratable: bool,
+}
+
+#[allow(non_snake_case)]
+fn Root(cx: Scope<RootProps>) -> Element {
+    render!{
+        Markdown {
+            src: &cx.props.src,
+            render_env: RenderEnv::Server,
+            source_positions: cx.props.hydratable,
+        }
+    }
+}
+
+fn render(src: &str, hydratable: bool) -> String {
+    let mut vdom = VirtualDom::new_with_props(Root, RootProps { src: src.to_string(), hydratable });
+    let _ = vdom.rebuild();
+    dioxus_ssr::render(&vdom)
+}
+
+/// render `src` to an html string, with the default options.
+/// Features relying on javascript are rendered as their static fallback.
+pub fn render_to_string(src: &str) -> String {
+    render(src, false)
+}
+
+/// render `src` to an html string like `render_to_string`,
+/// with the markers [`HydratedMarkdown`](crate::HydratedMarkdown) needs to make it interactive on the client
+pub fn render_to_hydratable_string(src: &str) -> String {
+    render(src, true)
+}
@@ -0,0 +1,199 @@
+use std::borrow::Cow;
+
+/// a piece of text, and how to read it
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct RubySegment {
+    pub base: String,
+    /// rendered above the base text, if any
+    pub reading: Option<String>,
+}
+
+/// characters after which a long token (typically an url) can be broken
+const BREAK_AFTER: &[char] = &['/', '.', '-', '_', '?', '&', '=', '#', ':'];
+
+/// split `text` in pieces between which a line break is allowed.
+/// Only whitespace-free runs longer than `threshold` characters are split:
+/// after url punctuation, or every `threshold` characters otherwise.
+pub(crate) fn break_long_words(text: &str, threshold: usize) -> Vec<&str> {
+    let threshold = threshold.max(1);
+    let mut pieces = Vec::new();
+    let mut piece_start = 0;
+
+    for (word_start, word) in words(text) {
+        if word.chars().count() <= threshold {
+            continue
+        }
+
+        let mut since_break = 0;
+        for (i, c) in word.char_indices() {
+            since_break += 1;
+            let end = word_start + i + c.len_utf8();
+            if end == word_start + word.len() {
+                break
+            }
+            if BREAK_AFTER.contains(&c) || since_break >= threshold {
+                pieces.push(&text[piece_start..end]);
+                piece_start = end;
+                since_break = 0;
+            }
+        }
+    }
+
+    pieces.push(&text[piece_start..]);
+    pieces
+}
+
+/// a piece of text to render
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub(crate) enum TextPiece<'t> {
+    Text(Cow<'t, str>),
+    /// a segment annotated with its reading
+    Ruby { base: String, reading: String },
+    /// a line break opportunity, `<wbr>`
+    Break,
+}
+
+/// the prefixes of the words that are urls, which are never annotated
+const URL_PREFIXES: &[&str] = &["http://", "https://", "www.", "mailto:"];
+
+/// `text` annotated by `ruby`, except its urls,
+/// with line break opportunities in its long words if `threshold` is set
+pub(crate) fn text_pieces<'t>(
+    text: &'t str,
+    ruby: Option<&dyn Fn(&str) -> Vec<RubySegment>>,
+    threshold: Option<usize>,
+) -> Vec<TextPiece<'t>> {
+    let mut pieces = Vec::new();
+    let Some(ruby) = ruby else {
+        push_breakable(&mut pieces, Cow::Borrowed(text), threshold);
+        return pieces
+    };
+    for (is_url, run) in url_runs(text) {
+        if is_url {
+            push_breakable(&mut pieces, Cow::Borrowed(run), threshold);
+            continue
+        }
+        for segment in ruby(run) {
+            match segment.reading {
+                Some(reading) => pieces.push(TextPiece::Ruby { base: segment.base, reading }),
+                None => push_breakable(&mut pieces, Cow::Owned(segment.base), threshold),
+            }
+        }
+    }
+    pieces
+}
+
+/// push `text`, split by `break_long_words` if `threshold` is set
+fn push_breakable<'t>(pieces: &mut Vec<TextPiece<'t>>, text: Cow<'t, str>, threshold: Option<usize>) {
+    let Some(threshold) = threshold else {
+        return pieces.push(TextPiece::Text(text))
+    };
+    let split: Vec<Cow<'t, str>> = match text {
+        Cow::Borrowed(text) => break_long_words(text, threshold).into_iter().map(Cow::Borrowed).collect(),
+        Cow::Owned(text) => break_long_words(&text, threshold).into_iter().map(|p| Cow::Owned(p.to_string())).collect(),
+    };
+    for (i, piece) in split.into_iter().enumerate() {
+        if i > 0 {
+            pieces.push(TextPiece::Break)
+        }
+        pieces.push(TextPiece::Text(piece))
+    }
+}
+
+/// `text` split in its url words and the runs between them, with whether each is an url
+fn url_runs(text: &str) -> Vec<(bool, &str)> {
+    let mut runs = Vec::new();
+    let mut start = 0;
+    for (offset, word) in words(text) {
+        if URL_PREFIXES.iter().any(|prefix| word.starts_with(prefix)) {
+            if offset > start {
+                runs.push((false, &text[start..offset]))
+            }
+            runs.push((true, word));
+            start = offset + word.len();
+        }
+    }
+    if start < text.len() || runs.is_empty() {
+        runs.push((false, &text[start..]))
+    }
+    runs
+}
+
+/// the whitespace-separated words of `text`, with their byte offset
+fn words(text: &str) -> impl Iterator<Item=(usize, &str)> {
+    text.split(char::is_whitespace)
+        .scan(0, |offset, word| {
+            let start = *offset;
+            // every separator is a single whitespace char
+            *offset += word.len() + text[start + word.len()..].chars().next().map_or(0, char::len_utf8);
+            Some((start, word))
+        })
+        .filter(|(_, word)| !word.is_empty())
+}
+
+/// `src` with `\r\n` replaced by `\n`, and the offsets in the result
+/// of the newlines whose `\r` was removed. `None` if there is nothing to replace
+pub(crate) fn normalize_newlines(src: &str) -> Option<(String, Vec<usize>)> {
+    if !src.contains("\r\n") {
+        return None
+    }
+
+    let mut normalized = String::with_capacity(src.len());
+    let mut removed = Vec::new();
+    for (i, line) in src.split("\r\n").enumerate() {
+        if i > 0 {
+            removed.push(normalized.len());
+            normalized.push('\n');
+        }
+        normalized.push_str(line);
+    }
+    Some((normalized, removed))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// annotates each `日` with `に`
+    fn ruby(text: &str) -> Vec<RubySegment> {
+        text.split_inclusive('日')
+            .flat_map(|part| match part.strip_suffix('日') {
+                Some(before) => vec![
+                    RubySegment { base: before.to_string(), reading: None },
+                    RubySegment { base: "日".to_string(), reading: Some("に".to_string()) },
+                ],
+                None => vec![RubySegment { base: part.to_string(), reading: None }],
+            })
+            .filter(|s| !s.base.is_empty())
+            .collect()
+    }
+
+    #[test]
+    fn urls_are_not_annotated() {
+        let pieces = text_pieces("日 https://日.jp 日", Some(&ruby), None);
+        assert_eq!(pieces, [
+            TextPiece::Ruby { base: "日".into(), reading: "に".into() },
+            TextPiece::Text(" ".into()),
+            TextPiece::Text("https://日.jp".into()),
+            TextPiece::Text(" ".into()),
+            TextPiece::Ruby { base: "日".into(), reading: "に".into() },
+        ]);
+    }
+
+    #[test]
+    fn annotated_text_is_broken() {
+        let pieces = text_pieces("日 abcdef", Some(&ruby), Some(3));
+        assert_eq!(pieces, [
+            TextPiece::Ruby { base: "日".into(), reading: "に".into() },
+            TextPiece::Text(" abc".into()),
+            TextPiece::Break,
+            TextPiece::Text("def".into()),
+        ]);
+    }
+
+    #[test]
+    fn text_without_annotator_is_borrowed() {
+        let pieces = text_pieces("short", None, Some(10));
+        assert!(matches!(pieces[..], [TextPiece::Text(Cow::Borrowed("short"))]));
+    }
+}
@@ -0,0 +1,310 @@
+use core::ops::Range;
+
+use pulldown_cmark_wikilink::{
+    Event, Tag, ParserOffsetIter, Options,
+    CodeBlockKind, Alignment as CmarkAlignment,
+};
+
+#[cfg(feature="serde")]
+use serde::Serialize;
+
+use crate::{definition, directive, mark};
+use crate::extract::table_of;
+use crate::html::{parse_tag, unescape_attribute, HtmlTag, TagKind};
+use crate::rewrite::RewrittenSource;
+
+/// how [`Markdown`](crate::Markdown) parses its source, given by its props with the same names.
+/// See [`MdProps::parse_config`](crate::MdProps::parse_config) for the one of some props.
+///
+/// The default is the one of the default props
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct ParseConfig {
+    /// the options of the parser, with the extensions the props add to them
+    pub options: Options,
+    pub wikilinks: bool,
+    pub normalize_newlines: bool,
+    pub directives: bool,
+    pub definition_lists: bool,
+    pub mark: bool,
+    pub autolinks: bool,
+}
+
+impl Default for ParseConfig {
+    fn default() -> Self {
+        Self {
+            options: Options::all(),
+            wikilinks: false,
+            normalize_newlines: false,
+            directives: false,
+            definition_lists: false,
+            mark: false,
+            autolinks: false,
+        }
+    }
+}
+
+/// a node of the markdown document, as seen by the renderer
+#[derive(Clone, PartialEq, Debug)]
+#[cfg_attr(feature="serde", derive(Serialize))]
+pub struct MdNode {
+    pub kind: NodeKind,
+    /// the range of the node in the markdown source
+    pub range: Range<usize>,
+    pub children: Vec<MdNode>,
+}
+
+#[derive(Clone, PartialEq, Debug)]
+#[cfg_attr(feature="serde", derive(Serialize))]
+pub enum NodeKind {
+    Document,
+    Paragraph,
+    Heading { level: u8 },
+    BlockQuote,
+    CodeBlock { language: Option<String> },
+    List { start: Option<u64> },
+    Item,
+    FootnoteDefinition { label: String },
+    Table { alignments: Vec<Alignment> },
+    TableHead,
+    TableRow,
+    TableCell,
+    Emphasis,
+    Strong,
+    Strikethrough,
+    Link { url: String, title: String },
+    Image { url: String, title: String },
+    /// a tag of a registered custom component
+    Component { name: String, attributes: Vec<(String, String)> },
+    /// a `:::` container whose name is not a component
+    Directive { name: String, title: String },
+    DefinitionList,
+    DefinitionTerm,
+    DefinitionDetails,
+    /// `==highlighted==` text
+    Mark,
+    Text(String),
+    Code(String),
+    Html(String),
+    FootnoteReference(String),
+    TaskListMarker(bool),
+    SoftBreak,
+    HardBreak,
+    Rule,
+    /// a construct of a parser extension without a dedicated kind
+    Other,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+#[cfg_attr(feature="serde", derive(Serialize))]
+pub enum Alignment {
+    None,
+    Left,
+    Center,
+    Right,
+}
+
+impl MdNode {
+    /// the text of the node as it is displayed, without formatting.
+    /// Tables are linearized, see [`ExtractedTable::linearized`](crate::ExtractedTable::linearized)
+    pub fn plain_text(&self) -> String {
+        let mut text = String::new();
+        self.push_plain_text(&mut text);
+        text
+    }
+
+    fn push_plain_text(&self, text: &mut String) {
+        match &self.kind {
+            NodeKind::Text(s) | NodeKind::Code(s) => text.push_str(s),
+            NodeKind::SoftBreak | NodeKind::HardBreak => text.push(' '),
+            // rows are linearized to stay readable without the layout
+            NodeKind::Table { .. } => {
+                let rows = table_of(self).map(|table| table.linearized()).unwrap_or_default();
+                text.push_str(&rows.join("\n"))
+            }
+            _ => self.children.iter().for_each(|child| child.push_plain_text(text)),
+        }
+    }
+}
+
+/// parse `src` into a tree, with the same parser configuration as [`Markdown`](crate::Markdown).
+/// The extensions are rewritten like the renderer does, and the ranges are in `src`.
+///
+/// `is_component` tells which html tags are custom components,
+/// use [`CustomComponents::handles`](crate::CustomComponents::handles)
+/// to match what gets rendered.
+pub fn parse_to_tree(src: &str, config: &ParseConfig, is_component: impl Fn(&str) -> bool) -> MdNode {
+    let source = RewrittenSource::new(src, config, &is_component);
+    let mut tree = parse_rewritten(&source.src, config, is_component);
+    if !source.rewrites.is_empty() || !source.removed_cr.is_empty() {
+        tree.map_ranges(&|range| source.original_range(range));
+    }
+    tree.range = 0..src.len();
+    tree
+}
+
+/// parse a source already rewritten by the renderer into a tree,
+/// with ranges in that source
+pub(crate) fn parse_rewritten(src: &str, config: &ParseConfig, is_component: impl Fn(&str) -> bool) -> MdNode {
+    // the nodes being built, from the root to the innermost
+    let mut stack = vec![MdNode {
+        kind: NodeKind::Document,
+        range: 0..src.len(),
+        children: Vec::new(),
+    }];
+
+    for (event, range) in ParserOffsetIter::new_ext(src, config.options, config.wikilinks) {
+        let kind = match event {
+            Event::Start(tag) => {
+                stack.push(MdNode { kind: tag_kind(tag), range, children: Vec::new() });
+                continue
+            }
+            Event::End(_) => {
+                // a tag left open inside this node is closed with it
+                while stack.last().is_some_and(|node| node.kind.tag_name().is_some()) {
+                    close_node(&mut stack);
+                }
+                close_node(&mut stack);
+                continue
+            }
+            Event::Html(html) => match parse_tag(&html) {
+                Some(tag) => match html_kind(&tag, config, &is_component) {
+                    Some(kind) => {
+                        let node = MdNode { kind, range: range.clone(), children: Vec::new() };
+                        match tag.kind {
+                            TagKind::Open => stack.push(node),
+                            TagKind::SelfClosing => push_child(&mut stack, node),
+                            TagKind::Close => {
+                                if stack.last().and_then(|node| node.kind.tag_name()) == Some(tag.name) {
+                                    stack.last_mut().unwrap().range.end = range.end;
+                                    close_node(&mut stack);
+                                }
+                            }
+                        }
+                        continue
+                    }
+                    None => NodeKind::Html(html.to_string()),
+                },
+                None => NodeKind::Html(html.to_string()),
+            },
+            Event::Text(s) => NodeKind::Text(s.to_string()),
+            Event::Code(s) => NodeKind::Code(s.to_string()),
+            Event::FootnoteReference(s) => NodeKind::FootnoteReference(s.to_string()),
+            Event::TaskListMarker(checked) => NodeKind::TaskListMarker(checked),
+            Event::SoftBreak => NodeKind::SoftBreak,
+            Event::HardBreak => NodeKind::HardBreak,
+            Event::Rule => NodeKind::Rule,
+            #[allow(unreachable_patterns)]
+            _ => NodeKind::Other,
+        };
+        push_child(&mut stack, MdNode { kind, range, children: Vec::new() });
+    }
+
+    while stack.len() > 1 {
+        close_node(&mut stack);
+    }
+    stack.pop().unwrap()
+}
+
+/// the kind of the node written as the html `tag`, if it is not plain html
+fn html_kind(tag: &HtmlTag, config: &ParseConfig, is_component: impl Fn(&str) -> bool) -> Option<NodeKind> {
+    let kind = match tag.name {
+        mark::TAG if config.mark => NodeKind::Mark,
+        definition::LIST_TAG if config.definition_lists => NodeKind::DefinitionList,
+        definition::TERM_TAG if config.definition_lists => NodeKind::DefinitionTerm,
+        definition::DETAILS_TAG if config.definition_lists => NodeKind::DefinitionDetails,
+        directive::FALLBACK_TAG if config.directives => {
+            let attribute = |name: &str| tag.attributes.iter()
+                .find(|(k, _)| *k == name)
+                .map_or(String::new(), |(_, v)| unescape_attribute(v).into_owned());
+            NodeKind::Directive { name: attribute("name"), title: attribute("title") }
+        }
+        name if is_component(name) => NodeKind::Component {
+            name: name.into(),
+            attributes: tag.attributes.iter()
+                .map(|(k, v)| (k.to_string(), v.to_string()))
+                .collect(),
+        },
+        _ => return None,
+    };
+    Some(kind)
+}
+
+impl NodeKind {
+    /// the html tag opening and closing nodes of this kind, for the ones written as tags
+    fn tag_name(&self) -> Option<&str> {
+        match self {
+            Self::Component { name, .. } => Some(name),
+            Self::Directive { .. } => Some(directive::FALLBACK_TAG),
+            Self::DefinitionList => Some(definition::LIST_TAG),
+            Self::DefinitionTerm => Some(definition::TERM_TAG),
+            Self::DefinitionDetails => Some(definition::DETAILS_TAG),
+            Self::Mark => Some(mark::TAG),
+            _ => None,
+        }
+    }
+}
+
+impl MdNode {
+    fn map_ranges(&mut self, map: &impl Fn(Range<usize>) -> Range<usize>) {
+        self.range = map(self.range.clone());
+        self.children.iter_mut().for_each(|child| child.map_ranges(map));
+    }
+}
+
+fn push_child(stack: &mut [MdNode], node: MdNode) {
+    stack.last_mut()
+        .expect("the document node is never closed")
+        .children
+        .push(node)
+}
+
+fn close_node(stack: &mut Vec<MdNode>) {
+    if stack.len() > 1 {
+        let node = stack.pop().unwrap();
+        push_child(stack, node);
+    }
+}
+
+fn tag_kind(tag: Tag) -> NodeKind {
+    match tag {
+        Tag::Paragraph => NodeKind::Paragraph,
+        Tag::Heading(level, _, _) => NodeKind::Heading { level: level as u8 },
+        Tag::BlockQuote => NodeKind::BlockQuote,
+        Tag::CodeBlock(kind) => NodeKind::CodeBlock { language: code_language(&kind) },
+        Tag::List(start) => NodeKind::List { start },
+        Tag::Item => NodeKind::Item,
+        Tag::FootnoteDefinition(label) => NodeKind::FootnoteDefinition { label: label.to_string() },
+        Tag::Table(alignments) => NodeKind::Table {
+            alignments: alignments.into_iter().map(Alignment::from).collect(),
+        },
+        Tag::TableHead => NodeKind::TableHead,
+        Tag::TableRow => NodeKind::TableRow,
+        Tag::TableCell => NodeKind::TableCell,
+        Tag::Emphasis => NodeKind::Emphasis,
+        Tag::Strong => NodeKind::Strong,
+        Tag::Strikethrough => NodeKind::Strikethrough,
+        Tag::Link(_, url, title) => NodeKind::Link { url: url.to_string(), title: title.to_string() },
+        Tag::Image(_, url, title) => NodeKind::Image { url: url.to_string(), title: title.to_string() },
+        #[allow(unreachable_patterns)]
+        _ => NodeKind::Other,
+    }
+}
+
+/// the language of a code block, the first word of its info string
+pub(crate) fn code_language(kind: &CodeBlockKind) -> Option<String> {
+    match kind {
+        CodeBlockKind::Fenced(info) => info.split_whitespace().next().map(String::from),
+        CodeBlockKind::Indented => None,
+    }
+}
+
+impl From<CmarkAlignment> for Alignment {
+    fn from(alignment: CmarkAlignment) -> Self {
+        match alignment {
+            CmarkAlignment::None => Self::None,
+            CmarkAlignment::Left => Self::Left,
+            CmarkAlignment::Center => Self::Center,
+            CmarkAlignment::Right => Self::Right,
+        }
+    }
+}
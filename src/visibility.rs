@@ -0,0 +1,91 @@
+use std::cell::{Cell, RefCell};
+use std::rc::Rc;
+
+use dioxus::prelude::*;
+use dioxus::html::prelude::EvalProvider;
+
+use crate::clipboard::js_string;
+
+thread_local! {
+    // the id of the next instance, to tell its elements from the ones of the others
+    static NEXT_ID: Cell<usize> = Cell::new(0);
+}
+
+/// an id for the elements of a new `Markdown` instance, unique in the app
+pub(crate) fn instance_id() -> String {
+    NEXT_ID.with(|next| next.replace(next.get() + 1)).to_string()
+}
+
+/// the links of one `Markdown` instance that scrolled into view, see `on_link_visible`.
+/// Its links have a `data-md-visible` attribute set to `id`, for the observer to find them
+pub(crate) struct LinkVisibility {
+    pub id: String,
+    /// the hrefs of the links that became visible since they were taken
+    visible: Rc<RefCell<Vec<String>>>,
+    observing: Cell<bool>,
+}
+
+impl Default for LinkVisibility {
+    fn default() -> Self {
+        Self {
+            id: instance_id(),
+            visible: Default::default(),
+            observing: Cell::new(false),
+        }
+    }
+}
+
+impl LinkVisibility {
+    /// start observing the links of this instance, the first time it is called.
+    /// Each href is reported once, the first time one of its links is visible
+    pub fn observe(&self, cx: &ScopeState, eval: &Rc<dyn EvalProvider>) {
+        if self.observing.replace(true) {
+            return
+        }
+
+        let script = format!(
+            r#"const id = {id};
+            const reported = new Set();
+            const observer = new IntersectionObserver(entries => {{
+                for (const entry of entries) {{
+                    if (!entry.isIntersecting) continue;
+                    observer.unobserve(entry.target);
+                    const href = entry.target.getAttribute("href");
+                    if (!reported.has(href)) {{
+                        reported.add(href);
+                        dioxus.send(href);
+                    }}
+                }}
+            }});
+            const observed = new WeakSet();
+            const scan = () => document.querySelectorAll("[data-md-visible]").forEach(link => {{
+                if (link.getAttribute("data-md-visible") === id && !observed.has(link)) {{
+                    observed.add(link);
+                    observer.observe(link);
+                }}
+            }});
+            new MutationObserver(scan).observe(document.body, {{ childList: true, subtree: true }});
+            scan();"#,
+            id = js_string(&self.id),
+        );
+        // without an observer, links are just never reported
+        let Ok(observer) = eval.new_evaluator(script) else {
+            return
+        };
+        let visible = self.visible.clone();
+        let update = cx.schedule_update();
+        cx.spawn(async move {
+            while let Ok(href) = observer.recv().await {
+                if let Some(href) = href.as_str() {
+                    visible.borrow_mut().push(href.to_string());
+                    update()
+                }
+            }
+        });
+    }
+
+    /// the hrefs of the links that became visible since the last call
+    pub fn take(&self) -> Vec<String> {
+        self.visible.take()
+    }
+}
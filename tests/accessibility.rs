@@ -0,0 +1,86 @@
+use std::rc::Rc;
+
+use dioxus::prelude::*;
+use dioxus::html::prelude::EvalProvider;
+use dioxus_markdown::*;
+
+mod common;
+
+const SRC: &str = "<Toc/>\n\n# Title\n\nCited[^note].\n\n```rust\nlet x = 1;\n```\n\n[^note]: A note.\n";
+
+fn labels() -> A11yConfig {
+    A11yConfig {
+        footnotes_label: "Notes de bas de page".into(),
+        toc_label: "Sommaire".into(),
+        ..A11yConfig::default()
+    }
+}
+
+#[test]
+fn the_footnotes_section_is_labelled_endnotes() {
+    fn app(cx: Scope) -> Element {
+        render!{
+            Markdown { src: SRC, accessibility: labels() }
+        }
+    }
+    let html = common::render(app);
+    let section = html.find("<section").expect(&html);
+    let section = &html[section..section + html[section..].find('>').unwrap()];
+    assert!(section.contains(r#"role="doc-endnotes""#), "{section}");
+    assert!(section.contains(r#"aria-label="Notes de bas de page""#), "{section}");
+}
+
+#[test]
+fn the_toc_is_labelled() {
+    fn app(cx: Scope) -> Element {
+        render!{
+            Markdown { src: SRC, accessibility: labels(), components: CustomComponents::new().with_builtins() }
+        }
+    }
+    let html = common::render(app);
+    let nav = html.find("<nav").expect(&html);
+    let nav = &html[nav..nav + html[nav..].find('>').unwrap()];
+    assert!(nav.contains(r#"aria-label="Sommaire""#), "{nav}");
+}
+
+#[test]
+fn nothing_is_labelled_by_default() {
+    fn app(cx: Scope) -> Element {
+        render!{
+            Markdown { src: SRC, components: CustomComponents::new().with_builtins() }
+        }
+    }
+    let html = common::render(app);
+    assert!(!html.contains("doc-endnotes"), "{html}");
+    assert!(!html.contains("Table of contents"), "{html}");
+}
+
+#[test]
+fn decorative_icons_are_hidden() {
+    fn app(cx: Scope) -> Element {
+        cx.use_hook(|| cx.provide_context(Rc::new(common::SilentEval::default()) as Rc<dyn EvalProvider>));
+        render!{
+            Markdown { src: SRC, copy_buttons: true, heading_anchors: true }
+        }
+    }
+    let html = common::render(app);
+    let icon = html.find("md-copy-icon").expect(&html);
+    let icon = &html[html[..icon].rfind('<').unwrap()..icon + html[icon..].find('>').unwrap()];
+    assert!(icon.contains(r#"aria-hidden="true""#), "{icon}");
+    let anchor = html.find("md-anchor").expect(&html);
+    let anchor = &html[html[..anchor].rfind('<').unwrap()..anchor + html[anchor..].find('>').unwrap()];
+    assert!(anchor.contains(r#"aria-hidden="true""#), "{anchor}");
+}
+
+#[cfg(feature = "katex")]
+#[test]
+fn math_is_an_image_with_a_label() {
+    fn app(cx: Scope) -> Element {
+        render!{
+            Markdown { src: "$x^2$", math: true, accessibility: A11yConfig::default() }
+        }
+    }
+    let html = common::render(app);
+    assert!(html.contains(r#"role="img""#), "{html}");
+    assert!(html.contains(r#"aria-label="math expression""#), "{html}");
+}
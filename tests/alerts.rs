@@ -0,0 +1,35 @@
+use dioxus::prelude::*;
+use dioxus_markdown::*;
+
+mod common;
+
+const SRC: &str = "> [!Warning]\n> Be careful.\n\n> [!OTHER]\n> Just a quote.\n";
+
+#[test]
+fn alerts_have_a_title_instead_of_their_marker() {
+    fn app(cx: Scope) -> Element {
+        render!{
+            Markdown { src: SRC, alerts: true }
+        }
+    }
+    let html = common::render(app);
+    assert!(html.contains("markdown-alert markdown-alert-warning"), "{html}");
+    assert!(html.contains(r#"<p class="markdown-alert-title">Warning</p>"#), "{html}");
+    assert!(html.contains("Be careful."), "{html}");
+    assert!(!html.contains("!Warning"), "{html}");
+    // unknown markers are plain quotes
+    assert_eq!(html.matches("markdown-alert-title").count(), 1, "{html}");
+    assert!(html.contains("OTHER"), "{html}");
+}
+
+#[test]
+fn alerts_are_quotes_by_default() {
+    fn app(cx: Scope) -> Element {
+        render!{
+            Markdown { src: SRC }
+        }
+    }
+    let html = common::render(app);
+    assert!(!html.contains("markdown-alert"), "{html}");
+    assert!(html.contains("Warning"), "{html}");
+}
@@ -0,0 +1,90 @@
+use std::rc::Rc;
+
+use dioxus::prelude::*;
+use dioxus_markdown::*;
+
+mod common;
+
+const SRC: &str = "# Hello, World!\n\nSome text.\n\n# Hello, World!\n";
+
+#[test]
+fn headings_link_to_their_id() {
+    fn app(cx: Scope) -> Element {
+        render!{
+            Markdown { src: SRC, heading_anchors: true }
+        }
+    }
+    let html = common::render(app);
+    assert!(html.contains(r#"id="hello-world""#), "{html}");
+    assert!(html.contains(r##"href="#hello-world""##), "{html}");
+    assert!(html.contains(r#"id="hello-world-1""#), "{html}");
+    assert!(html.contains(r##"href="#hello-world-1""##), "{html}");
+    assert_eq!(html.matches(r#"class="md-anchor""#).count(), 2, "{html}");
+    assert_eq!(html.matches(r#"aria-hidden="true""#).count(), 2, "{html}");
+}
+
+#[test]
+fn headings_have_no_anchor_by_default() {
+    fn app(cx: Scope) -> Element {
+        render!{
+            Markdown { src: SRC }
+        }
+    }
+    let html = common::render(app);
+    assert!(!html.contains("md-anchor"), "{html}");
+    assert!(!html.contains(r#"id="hello-world""#), "{html}");
+}
+
+#[test]
+fn custom_headings_have_no_anchor() {
+    fn app(cx: Scope) -> Element {
+        let headings: HtmlCallback<HeadingDescription> = Rc::new(|cx, heading| cx.render(rsx!{
+            div { class: "custom-heading", heading.content }
+        }));
+        render!{
+            Markdown { src: SRC, heading_anchors: true, render_headings: headings }
+        }
+    }
+    let html = common::render(app);
+    assert_eq!(html.matches("custom-heading").count(), 2, "{html}");
+    assert!(!html.contains("md-anchor"), "{html}");
+}
+
+#[test]
+fn heading_ids_have_no_anchor() {
+    fn app(cx: Scope) -> Element {
+        render!{
+            Markdown { src: SRC, heading_ids: true }
+        }
+    }
+    let html = common::render(app);
+    assert!(html.contains(r#"id="hello-world""#), "{html}");
+    assert!(html.contains(r#"id="hello-world-1""#), "{html}");
+    assert!(!html.contains("md-anchor"), "{html}");
+}
+
+#[test]
+fn explicit_ids_win_over_slugs() {
+    fn app(cx: Scope) -> Element {
+        render!{
+            Markdown { src: "# Install {#setup}\n\n# Setup\n", heading_ids: true }
+        }
+    }
+    let html = common::render(app);
+    assert!(html.contains(r#"id="setup""#), "{html}");
+    assert!(html.contains(r#"id="setup-1""#), "{html}");
+    assert!(!html.contains("{#setup}"), "{html}");
+}
+
+#[test]
+fn slugify_replaces_the_default_slugs() {
+    fn app(cx: Scope) -> Element {
+        let slugify: Rc<dyn Fn(&str) -> String> = Rc::new(|title| title.to_uppercase().replace(' ', "_"));
+        render!{
+            Markdown { src: SRC, heading_ids: true, slugify: slugify }
+        }
+    }
+    let html = common::render(app);
+    assert!(html.contains(r#"id="HELLO,_WORLD!""#), "{html}");
+    assert!(html.contains(r#"id="HELLO,_WORLD!-1""#), "{html}");
+}
@@ -0,0 +1,45 @@
+use dioxus::prelude::*;
+use dioxus_markdown::*;
+
+mod common;
+
+const SRC: &str = "See https://example.com/a_(b), www.example.org and me@example.net.\n\n`https://code.example.com`\n";
+
+#[test]
+fn bare_links_are_links() {
+    fn app(cx: Scope) -> Element {
+        render!{
+            Markdown { src: SRC, autolinks: true }
+        }
+    }
+    let html = common::render(app);
+    assert!(html.contains(r#"href="https://example.com/a_(b)""#), "{html}");
+    assert!(html.contains(r#"href="http://www.example.org""#), "{html}");
+    assert!(html.contains(r#"href="mailto:me@example.net""#), "{html}");
+    // the final period is not part of the email
+    assert!(!html.contains(r#"example.net.""#), "{html}");
+    assert!(!html.contains("code.example.com\""), "{html}");
+}
+
+#[test]
+fn positions_are_in_the_original_source() {
+    fn app(cx: Scope) -> Element {
+        render!{
+            Markdown { src: SRC, autolinks: true, source_positions: true }
+        }
+    }
+    let html = common::render(app);
+    let code = SRC.find('`').unwrap();
+    assert!(html.contains(&format!(r#"data-sourcepos="{code}-{}""#, SRC.len())), "{html}");
+}
+
+#[test]
+fn bare_links_are_text_by_default() {
+    fn app(cx: Scope) -> Element {
+        render!{
+            Markdown { src: SRC }
+        }
+    }
+    let html = common::render(app);
+    assert!(!html.contains("href"), "{html}");
+}
@@ -0,0 +1,36 @@
+use dioxus::prelude::*;
+use dioxus_markdown::*;
+
+mod common;
+
+const SRC: &str = "See [the end][end].\n\nSecond[^a].\n\nThird.\n\n[^a]: A note.\n\n[end]: https://example.com/end\n";
+
+fn chunked(cx: Scope) -> Element {
+    render!{
+        Markdown {
+            src: SRC,
+            footnotes: true,
+            chunked_render: ChunkConfig { initial_blocks: 1, blocks_per_chunk: 1, placeholder: true },
+        }
+    }
+}
+
+#[test]
+fn the_first_chunk_resolves_later_references() {
+    let html = common::render(chunked);
+    assert!(html.contains(r#"href="https://example.com/end""#), "{html}");
+    assert!(!html.contains("Second"), "{html}");
+    assert!(html.contains("md-loading"), "{html}");
+}
+
+#[test]
+fn every_chunk_is_rendered_in_the_end() {
+    let mut vdom = common::mount(chunked);
+    common::settle(&mut vdom);
+    let html = dioxus_ssr::render(&vdom);
+    for text in ["See", "Second", "Third", "A note."] {
+        assert_eq!(html.matches(text).count(), 1, "{text} in {html}");
+    }
+    assert!(!html.contains("md-loading"), "{html}");
+    assert!(html.contains(r#"id="fn-1""#), "{html}");
+}
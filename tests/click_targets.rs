@@ -0,0 +1,69 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use dioxus::core::{ElementId, Mutation};
+use dioxus::prelude::*;
+use dioxus_markdown::*;
+
+const SRC: &str = "# Title\n\nSome *text*.\n\n---\n";
+
+thread_local! {
+    static CLICKED: RefCell<Vec<ElementTag>> = RefCell::new(Vec::new());
+}
+
+/// the elements listening to clicks, in document order
+fn click_listeners(mutations: &[Mutation]) -> Vec<ElementId> {
+    mutations.iter()
+        .filter_map(|mutation| match mutation {
+            Mutation::NewEventListener { name: "click", id } => Some(*id),
+            _ => None,
+        })
+        .collect()
+}
+
+#[test]
+fn only_the_clicks_on_click_targets_are_reported() {
+    fn app(cx: Scope) -> Element {
+        render!{
+            Markdown {
+                src: SRC,
+                on_click: move |e: MarkdownMouseEvent| CLICKED.with(|c| c.borrow_mut().push(e.tag)),
+                click_targets: ElementFilter { headings: true, ..ElementFilter::none() },
+            }
+        }
+    }
+    let mut vdom = VirtualDom::new(app);
+    let mutations = vdom.rebuild();
+    for id in click_listeners(&mutations.edits) {
+        vdom.handle_event("click", Rc::new(MouseData::default()), id, true);
+    }
+    assert_eq!(CLICKED.with(|c| c.take()), vec![ElementTag::Heading { level: 1 }]);
+}
+
+#[test]
+fn no_element_listens_to_clicks_without_on_click() {
+    fn app(cx: Scope) -> Element {
+        render!{
+            Markdown { src: SRC, click_targets: ElementFilter::all() }
+        }
+    }
+    let mut vdom = VirtualDom::new(app);
+    let mutations = vdom.rebuild();
+    assert_eq!(click_listeners(&mutations.edits), vec![]);
+}
+
+#[test]
+fn the_elements_filtered_out_do_not_listen_to_clicks() {
+    fn app(cx: Scope) -> Element {
+        render!{
+            Markdown {
+                src: SRC,
+                on_click: move |_: MarkdownMouseEvent| (),
+                click_targets: ElementFilter { headings: true, ..ElementFilter::none() },
+            }
+        }
+    }
+    let mut vdom = VirtualDom::new(app);
+    let mutations = vdom.rebuild();
+    assert_eq!(click_listeners(&mutations.edits).len(), 1);
+}
@@ -0,0 +1,102 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use dioxus::core::{ElementId, Mutation};
+use dioxus::prelude::*;
+use dioxus::html::prelude::EvalProvider;
+use dioxus_markdown::*;
+
+mod common;
+
+const SRC: &str = "# Title\n\nSome *text*.\n";
+
+thread_local! {
+    static CLICKED: RefCell<Vec<(ElementTag, String)>> = RefCell::new(Vec::new());
+}
+
+/// the elements listening to clicks, in document order
+fn click_listeners(mutations: &[Mutation]) -> Vec<ElementId> {
+    mutations.iter()
+        .filter_map(|mutation| match mutation {
+            Mutation::NewEventListener { name: "click", id } => Some(*id),
+            _ => None,
+        })
+        .collect()
+}
+
+#[test]
+fn clicks_are_delegated_to_the_root_with_javascript() {
+    fn app(cx: Scope) -> Element {
+        cx.use_hook(|| cx.provide_context(Rc::new(common::SilentEval::default()) as Rc<dyn EvalProvider>));
+        render!{
+            Markdown { src: SRC, on_click: move |_: MarkdownMouseEvent| (), delegate_clicks: true }
+        }
+    }
+    let html = common::render(app);
+    assert_eq!(html.matches("data-md-root").count(), 1, "{html}");
+    assert!(html.contains(r#"data-md-target="0""#), "{html}");
+}
+
+#[test]
+fn elements_have_their_own_listener_by_default() {
+    fn app(cx: Scope) -> Element {
+        cx.use_hook(|| cx.provide_context(Rc::new(common::SilentEval::default()) as Rc<dyn EvalProvider>));
+        render!{
+            Markdown { src: SRC, on_click: move |_: MarkdownMouseEvent| () }
+        }
+    }
+    let html = common::render(app);
+    assert!(!html.contains("data-md-root"), "{html}");
+    assert!(!html.contains("data-md-target"), "{html}");
+}
+
+#[test]
+fn elements_have_their_own_listener_without_javascript() {
+    fn app(cx: Scope) -> Element {
+        render!{
+            Markdown { src: SRC, on_click: move |_: MarkdownMouseEvent| (), delegate_clicks: true }
+        }
+    }
+    let html = common::render(app);
+    assert!(!html.contains("data-md-root"), "{html}");
+    assert!(!html.contains("data-md-target"), "{html}");
+}
+
+#[test]
+fn nothing_is_delegated_without_on_click() {
+    fn app(cx: Scope) -> Element {
+        cx.use_hook(|| cx.provide_context(Rc::new(common::SilentEval::default()) as Rc<dyn EvalProvider>));
+        render!{
+            Markdown { src: SRC, delegate_clicks: true }
+        }
+    }
+    let html = common::render(app);
+    assert!(!html.contains("data-md-root"), "{html}");
+}
+
+#[test]
+fn keyboard_clicks_are_reported_during_the_event() {
+    fn app(cx: Scope) -> Element {
+        cx.use_hook(|| cx.provide_context(Rc::new(common::SilentEval::default()) as Rc<dyn EvalProvider>));
+        render!{
+            Markdown {
+                src: SRC,
+                on_click: move |e: MarkdownMouseEvent| {
+                    let clicked = e.source[e.position.clone()].to_string();
+                    CLICKED.with(|c| c.borrow_mut().push((e.tag, clicked)))
+                },
+            }
+        }
+    }
+    let mut vdom = VirtualDom::new(app);
+    let mutations = vdom.rebuild();
+    let listeners = click_listeners(&mutations.edits);
+    assert!(!listeners.is_empty());
+
+    // pressing enter on a focused element clicks it without a pointer, at 0,0
+    vdom.handle_event("click", Rc::new(MouseData::default()), listeners[0], true);
+    let clicked = CLICKED.with(|c| c.take());
+    assert_eq!(clicked.len(), 1, "{clicked:?}");
+    assert_eq!(clicked[0].0, ElementTag::Heading { level: 1 });
+    assert_eq!(clicked[0].1.trim_end(), "# Title");
+}
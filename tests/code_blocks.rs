@@ -0,0 +1,163 @@
+use std::rc::Rc;
+
+use dioxus::prelude::*;
+use dioxus_markdown::*;
+
+mod common;
+
+const SRC: &str = "text\n\n```rust\nlet x = 1;\n```\n\n    indented <code>\n";
+
+#[test]
+fn code_blocks_are_highlighted() {
+    fn app(cx: Scope) -> Element {
+        render!{ Markdown { src: SRC } }
+    }
+    let html = common::render(app);
+    assert!(html.contains(r#"<code class="language-rust">"#), "{html}");
+    assert!(html.contains("indented &lt;code&gt;"), "{html}");
+    assert!(!html.contains("md-code"), "{html}");
+}
+
+#[test]
+fn custom_renderer_gets_every_block() {
+    fn app(cx: Scope) -> Element {
+        render!{
+            Markdown {
+                src: SRC,
+                render_code_blocks: Rc::new(|cx: &ScopeState, block: CodeBlockDescription| {
+                    let language = block.language.unwrap_or_default();
+                    let written = &SRC[block.range];
+                    cx.render(rsx!{
+                        div { class: "block", "{language}|{block.code}|{written}" }
+                    })
+                }),
+            }
+        }
+    }
+    let html = common::render(app);
+    assert!(html.contains("rust|let x = 1;\n|```rust\nlet x = 1;\n```"), "{html}");
+    assert!(html.contains("|indented &lt;code&gt;\n|    indented &lt;code&gt;\n"), "{html}");
+}
+
+/// counts the highlights stored and reused
+#[derive(Default)]
+struct CountingCache {
+    inner: LruRenderCache,
+    puts: std::cell::Cell<usize>,
+    hits: std::cell::Cell<usize>,
+}
+
+impl RenderCache for CountingCache {
+    fn get(&self, key: &CacheKey) -> Option<Rc<str>> {
+        let value = self.inner.get(key);
+        self.hits.set(self.hits.get() + value.is_some() as usize);
+        value
+    }
+
+    fn put(&self, key: CacheKey, value: Rc<str>) {
+        self.puts.set(self.puts.get() + 1);
+        self.inner.put(key, value)
+    }
+}
+
+thread_local! {
+    static CACHE: Rc<CountingCache> = Rc::default();
+}
+
+#[test]
+fn highlighted_blocks_are_cached() {
+    fn app(cx: Scope) -> Element {
+        let cache: Rc<dyn RenderCache> = CACHE.with(|c| c.clone());
+        render!{
+            Markdown { src: SRC, cache: cache.clone() }
+            Markdown { src: SRC, cache: cache }
+        }
+    }
+    common::render(app);
+    CACHE.with(|cache| {
+        assert_eq!(cache.puts.get(), 1);
+        assert_eq!(cache.hits.get(), 1);
+    });
+}
+
+#[test]
+fn deferred_highlighting_keeps_the_text() {
+    fn app(cx: Scope) -> Element {
+        render!{ Markdown { src: SRC, defer_highlighting: true } }
+    }
+    let mut vdom = common::mount(app);
+    let plain = dioxus_ssr::render(&vdom);
+    assert!(plain.contains(r#"<code class="language-rust">let x = 1;"#), "{plain}");
+
+    common::settle(&mut vdom);
+    let highlighted = dioxus_ssr::render(&vdom);
+    assert!(!highlighted.contains(r#"<code class="language-rust">let x = 1;"#), "{highlighted}");
+    assert!(highlighted.contains("<span style="), "{highlighted}");
+}
+
+#[test]
+fn annotations_follow_their_line() {
+    fn app(cx: Scope) -> Element {
+        render!{
+            Markdown {
+                src: "```rust\nlet x = 1;\nlet y = 2;\n```\n",
+                code_line_annotations: Rc::new(|cx: &ScopeState, _: &CodeBlockDescription| {
+                    let mut annotations = std::collections::BTreeMap::new();
+                    annotations.insert(2, cx.render(rsx!{ "← y is 2" }));
+                    annotations
+                }) as Rc<dyn Fn(&ScopeState, &CodeBlockDescription) -> _>,
+            }
+        }
+    }
+    let html = common::render(app);
+    assert_eq!(html.matches(r#"class="md-line""#).count(), 2, "{html}");
+    let annotation = html.find("← y is 2").expect(&html);
+    assert!(html[..annotation].contains("y</span>"), "{html}");
+    assert!(html[annotation..].contains("</code>"), "{html}");
+}
+
+#[test]
+fn highlight_classes_are_scoped_to_the_theme() {
+    fn app(cx: Scope) -> Element {
+        render!{
+            Markdown { src: "```rust\nlet x = 1;\n```\n\n```rust\nlet y = 2;\n```\n", highlight_classes: true }
+            Markdown { src: "```rust\nlet z = 3;\n```\n", theme: "InspiredGitHub".to_string(), highlight_classes: true }
+        }
+    }
+    let html = common::render(app);
+    assert!(html.contains(r#"class="hl-base16-ocean-light-"#), "{html}");
+    assert!(html.contains(r#"class="hl-inspiredgithub-"#), "{html}");
+    assert!(!html.contains("style=\"color"), "{html}");
+    // without a document to mount them in, each stylesheet is rendered once
+    assert_eq!(html.matches(r#"<style id="hl-base16-ocean-light""#).count(), 1, "{html}");
+    assert_eq!(html.matches(r#"<style id="hl-inspiredgithub""#).count(), 1, "{html}");
+}
+
+/// remembers what is copied
+#[derive(Default)]
+struct Clipboard(std::cell::RefCell<Vec<String>>);
+
+impl ClipboardSink for Clipboard {
+    fn copy(&self, text: &str) {
+        self.0.borrow_mut().push(text.to_string())
+    }
+}
+
+#[test]
+fn copy_buttons_need_a_clipboard() {
+    fn app(cx: Scope) -> Element {
+        render!{ Markdown { src: SRC, copy_buttons: true } }
+    }
+    let html = common::render(app);
+    assert!(!html.contains("md-copy"), "{html}");
+}
+
+#[test]
+fn copy_buttons_are_shown_with_a_clipboard() {
+    fn app(cx: Scope) -> Element {
+        let clipboard: Rc<dyn ClipboardSink> = cx.use_hook(|| Rc::new(Clipboard::default())).clone();
+        render!{ Markdown { src: SRC, copy_buttons: true, clipboard: clipboard } }
+    }
+    let html = common::render(app);
+    assert_eq!(html.matches(r#"class="md-copy""#).count(), 2, "{html}");
+}
@@ -0,0 +1,69 @@
+#![allow(dead_code)]
+pub mod normalize;
+use std::cell::RefCell;
+use std::future::Future;
+use std::pin::Pin;
+use std::rc::Rc;
+
+use dioxus::prelude::*;
+use dioxus::html::prelude::{EvalError, EvalProvider, Evaluator};
+use futures::FutureExt;
+
+/// accepts any script and never answers, keeping the scripts it was given
+#[derive(Default)]
+pub struct SilentEval {
+    pub scripts: RefCell<Vec<String>>,
+}
+
+impl EvalProvider for SilentEval {
+    fn new_evaluator(&self, js: String) -> Result<Rc<dyn Evaluator>, EvalError> {
+        self.scripts.borrow_mut().push(js);
+        Ok(Rc::new(SilentEval::default()))
+    }
+}
+
+impl Evaluator for SilentEval {
+    fn send(&self, _data: serde_json::Value) -> Result<(), EvalError> {
+        Ok(())
+    }
+
+    fn recv(&self) -> Pin<Box<dyn Future<Output = Result<serde_json::Value, EvalError>>>> {
+        Box::pin(std::future::pending())
+    }
+
+    fn join(&self) -> Pin<Box<dyn Future<Output = Result<serde_json::Value, EvalError>>>> {
+        Box::pin(std::future::pending())
+    }
+}
+
+/// the html of `app` after its first render
+pub fn render(app: fn(Scope) -> Element) -> String {
+    let mut vdom = VirtualDom::new(app);
+    let _ = vdom.rebuild();
+    dioxus_ssr::render(&vdom)
+}
+
+/// a virtual dom of `app`, after its first render
+pub fn mount(app: fn(Scope) -> Element) -> VirtualDom {
+    let mut vdom = VirtualDom::new(app);
+    let _ = vdom.rebuild();
+    vdom
+}
+
+/// run the effects and tasks that are ready, and render again until nothing is left to do
+pub fn settle(vdom: &mut VirtualDom) {
+    for _ in 0..16 {
+        if vdom.wait_for_work().now_or_never().is_none() {
+            break
+        }
+        let _ = vdom.render_immediate();
+    }
+}
+
+/// render the root component again, as if its props changed
+pub fn rerender(vdom: &mut VirtualDom) -> String {
+    vdom.mark_dirty(ScopeId(0));
+    let _ = vdom.render_immediate();
+    settle(vdom);
+    dioxus_ssr::render(vdom)
+}
@@ -0,0 +1,308 @@
+//! a canonical form of html, to compare the output of different renderers
+
+/// the tags around which whitespace is not significant
+const BLOCK_TAGS: &[&str] = &[
+    "address", "article", "aside", "blockquote", "body", "caption", "col", "colgroup", "dd", "details",
+    "div", "dl", "dt", "fieldset", "figcaption", "figure", "footer", "form", "h1", "h2", "h3", "h4", "h5",
+    "h6", "header", "hr", "html", "iframe", "li", "main", "nav", "ol", "p", "pre", "script", "section",
+    "style", "summary", "table", "tbody", "td", "textarea", "tfoot", "th", "thead", "tr", "ul",
+];
+
+/// attributes whose value does not matter
+const BOOLEAN_ATTRIBUTES: &[&str] = &["checked", "disabled", "open"];
+
+/// the elements and attributes a renderer writes differently on purpose, see `normalize_html_with`
+#[derive(Default)]
+pub struct Equivalences {
+    /// elements renamed before comparing, like `("em", "i")`
+    pub elements: Vec<(String, String)>,
+    /// attributes of an element ignored, like `("ol", "start")`
+    pub ignored_attributes: Vec<(String, String)>,
+}
+
+enum Token {
+    Open { name: String, attributes: Vec<(String, String)> },
+    Close(String),
+    Text(String),
+    /// comments, doctypes, processing instructions and cdata, kept as written
+    Raw(String),
+}
+
+/// `html` in a canonical form:
+/// tag and attribute names are lowercase, attributes are sorted and their values decoded,
+/// character references are decoded in text and only `&`, `<` and `>` escaped again,
+/// and whitespace is collapsed outside of `pre`, and dropped around block tags.
+///
+/// What the renderers add for presentation is ignored:
+/// `data-` attributes, empty `class` and `style` attributes,
+/// styles other than `text-align`, and the comments dioxus separates text nodes with.
+/// The value of boolean attributes is ignored, `false` meaning absent
+pub fn normalize_html(html: &str) -> String {
+    normalize_html_with(html, &Equivalences::default())
+}
+
+/// `html` in a canonical form like `normalize_html`,
+/// with the elements of `equivalences` renamed and its attributes ignored
+pub fn normalize_html_with(html: &str, equivalences: &Equivalences) -> String {
+    let rename = |name: &str| equivalences.elements.iter()
+        .find(|(from, _)| from == name)
+        .map_or(name.to_string(), |(_, to)| to.clone());
+    let mut normalized = String::new();
+    let mut pre = 0usize;
+    let tokens = tokenize(html);
+    for (i, token) in tokens.iter().enumerate() {
+        match token {
+            Token::Open { name, attributes } => {
+                let attributes = attributes.iter()
+                    .filter(|(attribute, _)| !equivalences.ignored_attributes.iter().any(|(e, a)| e == name && a == attribute));
+                let name = &rename(name);
+                if is_block(name) && pre == 0 {
+                    trim_end(&mut normalized);
+                    normalized.push('\n')
+                }
+                if name == "pre" {
+                    pre += 1
+                }
+                normalized.push('<');
+                normalized.push_str(name);
+                for (attribute, value) in attributes {
+                    normalized.push_str(&format!(r#" {attribute}="{}""#, escape(value, true)));
+                }
+                normalized.push('>');
+            }
+            Token::Close(name) => {
+                let name = &rename(name);
+                if is_block(name) && pre == 0 {
+                    trim_end(&mut normalized);
+                }
+                if name == "pre" {
+                    pre = pre.saturating_sub(1)
+                }
+                normalized.push_str(&format!("</{name}>"));
+            }
+            Token::Text(text) if pre > 0 => normalized.push_str(&escape(text, false)),
+            Token::Text(text) => {
+                let mut text = collapse_whitespace(text);
+                if normalized.is_empty() || after_block(&tokens[..i]) || normalized.ends_with(' ') {
+                    text = text.trim_start().to_string()
+                }
+                if before_block(&tokens[i + 1..]) {
+                    text = text.trim_end().to_string()
+                }
+                normalized.push_str(&escape(&text, false))
+            }
+            Token::Raw(raw) => normalized.push_str(raw),
+        }
+    }
+    normalized.trim().to_string()
+}
+
+/// `html` without the element wrapping all of it, if there is one
+pub fn unwrap_root(html: &str) -> Option<&str> {
+    let inner = html.strip_prefix("<div>")?.strip_suffix("</div>")?;
+    // the closing tag must be the one of the first opening tag
+    let mut depth = 0isize;
+    for (i, _) in inner.match_indices("div") {
+        if inner[..i].ends_with("</") {
+            depth -= 1
+        } else if inner[..i].ends_with('<') {
+            depth += 1
+        }
+        if depth < 0 {
+            return None
+        }
+    }
+    Some(inner.trim())
+}
+
+fn is_block(name: &str) -> bool {
+    BLOCK_TAGS.contains(&name)
+}
+
+fn after_block(before: &[Token]) -> bool {
+    matches!(before.last(), Some(Token::Open { name, .. } | Token::Close(name)) if is_block(name))
+}
+
+fn before_block(after: &[Token]) -> bool {
+    match after.first() {
+        None => true,
+        Some(Token::Open { name, .. } | Token::Close(name)) => is_block(name),
+        _ => false,
+    }
+}
+
+fn trim_end(html: &mut String) {
+    html.truncate(html.trim_end_matches([' ', '\n']).len())
+}
+
+fn collapse_whitespace(text: &str) -> String {
+    let mut collapsed = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c.is_ascii_whitespace() {
+            true if collapsed.ends_with(' ') => {}
+            true => collapsed.push(' '),
+            false => collapsed.push(c),
+        }
+    }
+    collapsed
+}
+
+fn escape(text: &str, attribute: bool) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' if attribute => escaped.push_str("&quot;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// `text` with the character references the renderers emit decoded
+fn decode(text: &str) -> String {
+    let mut decoded = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(amp) = rest.find('&') {
+        decoded.push_str(&rest[..amp]);
+        rest = &rest[amp..];
+        let reference = rest.find(';')
+            .filter(|&end| end <= 10)
+            .and_then(|end| Some((reference_char(&rest[1..end])?, end)));
+        match reference {
+            Some((c, end)) => {
+                decoded.push(c);
+                rest = &rest[end + 1..]
+            }
+            None => {
+                decoded.push('&');
+                rest = &rest[1..]
+            }
+        }
+    }
+    decoded.push_str(rest);
+    decoded
+}
+
+fn reference_char(name: &str) -> Option<char> {
+    let code = match name {
+        "amp" => '&' as u32,
+        "lt" => '<' as u32,
+        "gt" => '>' as u32,
+        "quot" => '"' as u32,
+        "apos" => '\'' as u32,
+        "nbsp" => 0xa0,
+        _ => match name.strip_prefix('#')? {
+            hex if hex.starts_with(['x', 'X']) => u32::from_str_radix(&hex[1..], 16).ok()?,
+            decimal => decimal.parse().ok()?,
+        },
+    };
+    char::from_u32(code)
+}
+
+fn tokenize(html: &str) -> Vec<Token> {
+    let mut tokens = Vec::new();
+    let mut text = String::new();
+    let mut rest = html;
+    while let Some(c) = rest.chars().next() {
+        let tag = c == '<' && rest[1..].starts_with(|c: char| c.is_ascii_alphabetic() || c == '/' || c == '!' || c == '?');
+        if !tag {
+            text.push(c);
+            rest = &rest[c.len_utf8()..];
+            continue
+        }
+        if !text.is_empty() {
+            tokens.push(Token::Text(decode(&std::mem::take(&mut text))));
+        }
+        let end = match rest.starts_with("<!--") {
+            true => rest.find("-->").map_or(rest.len(), |end| end + 3),
+            false => tag_end(rest),
+        };
+        let (tag, after) = rest.split_at(end);
+        rest = after;
+        if tag.starts_with("<!") || tag.starts_with("<?") {
+            // dioxus separates adjacent text nodes with empty comments
+            if tag != "<!--#-->" {
+                tokens.push(Token::Raw(tag.to_string()))
+            }
+            continue
+        }
+        tokens.push(parse_tag(tag));
+    }
+    if !text.is_empty() {
+        tokens.push(Token::Text(decode(&text)));
+    }
+    tokens
+}
+
+/// the length of the tag at the start of `html`, quoted values included
+fn tag_end(html: &str) -> usize {
+    let mut quote = None;
+    for (i, c) in html.char_indices() {
+        match (quote, c) {
+            (None, '"' | '\'') => quote = Some(c),
+            (Some(q), c) if q == c => quote = None,
+            (None, '>') => return i + 1,
+            _ => {}
+        }
+    }
+    html.len()
+}
+
+fn parse_tag(tag: &str) -> Token {
+    let inner = tag.trim_start_matches('<').trim_end_matches('>').trim_end_matches('/');
+    if let Some(name) = inner.strip_prefix('/') {
+        return Token::Close(name.trim().to_ascii_lowercase())
+    }
+    let name_end = inner.find(|c: char| c.is_ascii_whitespace()).unwrap_or(inner.len());
+    let name = inner[..name_end].to_ascii_lowercase();
+    let mut attributes = Vec::new();
+    let mut rest = inner[name_end..].trim_start();
+    while !rest.is_empty() {
+        let end = rest.find(|c: char| c.is_ascii_whitespace() || c == '=').unwrap_or(rest.len());
+        let attribute = rest[..end].to_ascii_lowercase();
+        rest = rest[end..].trim_start();
+        let mut value = String::new();
+        if let Some(after) = rest.strip_prefix('=') {
+            let after = after.trim_start();
+            let (raw, remaining) = match after.chars().next() {
+                Some(q @ ('"' | '\'')) => {
+                    let end = after[1..].find(q).map_or(after.len(), |end| end + 1);
+                    (&after[1..end], after.get(end + 1..).unwrap_or_default())
+                }
+                _ => after.split_at(after.find(|c: char| c.is_ascii_whitespace()).unwrap_or(after.len())),
+            };
+            value = decode(raw);
+            rest = remaining.trim_start();
+        }
+        if let Some(attribute) = normalize_attribute(attribute, value) {
+            attributes.push(attribute)
+        }
+    }
+    attributes.sort();
+    Token::Open { name, attributes }
+}
+
+fn normalize_attribute(attribute: String, value: String) -> Option<(String, String)> {
+    if attribute.is_empty() || attribute.starts_with("data-") {
+        return None
+    }
+    if BOOLEAN_ATTRIBUTES.contains(&attribute.as_str()) {
+        return (value != "false").then(|| (attribute, String::new()))
+    }
+    let value = match attribute.as_str() {
+        "style" => value.split(';')
+            .map(|declaration| declaration.split(':').map(str::trim).collect::<Vec<_>>().join(": "))
+            .filter(|declaration| declaration.starts_with("text-align"))
+            .collect::<Vec<_>>()
+            .join("; "),
+        "class" => value.split_whitespace().collect::<Vec<_>>().join(" "),
+        _ => value,
+    };
+    if value.is_empty() && (attribute == "style" || attribute == "class") {
+        return None
+    }
+    Some((attribute, value))
+}
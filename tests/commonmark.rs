@@ -0,0 +1,162 @@
+//! the examples of the CommonMark spec and of a part of the GFM spec,
+//! rendered like on a server and compared to the spec after normalization.
+//! The differences on purpose are listed in `spec/deviations.txt`
+
+use std::rc::Rc;
+
+use dioxus::prelude::*;
+use dioxus_markdown::*;
+
+mod common;
+
+use common::normalize::{normalize_html_with, unwrap_root, Equivalences};
+
+struct Example {
+    example: usize,
+    section: String,
+    markdown: String,
+    html: String,
+}
+
+fn examples(json: &str) -> Vec<Example> {
+    let examples: Vec<serde_json::Value> = serde_json::from_str(json).unwrap();
+    examples.iter()
+        .map(|example| Example {
+            example: example["example"].as_u64().unwrap() as usize,
+            section: example["section"].as_str().unwrap().to_string(),
+            markdown: example["markdown"].as_str().unwrap().to_string(),
+            html: example["html"].as_str().unwrap().to_string(),
+        })
+        .collect()
+}
+
+/// an entry of `spec/deviations.txt`
+struct Deviation {
+    suite: String,
+    /// the number of the example, or the name of its section
+    example: String,
+}
+
+/// the entries of `spec/deviations.txt`
+fn entries() -> impl Iterator<Item = &'static str> {
+    include_str!("spec/deviations.txt").lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+}
+
+fn equivalences() -> Equivalences {
+    let mut equivalences = Equivalences::default();
+    for entry in entries() {
+        let (entry, _reason) = entry.split_once(':').expect("an entry has a reason");
+        if let Some(elements) = entry.strip_prefix("element ") {
+            let (spec, rendered) = elements.split_once('=').expect("an element entry has two elements");
+            equivalences.elements.push((spec.trim().to_string(), rendered.trim().to_string()))
+        }
+        if let Some(attribute) = entry.strip_prefix("attribute ") {
+            let (element, name) = attribute.trim().split_once(' ').expect("an attribute entry has an element");
+            equivalences.ignored_attributes.push((element.to_string(), name.trim().to_string()))
+        }
+    }
+    equivalences
+}
+
+fn deviations() -> Vec<Deviation> {
+    entries()
+        .filter(|entry| !entry.starts_with("element ") && !entry.starts_with("attribute "))
+        .map(|line| {
+            let (entry, _reason) = line.split_once(':').expect("an entry has a reason");
+            let (suite, example) = entry.split_once(' ').expect("an entry starts with its suite");
+            Deviation { suite: suite.to_string(), example: example.trim().to_string() }
+        })
+        .collect()
+}
+
+#[derive(Props, PartialEq)]
+struct ExampleProps {
+    src: String,
+}
+
+/// the options of the spec: the extensions of GFM, without smart punctuation
+fn spec_options() -> Options {
+    let mut options = Options::empty();
+    options.insert(Options::ENABLE_TABLES);
+    options.insert(Options::ENABLE_FOOTNOTES);
+    options.insert(Options::ENABLE_STRIKETHROUGH);
+    options.insert(Options::ENABLE_TASKLISTS);
+    options.insert(Options::ENABLE_HEADING_ATTRIBUTES);
+    options
+}
+
+#[allow(non_snake_case)]
+fn Rendered(cx: Scope<ExampleProps>) -> Element {
+    // the code is not highlighted, the spec only has it escaped
+    let code: Rc<dyn Fn(&CodeBlockDescription) -> Option<String>> = Rc::new(|block: &CodeBlockDescription| {
+        Some(block.code.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;"))
+    });
+    render!{
+        Markdown {
+            src: &cx.props.src,
+            parse_options: spec_options(),
+            smart_punctuation: false,
+            render_env: RenderEnv::Server,
+            prehighlighted_code: code,
+        }
+    }
+}
+
+fn render(src: &str) -> String {
+    let mut vdom = VirtualDom::new_with_props(Rendered, ExampleProps { src: src.to_string() });
+    let _ = vdom.rebuild();
+    dioxus_ssr::render(&vdom)
+}
+
+/// render the examples of `suite`, and fail on the differences with the spec not in the allowlist,
+/// and on the allowed examples that render like the spec
+fn check(suite: &str, json: &str) {
+    let deviations = deviations();
+    let equivalences = equivalences();
+    let allowed = |example: &Example| deviations.iter()
+        .filter(|deviation| deviation.suite == suite)
+        .find(|deviation| deviation.example == example.example.to_string() || deviation.example == example.section);
+
+    let mut failures = Vec::new();
+    let mut new_entries = Vec::new();
+    let mut fixed = Vec::new();
+    for example in examples(json) {
+        let expected = normalize_html_with(&example.html, &equivalences);
+        let rendered = normalize_html_with(&render(&example.markdown), &equivalences);
+        let same = rendered == expected || unwrap_root(&rendered) == Some(&*expected);
+        match (same, allowed(&example)) {
+            (false, None) => {
+                failures.push(format!(
+                    "example {} ({}):\n{}\nexpected:\n{expected}\nrendered:\n{rendered}\n",
+                    example.example, example.section, example.markdown,
+                ));
+                new_entries.push(format!("{suite} {}: ", example.example));
+            }
+            // the sections are not expected to differ in every example
+            (true, Some(deviation)) if deviation.example == example.example.to_string() => {
+                fixed.push(example.example)
+            }
+            _ => {}
+        }
+    }
+    assert!(
+        failures.is_empty() && fixed.is_empty(),
+        "{}\n{} examples differ from the spec, allow them in spec/deviations.txt with a reason:\n{}\n\
+        these allowed examples render like the spec, remove them from spec/deviations.txt: {fixed:?}",
+        failures.join("\n"),
+        failures.len(),
+        new_entries.join("\n"),
+    );
+}
+
+#[test]
+fn commonmark_spec() {
+    check("commonmark", include_str!("spec/commonmark.json"))
+}
+
+#[test]
+fn gfm_spec() {
+    check("gfm", include_str!("spec/gfm.json"))
+}
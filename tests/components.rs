@@ -0,0 +1,228 @@
+use std::rc::Rc;
+
+use dioxus::prelude::*;
+use dioxus_markdown::*;
+
+mod common;
+
+const BROKEN: &str = "before\n\n<Broken/>\n\nafter";
+
+fn broken_components<'a>() -> CustomComponents<'a> {
+    CustomComponents::new()
+        .with("Broken", |_, _| panic!("broken component"))
+}
+
+#[test]
+fn panicking_component_renders_the_default_boundary() {
+    fn app(cx: Scope) -> Element {
+        render!{
+            Markdown { src: BROKEN, components: broken_components() }
+        }
+    }
+    let html = common::render(app);
+    assert!(html.contains(r#"class="markdown-error""#), "{html}");
+    assert!(!html.contains("after"), "{html}");
+}
+
+#[test]
+fn panicking_component_renders_the_error_boundary() {
+    fn app(cx: Scope) -> Element {
+        render!{
+            Markdown {
+                src: BROKEN,
+                components: broken_components(),
+                error_boundary: Rc::new(|cx: &ScopeState, failure: RenderFailure| cx.render(rsx!{
+                    p { class: "boundary", "{failure.message}" }
+                })),
+            }
+        }
+    }
+    let html = common::render(app);
+    assert!(html.contains(r#"<p class="boundary">broken component</p>"#), "{html}");
+}
+
+#[test]
+fn document_renders_again_after_a_panicking_component() {
+    fn app(cx: Scope) -> Element {
+        render!{
+            Markdown { src: BROKEN, components: broken_components() }
+            Markdown { src: "<Toc/>\n\n# Title", components: CustomComponents::new().with_builtins() }
+        }
+    }
+    let html = common::render(app);
+    assert!(html.contains(r#"class="markdown-error""#), "{html}");
+    assert!(html.contains("Title"), "{html}");
+}
+
+const NESTED: &str = "<Outer>\n\n<Inner/>\n\n</Outer>\n\n<Inner/>";
+
+fn source_components<'a>() -> CustomComponents<'a> {
+    // each component shows the markdown it wraps
+    let show = |cx: &'a ScopeState, props: MdComponentProps<'a>| {
+        let children = props.children_text(NESTED).unwrap_or("none").trim().to_string();
+        Ok(cx.render(rsx!{
+            section { "{children}" }
+            props.children
+        }))
+    };
+    CustomComponents::new()
+        .with("Outer", show)
+        .with("Inner", show)
+}
+
+#[test]
+fn nested_components_find_their_own_tag() {
+    fn app(cx: Scope) -> Element {
+        render!{
+            Markdown { src: NESTED, components: source_components() }
+        }
+    }
+    let html = common::render(app);
+    assert!(html.contains("<section>&lt;Inner/&gt;</section>"), "{html}");
+    assert_eq!(html.matches("<section>none</section>").count(), 2, "{html}");
+}
+
+thread_local! {
+    static REGISTRY: ComponentRegistry = ComponentRegistry::new();
+}
+
+#[test]
+fn components_registered_later_are_rendered() {
+    fn app(cx: Scope) -> Element {
+        render!{
+            Markdown { src: "before\n\n<Late/>\n\nafter", registry: REGISTRY.with(Clone::clone) }
+        }
+    }
+    let mut vdom = common::mount(app);
+    let html = dioxus_ssr::render(&vdom);
+    assert!(!html.contains(r#"class="late""#), "{html}");
+
+    REGISTRY.with(|registry| registry.register("Late", |cx, _| Ok(cx.render(rsx!{
+        span { class: "late", "registered late" }
+    }))));
+    common::settle(&mut vdom);
+    let html = dioxus_ssr::render(&vdom);
+    assert!(html.contains(r#"<span class="late">registered late</span>"#), "{html}");
+    assert!(html.contains("after"), "{html}");
+}
+
+#[test]
+fn component_names_can_ignore_case() {
+    fn app(cx: Scope) -> Element {
+        let (components, _) = CustomComponents::new()
+            .with("Counter", |cx, _| Ok(cx.render(rsx!{ span { class: "counter", "counted" } })))
+            .case_insensitive(true);
+        render!{
+            Markdown { src: "<counter/>\n\n<COUNTER/>\n\n<Counter/>", components: components }
+        }
+    }
+    let html = common::render(app);
+    assert_eq!(html.matches(r#"<span class="counter">counted</span>"#).count(), 3, "{html}");
+}
+
+fn alert_components<'a>() -> CustomComponents<'a> {
+    CustomComponents::new()
+        .with("Alert", |cx: &'a ScopeState, props: MdComponentProps<'a>| {
+            let dismissible = props.get_flag("dismissible");
+            let parsed = props.get_parsed_optional::<bool>("dismissible")?;
+            let level = props.attributes.get("level").cloned().unwrap_or_default();
+            Ok(cx.render(rsx!{
+                p { class: "alert", "{level} {dismissible} {parsed:?}" }
+            }))
+        })
+}
+
+#[test]
+fn valueless_attributes_are_flags() {
+    fn app(cx: Scope) -> Element {
+        render!{
+            Markdown {
+                src: "<Alert dismissible level=\"warn\"/>\n\n<Alert level=\"info\"/>\n",
+                components: alert_components(),
+            }
+        }
+    }
+    let html = common::render(app);
+    assert!(html.contains(r#"<p class="alert">warn true Some(true)</p>"#), "{html}");
+    assert!(html.contains(r#"<p class="alert">info false None</p>"#), "{html}");
+}
+
+fn counter_components<'a>() -> CustomComponents<'a> {
+    CustomComponents::new()
+        .with("Counter", |cx: &'a ScopeState, props: MdComponentProps<'a>| {
+            let attributes: Vec<String> = props.attributes()
+                .map(|(name, value)| format!("{name}={value}"))
+                .collect();
+            let attributes = attributes.join(" ");
+            let reader = props.reader();
+            let initial: i32 = reader.get_parsed_optional("initial")?.unwrap_or_default();
+            let step: i32 = reader.get_parsed_optional("step")?.unwrap_or(1);
+            let unused = reader.unused_attributes().join(" ");
+            Ok(cx.render(rsx!{
+                p { class: "counter", "{initial}+{step} ({attributes}) unused: {unused}" }
+            }))
+        })
+}
+
+#[test]
+fn attributes_are_listed_and_typos_found() {
+    fn app(cx: Scope) -> Element {
+        render!{
+            Markdown {
+                src: "<Counter inital=\"5\" step=\"2\"/>\n\n<Counter initial=\"3\"/>\n",
+                components: counter_components(),
+            }
+        }
+    }
+    let html = common::render(app);
+    assert!(html.contains(r#"<p class="counter">0+2 (inital=5 step=2) unused: inital</p>"#), "{html}");
+    assert!(html.contains(r#"<p class="counter">3+1 (initial=3) unused: </p>"#), "{html}");
+}
+
+const TABS: &str = "<Tabs>\n\n**first**\n\n</Tabs>\n\n<Tabs/>";
+
+#[test]
+fn components_get_the_source_of_their_children() {
+    fn app(cx: Scope) -> Element {
+        let components = CustomComponents::new()
+            .with("Tabs", |cx, props| {
+                let source = props.children_text(TABS).unwrap_or("none").trim().to_string();
+                Ok(cx.render(rsx!{ pre { "{source}" } }))
+            });
+        render!{
+            Markdown { src: TABS, components: components }
+        }
+    }
+    let html = common::render(app);
+    assert!(html.contains("<pre>**first**</pre>"), "{html}");
+    assert!(html.contains("<pre>none</pre>"), "{html}");
+}
+
+#[test]
+fn names_differing_by_case_are_reported_as_conflicts() {
+    let components = CustomComponents::new()
+        .with("Late", |cx, _| Ok(cx.render(rsx!{ "upper" })))
+        .with("late", |cx, _| Ok(cx.render(rsx!{ "lower" })));
+    let (components, conflicts) = components.case_insensitive(true);
+    assert_eq!(conflicts.len(), 1, "{conflicts:?}");
+    assert_eq!(components.names().count(), 1);
+    assert!(components.contains("LATE"));
+}
+
+#[test]
+fn registered_components_are_found_ignoring_case() {
+    fn app(cx: Scope) -> Element {
+        let registry = use_markdown_components(cx, || {
+            let registry = ComponentRegistry::new();
+            registry.register("Late", |cx, _| Ok(cx.render(rsx!{ span { class: "late", "registered" } })));
+            registry
+        });
+        let (components, _) = CustomComponents::new().case_insensitive(true);
+        render!{
+            Markdown { src: "<late/>", components: components }
+            Markdown { src: "<late/>", registry: registry.clone() }
+        }
+    }
+    let html = common::render(app);
+    assert_eq!(html.matches(r#"class="late""#).count(), 1, "{html}");
+}
@@ -0,0 +1,47 @@
+use dioxus::prelude::*;
+use dioxus_markdown::*;
+
+mod common;
+
+const SRC: &str = "Term\n: definition\n: another\n\nafter\n";
+
+#[test]
+fn definition_lists_render_as_dl() {
+    fn app(cx: Scope) -> Element {
+        render!{
+            Markdown { src: SRC, definition_lists: true }
+        }
+    }
+    let html = common::render(app);
+    assert_eq!(html.matches("<dl").count(), 1, "{html}");
+    assert_eq!(html.matches("<dt").count(), 1, "{html}");
+    assert_eq!(html.matches("<dd").count(), 2, "{html}");
+    assert!(!html.contains("md-d"), "{html}");
+    let after = html.find("after").unwrap();
+    assert!(html.find("</dl>").is_some_and(|end| end < after), "{html}");
+}
+
+#[test]
+fn positions_are_in_the_original_source() {
+    fn app(cx: Scope) -> Element {
+        render!{
+            Markdown { src: SRC, definition_lists: true, source_positions: true }
+        }
+    }
+    let html = common::render(app);
+    assert!(html.contains(r#"data-sourcepos="0-5""#), "{html}");
+    assert!(html.contains(r#"data-sourcepos="7-18""#), "{html}");
+    assert!(html.contains(r#"data-sourcepos="29-35""#), "{html}");
+}
+
+#[test]
+fn definition_lists_are_text_by_default() {
+    fn app(cx: Scope) -> Element {
+        render!{
+            Markdown { src: SRC }
+        }
+    }
+    let html = common::render(app);
+    assert!(!html.contains("<dl"), "{html}");
+    assert!(html.contains(": definition"), "{html}");
+}
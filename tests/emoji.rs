@@ -0,0 +1,44 @@
+use std::collections::BTreeMap;
+
+use dioxus::prelude::*;
+use dioxus_markdown::*;
+
+mod common;
+
+#[test]
+fn custom_emoji_are_images() {
+    fn app(cx: Scope) -> Element {
+        let custom = BTreeMap::from([("ferris".to_string(), "ferris.png".to_string())]);
+        render!{
+            Markdown { src: "Hi :ferris:, `:ferris:` :nope:", emoji: true, custom_emoji: custom }
+        }
+    }
+    let html = common::render(app);
+    assert_eq!(html.matches(r#"class="emoji""#).count(), 1, "{html}");
+    assert!(html.contains(r#"src="ferris.png""#), "{html}");
+    assert!(html.contains(":ferris:</code>"), "{html}");
+    assert!(html.contains(":nope:"), "{html}");
+}
+
+#[cfg(feature="emoji")]
+#[test]
+fn shortcodes_are_replaced_with_the_emoji_feature() {
+    fn app(cx: Scope) -> Element {
+        render!{
+            Markdown { src: "Nice :+1:", emoji: true }
+        }
+    }
+    let html = common::render(app);
+    assert!(html.contains("👍") && !html.contains(":+1:"), "{html}");
+}
+
+#[test]
+fn shortcodes_are_text_by_default() {
+    fn app(cx: Scope) -> Element {
+        render!{
+            Markdown { src: "Nice :+1:" }
+        }
+    }
+    let html = common::render(app);
+    assert!(html.contains(":+1:"), "{html}");
+}
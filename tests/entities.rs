@@ -0,0 +1,86 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use dioxus::prelude::*;
+use dioxus_markdown::*;
+
+mod common;
+
+thread_local! {
+    static TITLES: RefCell<Vec<String>> = RefCell::new(Vec::new());
+}
+
+#[test]
+fn text_is_decoded_and_escaped_once() {
+    fn app(cx: Scope) -> Element {
+        render!{
+            Markdown { src: "&copy; 2024&nbsp;&#8212; a &lt;b&gt; &amp; c" }
+        }
+    }
+    let html = common::render(app);
+    assert!(html.contains("© 2024\u{a0}— a &lt;b&gt; &amp; c"), "{html}");
+    assert!(!html.contains("&amp;lt;"), "{html}");
+    assert!(!html.contains("nbsp"), "{html}");
+}
+
+#[test]
+fn text_is_displayed_as_written_without_decoding() {
+    fn app(cx: Scope) -> Element {
+        render!{
+            Markdown { src: "&copy; 2024&nbsp;&#8212; a", decode_entities: false }
+        }
+    }
+    let html = common::render(app);
+    // the references are text, so their `&` is escaped
+    assert!(html.contains("&amp;copy; 2024&amp;nbsp;&amp;#8212; a"), "{html}");
+}
+
+#[test]
+fn heading_slugs_use_the_decoded_characters() {
+    fn app(cx: Scope) -> Element {
+        render!{
+            Markdown { src: "# Caf&eacute; &amp; cr&egrave;me\n", heading_ids: true }
+            Markdown { src: "# Caf&eacute; &amp; cr&egrave;me\n", heading_ids: true, decode_entities: false }
+        }
+    }
+    let html = common::render(app);
+    assert_eq!(html.matches(r#"id="café--crème""#).count(), 2, "{html}");
+    assert!(html.contains("Café &amp; crème"), "{html}");
+}
+
+#[test]
+fn link_titles_are_decoded() {
+    fn app(cx: Scope) -> Element {
+        let links: HtmlCallback<LinkDescription<Element>> = Rc::new(|cx, link| {
+            TITLES.with(|titles| titles.borrow_mut().push(link.title.clone()));
+            cx.render(rsx!{ a { href: "{link.url}", link.content } })
+        });
+        render!{
+            Markdown { src: r#"[a link](/a "Tom &amp; Jerry&nbsp;&copy;")"#, render_links: links }
+        }
+    }
+    common::render(app);
+    assert_eq!(TITLES.with(|titles| titles.take()), vec!["Tom & Jerry\u{a0}©".to_string()]);
+}
+
+#[test]
+fn code_stays_literal() {
+    fn app(cx: Scope) -> Element {
+        render!{
+            Markdown { src: "`&copy;`\n\n```\n&amp; &#42;\n```\n" }
+        }
+    }
+    let html = common::render(app);
+    assert!(html.contains("&amp;copy;"), "{html}");
+    assert!(html.contains("&amp;amp; &amp;#42;"), "{html}");
+    assert!(!html.contains('©'), "{html}");
+}
+
+#[cfg(feature = "ssr")]
+#[test]
+fn server_rendering_encodes_once() {
+    let html = render_to_string("a &lt;b&gt; &amp; c `&lt;`");
+    assert!(html.contains("a &lt;b&gt; &amp; c"), "{html}");
+    assert!(html.contains("&amp;lt;"), "the code is literal in {html}");
+    assert_eq!(html.matches("&amp;lt;").count(), 1, "{html}");
+}
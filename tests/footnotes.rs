@@ -0,0 +1,83 @@
+use dioxus::prelude::*;
+use dioxus_markdown::*;
+
+mod common;
+
+/// one footnote cited three times, and two never cited
+const CITED: &str = "First[^cited], second[^cited] and third[^cited].
+
+[^unused]: Never cited.
+[^cited]: Cited three times.
+[^also-unused]: Never cited either.
+";
+
+fn cited() -> String {
+    fn app(cx: Scope) -> Element {
+        render!{
+            Markdown { src: CITED }
+        }
+    }
+    common::render(app)
+}
+
+#[test]
+fn each_reference_has_its_own_target() {
+    let html = cited();
+    for id in ["fnref-cited", "fnref-cited-1", "fnref-cited-2"] {
+        assert!(html.contains(&format!(r#"id="{id}""#)), "{id} in {html}");
+    }
+    assert_eq!(html.matches(r##"href="#fn-1""##).count(), 3, "{html}");
+}
+
+#[test]
+fn back_references_list_every_reference() {
+    let html = cited();
+    for (n, id) in ["fnref-cited", "fnref-cited-1", "fnref-cited-2"].into_iter().enumerate() {
+        assert!(html.contains(&format!(r##"href="#{id}""##)), "{id} in {html}");
+        assert!(html.contains(&format!("<sup>{}</sup>", n + 1)), "{html}");
+    }
+}
+
+#[test]
+fn unused_definitions_are_omitted() {
+    let html = cited();
+    assert!(!html.contains("Never cited"), "{html}");
+    assert!(html.contains("Cited three times"), "{html}");
+    assert!(!html.contains(r#"id="fn-2""#), "{html}");
+}
+
+#[test]
+fn footnotes_are_numbered_by_first_reference() {
+    fn app(cx: Scope) -> Element {
+        render!{
+            Markdown { src: "b[^b] a[^a]\n\n[^a]: A\n\n[^b]: B\n" }
+        }
+    }
+    let html = common::render(app);
+    let b = html.find(r#"id="fn-1""#).expect(&html);
+    let a = html.find(r#"id="fn-2""#).expect(&html);
+    assert!(b < a && html[b..a].contains('B'), "{html}");
+}
+
+#[test]
+fn a_single_reference_is_not_numbered() {
+    fn app(cx: Scope) -> Element {
+        render!{
+            Markdown { src: "a[^a]\n\n[^a]: A\n" }
+        }
+    }
+    let html = common::render(app);
+    assert!(html.contains(r##"href="#fnref-a""##), "{html}");
+    assert!(!html.contains("<sup>1</sup>"), "{html}");
+}
+
+#[test]
+fn nothing_is_a_footnote_without_the_parse_option() {
+    fn app(cx: Scope) -> Element {
+        render!{
+            Markdown { src: CITED, parse_options: Options::all() - Options::ENABLE_FOOTNOTES, footnotes: false }
+        }
+    }
+    let html = common::render(app);
+    assert!(!html.contains("footnote"), "{html}");
+}
@@ -0,0 +1,26 @@
+use std::cell::Cell;
+
+use dioxus::prelude::*;
+use dioxus_markdown::*;
+
+mod common;
+
+thread_local! {
+    static CALLS: Cell<usize> = Cell::new(0);
+}
+
+#[test]
+fn on_frontmatter_is_not_called_without_frontmatter() {
+    fn app(cx: Scope) -> Element {
+        render!{
+            Markdown {
+                src: "# Title\n\n---\n\nsome text\n",
+                on_frontmatter: move |_| CALLS.with(|c| c.set(c.get() + 1)),
+            }
+        }
+    }
+    CALLS.with(|c| c.set(0));
+    let mut vdom = common::mount(app);
+    common::rerender(&mut vdom);
+    assert_eq!(CALLS.with(|c| c.get()), 0);
+}
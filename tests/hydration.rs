@@ -0,0 +1,24 @@
+use dioxus::prelude::*;
+use dioxus_markdown::*;
+
+mod common;
+
+#[test]
+fn the_html_is_inserted_as_is() {
+    fn app(cx: Scope) -> Element {
+        render!{
+            HydratedMarkdown { html: r#"<ul><li data-sourcepos="0-9"><input type="checkbox"> task</li></ul>"# }
+        }
+    }
+    let html = common::render(app);
+    assert!(html.contains("data-md-hydrated="), "{html}");
+    assert!(html.contains(r#"<li data-sourcepos="0-9"><input type="checkbox"> task</li>"#), "{html}");
+}
+
+#[cfg(feature = "ssr")]
+#[test]
+fn hydratable_html_has_source_positions() {
+    let html = render_to_hydratable_string("- [ ] task\n");
+    assert!(html.contains(r#"data-sourcepos="0-"#), "{html}");
+    assert!(!render_to_string("- [ ] task\n").contains("data-sourcepos"));
+}
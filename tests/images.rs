@@ -0,0 +1,26 @@
+use std::rc::Rc;
+
+use dioxus::prelude::*;
+use dioxus_markdown::*;
+
+mod common;
+
+#[test]
+fn alt_text_is_flat_and_rich() {
+    fn app(cx: Scope) -> Element {
+        render!{
+            Markdown {
+                src: "![*rich* `alt` ![nested](n.png)](x.png)",
+                render_images: Rc::new(|cx: &ScopeState, image: ImageDescription| cx.render(rsx!{
+                    figure {
+                        img { src: "{image.url}", alt: "{image.alt}" }
+                        figcaption { image.alt_content }
+                    }
+                })),
+            }
+        }
+    }
+    let html = common::render(app);
+    assert!(html.contains(r#"alt="rich alt nested""#), "{html}");
+    assert!(html.contains("<figcaption><em>rich</em> <code>alt</code> nested</figcaption>"), "{html}");
+}
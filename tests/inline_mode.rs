@@ -0,0 +1,97 @@
+use dioxus::prelude::*;
+use dioxus_markdown::*;
+
+mod common;
+
+use common::normalize::{normalize_html, unwrap_root};
+
+#[derive(Props, PartialEq)]
+struct InlineProps {
+    src: &'static str,
+    #[props(default)]
+    extensions: bool,
+}
+
+#[allow(non_snake_case)]
+fn Inline(cx: Scope<InlineProps>) -> Element {
+    let extensions = cx.props.extensions;
+    render!{
+        Markdown {
+            src: cx.props.src,
+            inline_mode: true,
+            alerts: extensions,
+            definition_lists: extensions,
+            directives: extensions,
+        }
+    }
+}
+
+/// the normalized html of `src` in inline mode
+fn render(src: &'static str, extensions: bool) -> String {
+    let mut vdom = VirtualDom::new_with_props(Inline, InlineProps { src, extensions });
+    let _ = vdom.rebuild();
+    let html = normalize_html(&dioxus_ssr::render(&vdom));
+    unwrap_root(&html).map_or(html.clone(), String::from)
+}
+
+const BLOCK_ELEMENTS: &[&str] = &[
+    "div", "p", "ul", "ol", "li", "h1", "h2", "h3", "h4", "h5", "h6", "pre", "blockquote", "hr",
+    "table", "thead", "tbody", "tr", "td", "th", "section", "dl", "dt", "dd", "details", "summary",
+];
+
+/// fail if `html` has a block element
+fn assert_phrasing(html: &str) {
+    for element in BLOCK_ELEMENTS {
+        assert!(!html.contains(&format!("<{element}>")) && !html.contains(&format!("<{element} ")), "<{element}> in {html}");
+    }
+}
+
+#[test]
+fn blocks_degrade_to_phrasing_content() {
+    let snapshots = [
+        ("# Title\n\nSome *text*.\n", "<b>Title</b><span>Some <i>text</i>.</span>"),
+        ("- one\n- two\n", "<span><span>one </span><span>two </span></span>"),
+        ("> quoted\n", "<span><span>quoted</span></span>"),
+        ("```\nlet x;\n```\n", "<span><code>let x; </code></span>"),
+        ("before\n\n---\n\nafter\n", "<span>before</span><span>after</span>"),
+    ];
+    for (src, snapshot) in snapshots {
+        let html = render(src, false);
+        assert_phrasing(&html);
+        assert_eq!(html, snapshot, "{src:?}");
+    }
+}
+
+#[test]
+fn footnotes_stay_inline() {
+    let html = render("a[^1]\n\n[^1]: the note\n", false);
+    assert_phrasing(&html);
+    assert!(html.contains(r#"<span class="footnotes"><span id="fn-1">"#), "{html}");
+    assert!(html.contains("the note"), "{html}");
+}
+
+#[test]
+fn alerts_stay_inline() {
+    let html = render("> [!NOTE]\n> careful\n", true);
+    assert_phrasing(&html);
+    assert!(html.contains(r#"<span class="markdown-alert markdown-alert-note"><b class="markdown-alert-title">"#), "{html}");
+    assert!(html.contains("careful"), "{html}");
+}
+
+#[test]
+fn definition_lists_stay_inline() {
+    let html = render("term\n: definition\n", true);
+    assert_phrasing(&html);
+    assert!(html.contains("<b>term"), "{html}");
+    assert!(html.contains("definition"), "{html}");
+}
+
+#[test]
+fn directives_stay_inline() {
+    for src in ["::: warning Careful\nbody\n:::\n", "::: details More\nhidden\n:::\n"] {
+        let html = render(src, true);
+        assert_phrasing(&html);
+        assert!(html.contains(r#"<b class="md-directive-title">"#), "{html}");
+        assert!(html.contains("body") || html.contains("hidden"), "{html}");
+    }
+}
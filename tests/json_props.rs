@@ -0,0 +1,57 @@
+#![cfg(feature = "json-props")]
+use dioxus::prelude::*;
+use dioxus_markdown::*;
+
+mod common;
+
+fn chart<'a>() -> CustomComponents<'a> {
+    CustomComponents::new()
+        .with("Chart", |cx, props| {
+            let data: Vec<i64> = props.get_json("data")?;
+            let sum: i64 = data.iter().sum();
+            Ok(cx.render(rsx!{ span { class: "chart", "{data.len()} points, {sum}" } }))
+        })
+}
+
+#[test]
+fn json_attributes_are_parsed_with_either_quotes() {
+    fn app(cx: Scope) -> Element {
+        render!{
+            Markdown {
+                src: "<Chart data='[1,2,3]'/>\n\n<Chart data=\"[10, 20]\"/>\n",
+                components: chart(),
+            }
+        }
+    }
+    let html = common::render(app);
+    assert!(html.contains("3 points, 6"), "{html}");
+    assert!(html.contains("2 points, 30"), "{html}");
+}
+
+#[test]
+fn json_objects_keep_their_double_quotes_in_single_quoted_attributes() {
+    fn app(cx: Scope) -> Element {
+        let components = CustomComponents::new()
+            .with("Point", |cx, props| {
+                let point: std::collections::BTreeMap<String, i64> = props.get_json("at")?;
+                let (x, y) = (point["x"], point["y"]);
+                Ok(cx.render(rsx!{ span { "({x}, {y})" } }))
+            });
+        render!{
+            Markdown { src: r#"<Point at='{"x": 1, "y": 2}'/>"#, components: components }
+        }
+    }
+    let html = common::render(app);
+    assert!(html.contains("(1, 2)"), "{html}");
+}
+
+#[test]
+fn invalid_json_is_a_component_error() {
+    fn app(cx: Scope) -> Element {
+        render!{
+            Markdown { src: "<Chart data='[1,'/>", components: chart() }
+        }
+    }
+    let html = common::render(app);
+    assert!(html.contains("invalid json for `data`"), "{html}");
+}
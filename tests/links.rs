@@ -0,0 +1,65 @@
+use std::cell::{Cell, RefCell};
+use std::rc::Rc;
+
+use dioxus::core::Mutation;
+use dioxus::prelude::*;
+use dioxus_markdown::*;
+
+mod common;
+
+thread_local! {
+    static CUSTOM_LINKS: Cell<bool> = Cell::new(true);
+    static CLICKED: RefCell<Vec<LinkClickEvent>> = RefCell::new(Vec::new());
+}
+
+#[test]
+fn render_links_can_be_toggled_between_renders() {
+    fn app(cx: Scope) -> Element {
+        let custom: HtmlCallback<LinkDescription<Element>> = Rc::new(|cx, link| cx.render(rsx!{
+            a { class: "custom", href: "{link.url}", link.content }
+        }));
+        let src = "[a link](/a)\n";
+        if CUSTOM_LINKS.with(Cell::get) {
+            render!{ Markdown { src: src, render_links: custom } }
+        } else {
+            render!{ Markdown { src: src } }
+        }
+    }
+    let mut vdom = common::mount(app);
+    assert!(dioxus_ssr::render(&vdom).contains(r#"class="custom""#));
+
+    for custom in [false, true, false] {
+        CUSTOM_LINKS.with(|c| c.set(custom));
+        let html = common::rerender(&mut vdom);
+        assert_eq!(html.contains(r#"class="custom""#), custom, "{html}");
+        assert!(html.contains(r#"href="/a""#), "{html}");
+    }
+}
+
+#[test]
+fn link_clicks_have_the_source_of_the_render() {
+    fn app(cx: Scope) -> Element {
+        render!{
+            Markdown {
+                src: "Read [the docs](/docs).\n",
+                on_link_click: move |e: LinkClickEvent| {
+                    e.prevent_default();
+                    CLICKED.with(|c| c.borrow_mut().push(e))
+                },
+            }
+        }
+    }
+    let mut vdom = VirtualDom::new(app);
+    let mutations = vdom.rebuild();
+    let link = mutations.edits.iter()
+        .find_map(|mutation| match mutation {
+            Mutation::NewEventListener { name: "click", id } => Some(*id),
+            _ => None,
+        })
+        .expect("the link listens to clicks");
+
+    vdom.handle_event("click", Rc::new(MouseData::default()), link, true);
+    let clicked = CLICKED.with(|c| c.take());
+    assert_eq!(clicked.len(), 1);
+    assert_eq!(&*clicked[0].source, "Read [the docs](/docs).\n");
+}
@@ -0,0 +1,44 @@
+use dioxus::prelude::*;
+use dioxus_markdown::*;
+
+mod common;
+
+const SRC: &str = "Some ==highlighted **text**== and `==code==`.\n\n== not a mark ==\n";
+
+#[test]
+fn highlighted_text_renders_as_mark() {
+    fn app(cx: Scope) -> Element {
+        render!{
+            Markdown { src: SRC, mark: true }
+        }
+    }
+    let html = common::render(app);
+    assert_eq!(html.matches("<mark").count(), 1, "{html}");
+    assert!(html.contains("text</b></mark>"), "{html}");
+    assert!(html.contains("==code==</code>"), "{html}");
+    assert!(html.contains("== not a mark =="), "{html}");
+}
+
+#[test]
+fn positions_are_in_the_original_source() {
+    fn app(cx: Scope) -> Element {
+        render!{
+            Markdown { src: SRC, mark: true, source_positions: true }
+        }
+    }
+    let html = common::render(app);
+    let second = SRC.find("\n\n").unwrap() + 2;
+    assert!(html.contains(&format!(r#"data-sourcepos="{second}-{}""#, SRC.len())), "{html}");
+}
+
+#[test]
+fn marks_are_text_by_default() {
+    fn app(cx: Scope) -> Element {
+        render!{
+            Markdown { src: SRC }
+        }
+    }
+    let html = common::render(app);
+    assert!(!html.contains("<mark"), "{html}");
+    assert!(html.contains("==highlighted"), "{html}");
+}
@@ -0,0 +1,66 @@
+#![cfg(feature = "katex")]
+use std::rc::Rc;
+
+use dioxus::prelude::*;
+use dioxus_markdown::*;
+
+mod common;
+
+thread_local! {
+    static CACHE: Rc<LruRenderCache> = Rc::default();
+}
+
+const SRC: &str = "Squared: $x^2$";
+
+#[test]
+fn math_is_rendered_with_katex() {
+    fn app(cx: Scope) -> Element {
+        render!{
+            Markdown { src: SRC, math: true }
+        }
+    }
+    let html = common::render(app);
+    assert!(html.contains("katex"), "{html}");
+    assert!(!html.contains("math-source"), "{html}");
+}
+
+#[test]
+fn math_shows_its_source_by_default() {
+    fn app(cx: Scope) -> Element {
+        render!{
+            Markdown { src: SRC }
+        }
+    }
+    let html = common::render(app);
+    assert!(html.contains(r#"<code class="math-source">$x^2$</code>"#), "{html}");
+}
+
+fn key(tex: &str) -> CacheKey {
+    CacheKey::Math { tex: tex.to_string(), display: false }
+}
+
+#[test]
+fn math_is_served_from_the_cache() {
+    fn app(cx: Scope) -> Element {
+        let cache: Rc<dyn RenderCache> = CACHE.with(|c| c.clone());
+        render!{
+            Markdown { src: "Squared: $x^2$", math: true, cache: cache }
+        }
+    }
+    CACHE.with(|cache| cache.put(key("x^2"), "<b>cached</b>".into()));
+    let html = common::render(app);
+    assert!(html.contains("<b>cached</b>"), "{html}");
+}
+
+#[test]
+fn rendered_math_is_cached() {
+    fn app(cx: Scope) -> Element {
+        let cache: Rc<dyn RenderCache> = CACHE.with(|c| c.clone());
+        render!{
+            Markdown { src: "Cubed: $y^3$", math: true, cache: cache }
+        }
+    }
+    let html = common::render(app);
+    let cached = CACHE.with(|cache| cache.get(&key("y^3"))).expect("the expression is cached");
+    assert!(html.contains(&*cached), "{html}");
+}
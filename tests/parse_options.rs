@@ -0,0 +1,135 @@
+use dioxus::prelude::*;
+use dioxus_markdown::*;
+
+mod common;
+
+const SRC: &str = "| a | b |
+|---|---|
+| 1 | 2 |
+
+~~gone~~
+
+- [x] done
+
+Cited[^note].
+
+[^note]: A note.
+";
+
+fn has_table(html: &str) -> bool {
+    html.contains("<table")
+}
+
+fn has_strikethrough(html: &str) -> bool {
+    !html.contains("~~gone~~")
+}
+
+fn has_task(html: &str) -> bool {
+    html.contains(r#"type="checkbox""#)
+}
+
+fn has_footnote(html: &str) -> bool {
+    html.contains("fnref-note")
+}
+
+#[test]
+fn every_extension_is_on_by_default() {
+    fn app(cx: Scope) -> Element {
+        render!{
+            Markdown { src: SRC }
+        }
+    }
+    let html = common::render(app);
+    assert!(has_table(&html), "{html}");
+    assert!(has_strikethrough(&html), "{html}");
+    assert!(has_task(&html), "{html}");
+    assert!(has_footnote(&html), "{html}");
+}
+
+#[test]
+fn each_extension_can_be_turned_off() {
+    fn no_tables(cx: Scope) -> Element {
+        render!{ Markdown { src: SRC, tables: false } }
+    }
+    fn no_strikethrough(cx: Scope) -> Element {
+        render!{ Markdown { src: SRC, strikethrough: false } }
+    }
+    fn no_task_lists(cx: Scope) -> Element {
+        render!{ Markdown { src: SRC, task_lists: false } }
+    }
+    fn no_footnotes(cx: Scope) -> Element {
+        render!{ Markdown { src: SRC, footnotes: false } }
+    }
+
+    let html = common::render(no_tables);
+    assert!(!has_table(&html), "{html}");
+    assert!(has_strikethrough(&html) && has_task(&html) && has_footnote(&html), "{html}");
+
+    let html = common::render(no_strikethrough);
+    assert!(!has_strikethrough(&html), "{html}");
+    assert!(has_table(&html) && has_task(&html) && has_footnote(&html), "{html}");
+
+    let html = common::render(no_task_lists);
+    assert!(!has_task(&html), "{html}");
+    assert!(has_table(&html) && has_strikethrough(&html) && has_footnote(&html), "{html}");
+
+    let html = common::render(no_footnotes);
+    assert!(!has_footnote(&html), "{html}");
+    assert!(has_table(&html) && has_strikethrough(&html) && has_task(&html), "{html}");
+}
+
+#[test]
+fn extensions_are_added_to_parse_options() {
+    fn app(cx: Scope) -> Element {
+        render!{
+            Markdown { src: SRC, parse_options: Options::empty() }
+        }
+    }
+    let html = common::render(app);
+    assert!(has_table(&html), "{html}");
+    assert!(has_strikethrough(&html), "{html}");
+    assert!(has_task(&html), "{html}");
+    assert!(has_footnote(&html), "{html}");
+}
+
+#[test]
+fn parse_options_decide_for_the_extensions_turned_off() {
+    fn app(cx: Scope) -> Element {
+        let mut options = Options::empty();
+        options.insert(Options::ENABLE_TABLES);
+        render!{
+            Markdown {
+                src: SRC,
+                parse_options: options,
+                tables: false,
+                strikethrough: false,
+                task_lists: false,
+                footnotes: false,
+            }
+        }
+    }
+    let html = common::render(app);
+    assert!(has_table(&html), "{html}");
+    assert!(!has_strikethrough(&html), "{html}");
+    assert!(!has_task(&html), "{html}");
+    assert!(!has_footnote(&html), "{html}");
+}
+
+#[test]
+fn smart_punctuation_is_added_to_parse_options() {
+    fn smart(cx: Scope) -> Element {
+        render!{
+            Markdown { src: r#""quoted" -- and..."#, parse_options: Options::empty() }
+        }
+    }
+    fn straight(cx: Scope) -> Element {
+        render!{
+            Markdown { src: r#""quoted" -- and..."#, parse_options: Options::empty(), smart_punctuation: false }
+        }
+    }
+    let html = common::render(smart);
+    assert!(html.contains("“quoted” – and…"), "{html}");
+    let html = common::render(straight);
+    assert!(html.contains("-- and..."), "{html}");
+    assert!(!html.contains('“'), "{html}");
+}
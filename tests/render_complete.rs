@@ -0,0 +1,108 @@
+use std::cell::{Cell, RefCell};
+use std::rc::Rc;
+
+use dioxus::prelude::*;
+use dioxus_markdown::*;
+
+mod common;
+
+thread_local! {
+    static SRC: RefCell<&'static str> = RefCell::new("");
+    /// the number of times the text was rendered, once per render of the document
+    static RENDERS: Cell<usize> = Cell::new(0);
+    static COMPLETED: RefCell<Vec<RenderStats>> = RefCell::new(Vec::new());
+}
+
+const DOCUMENT: &str = "# Title\n\nsome [link](https://example.com) text\n\n![image](image.png)\n\n> ```\n> not counted\n> ```\n";
+
+fn app(cx: Scope) -> Element {
+    let src = SRC.with(|src| *src.borrow());
+    render!{
+        Markdown {
+            src: src,
+            on_render_complete: move |stats| COMPLETED.with(|c| c.borrow_mut().push(stats)),
+        }
+    }
+}
+
+#[test]
+fn on_render_complete_reports_the_counts_of_the_document() {
+    SRC.with(|src| src.replace(DOCUMENT));
+    COMPLETED.with(|c| c.take());
+    let mut vdom = common::mount(app);
+    common::settle(&mut vdom);
+    let completed = COMPLETED.with(|c| c.take());
+    assert_eq!(completed, vec![RenderStats {
+        blocks: 4,
+        headings: 1,
+        links: 1,
+        images: 1,
+        code_blocks: 1,
+        words: 5,
+    }]);
+}
+
+#[test]
+fn on_render_complete_is_called_again_only_for_a_new_source() {
+    SRC.with(|src| src.replace("some text"));
+    COMPLETED.with(|c| c.take());
+    let mut vdom = common::mount(app);
+    common::settle(&mut vdom);
+    common::rerender(&mut vdom);
+    assert_eq!(COMPLETED.with(|c| c.take()).len(), 1);
+
+    SRC.with(|src| src.replace("other text here"));
+    common::rerender(&mut vdom);
+    let completed = COMPLETED.with(|c| c.take());
+    assert_eq!(completed.len(), 1, "{completed:?}");
+    assert_eq!(completed[0].words, 3);
+}
+
+fn count_renders<'a>() -> Rc<dyn Fn(&'a ScopeState, &str, std::ops::Range<usize>) -> Option<Element<'a>>> {
+    Rc::new(|_, _, _| {
+        RENDERS.with(|r| r.set(r.get() + 1));
+        None
+    })
+}
+
+/// the renders of the document for the first source, then for a new one
+fn renders(app: fn(Scope) -> Element) -> (usize, usize) {
+    SRC.with(|src| src.replace("some text"));
+    RENDERS.with(|r| r.set(0));
+    let mut vdom = common::mount(app);
+    common::settle(&mut vdom);
+    let first = RENDERS.with(|r| r.replace(0));
+    SRC.with(|src| src.replace("other text"));
+    common::rerender(&mut vdom);
+    (first, RENDERS.with(|r| r.get()))
+}
+
+#[test]
+fn a_new_source_is_rendered_once_without_on_render_complete() {
+    fn app(cx: Scope) -> Element {
+        let src = SRC.with(|src| *src.borrow());
+        render!{
+            Markdown { src: src, transform_text: count_renders() }
+        }
+    }
+    assert_eq!(renders(app), (1, 1));
+}
+
+#[test]
+fn on_render_complete_is_called_once_per_source() {
+    fn app(cx: Scope) -> Element {
+        let src = SRC.with(|src| *src.borrow());
+        render!{
+            Markdown {
+                src: src,
+                transform_text: count_renders(),
+                on_render_complete: move |stats| COMPLETED.with(|c| c.borrow_mut().push(stats)),
+            }
+        }
+    }
+    COMPLETED.with(|c| c.take());
+    assert_eq!(renders(app), (2, 2));
+    let completed = COMPLETED.with(|c| c.take());
+    assert_eq!(completed.len(), 2, "{completed:?}");
+    assert_eq!(completed[1].words, 2);
+}
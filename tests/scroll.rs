@@ -0,0 +1,48 @@
+use dioxus::prelude::*;
+use dioxus_markdown::*;
+
+mod common;
+
+#[test]
+fn blocks_have_source_positions_to_restore_the_scroll() {
+    fn app(cx: Scope) -> Element {
+        let scroll_state = use_state(cx, ScrollState::default);
+        render!{
+            Markdown {
+                src: "first\n\nsecond\n",
+                scroll_container: "#article".to_string(),
+                scroll_state: scroll_state.clone(),
+            }
+        }
+    }
+    let html = common::render(app);
+    assert!(html.contains(r#"data-sourcepos="7-14""#), "{html}");
+}
+
+#[test]
+fn nothing_is_done_without_a_container() {
+    fn app(cx: Scope) -> Element {
+        let scroll_state = use_state(cx, ScrollState::default);
+        render!{
+            Markdown { src: "first\n\nsecond\n", scroll_state: scroll_state.clone() }
+        }
+    }
+    let html = common::render(app);
+    assert!(!html.contains("data-sourcepos"), "{html}");
+}
+
+#[test]
+fn footnote_jumps_keep_the_scroll_offset() {
+    fn app(cx: Scope) -> Element {
+        render!{
+            Markdown { src: "Cited[^note].\n\n[^note]: A note.\n", scroll_offset: 48.0 }
+        }
+    }
+    let html = common::render(app);
+    // both ends of the jump: the reference to the note, and the note back to the reference
+    for id in ["fn-1", "fnref-note"] {
+        let start = html.find(&format!(r#"id="{id}""#)).expect(&html);
+        let tag = &html[html[..start].rfind('<').unwrap()..start + html[start..].find('>').unwrap()];
+        assert!(tag.contains("scroll-margin-top: 48px"), "{tag}");
+    }
+}
@@ -0,0 +1,57 @@
+use std::rc::Rc;
+
+use dioxus::prelude::*;
+use dioxus_markdown::*;
+
+mod common;
+
+const SRC: &str = "::: details More about it\nHidden at first.\n:::\n\n::: details\nNo title.\n:::\n";
+
+#[test]
+fn details_are_closed_by_default() {
+    fn app(cx: Scope) -> Element {
+        render!{
+            Markdown { src: SRC, directives: true }
+        }
+    }
+    let html = common::render(app);
+    assert_eq!(html.matches("<details").count(), 2, "{html}");
+    assert!(html.contains("<summary>More about it</summary>"), "{html}");
+    assert!(html.contains("<summary>Details</summary>"), "{html}");
+    assert!(html.contains("Hidden at first."), "{html}");
+    assert!(!html.contains("open="), "{html}");
+}
+
+#[test]
+fn initial_open_sections_are_open() {
+    fn app(cx: Scope) -> Element {
+        render!{
+            Markdown {
+                src: SRC,
+                directives: true,
+                initial_open_sections: vec!["more-about-it".to_string()],
+            }
+        }
+    }
+    let html = common::render(app);
+    assert_eq!(html.matches("open=").count(), 1, "{html}");
+    let open = html.find(r#"open="true""#).expect(&html);
+    assert!(open < html.find("More about it").unwrap(), "{html}");
+}
+
+#[test]
+fn section_ids_follow_slugify() {
+    fn app(cx: Scope) -> Element {
+        let slugify: Rc<dyn Fn(&str) -> String> = Rc::new(|title| title.to_uppercase().replace(' ', "_"));
+        render!{
+            Markdown {
+                src: SRC,
+                directives: true,
+                slugify: slugify,
+                initial_open_sections: vec!["MORE_ABOUT_IT".to_string()],
+            }
+        }
+    }
+    let html = common::render(app);
+    assert_eq!(html.matches("open=").count(), 1, "{html}");
+}
@@ -0,0 +1,34 @@
+use dioxus::prelude::*;
+use dioxus_markdown::*;
+
+mod common;
+
+const SRC: &str = "# Title\n\nSome *text*.\n";
+
+#[test]
+fn elements_have_their_source_range_with_source_attributes() {
+    fn app(cx: Scope) -> Element {
+        render!{
+            Markdown { src: SRC, source_attributes: true }
+        }
+    }
+    let html = common::render(app);
+    assert!(html.contains(r#"data-md-start="14" data-md-end="20""#), "{html}");
+}
+
+#[test]
+fn elements_have_no_source_range_by_default() {
+    fn app(cx: Scope) -> Element {
+        render!{
+            Markdown { src: SRC }
+        }
+    }
+    let html = common::render(app);
+    assert!(!html.contains("data-md-start"), "{html}");
+}
+
+#[test]
+fn a_selection_covers_both_of_its_ends() {
+    assert_eq!(selection_to_source_range(14..20, 0..8), 0..20);
+    assert_eq!(selection_to_source_range(9..21, 14..20), 9..21);
+}
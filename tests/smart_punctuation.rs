@@ -0,0 +1,29 @@
+use dioxus::prelude::*;
+use dioxus_markdown::*;
+
+mod common;
+
+const SRC: &str = "\"Quoted\" -- and... `\"code\" --`";
+
+#[test]
+fn punctuation_is_smart_by_default() {
+    fn app(cx: Scope) -> Element {
+        render!{
+            Markdown { src: SRC }
+        }
+    }
+    let html = common::render(app);
+    assert!(html.contains('“') && html.contains('–') && html.contains('…'), "{html}");
+    assert!(html.contains("\"code\" --</code>") || html.contains("&quot;code&quot; --</code>"), "{html}");
+}
+
+#[test]
+fn smart_punctuation_can_be_turned_off() {
+    fn app(cx: Scope) -> Element {
+        render!{
+            Markdown { src: SRC, smart_punctuation: false }
+        }
+    }
+    let html = common::render(app);
+    assert!(!html.contains('“') && !html.contains('…'), "{html}");
+}
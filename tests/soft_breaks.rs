@@ -0,0 +1,47 @@
+use dioxus::prelude::*;
+use dioxus_markdown::*;
+
+mod common;
+
+use common::normalize::normalize_html;
+
+const CJK: &str = "日本語の\n文章です\n\n```\nlet x;\nlet y;\n```\n";
+
+#[derive(Props, PartialEq)]
+struct BreakProps {
+    mode: SoftBreakMode,
+}
+
+#[allow(non_snake_case)]
+fn Breaks(cx: Scope<BreakProps>) -> Element {
+    render!{
+        Markdown { src: CJK, soft_break: cx.props.mode }
+    }
+}
+
+fn render(mode: SoftBreakMode) -> String {
+    let mut vdom = VirtualDom::new_with_props(Breaks, BreakProps { mode });
+    let _ = vdom.rebuild();
+    dioxus_ssr::render(&vdom)
+}
+
+#[test]
+fn cjk_lines_are_joined_without_a_gap() {
+    let html = render(SoftBreakMode::Nothing);
+    assert!(normalize_html(&html).contains("日本語の文章です"), "{html}");
+    // code blocks keep their lines
+    assert!(html.contains("let x;\nlet y;"), "{html}");
+}
+
+#[test]
+fn soft_breaks_are_spaces_by_default() {
+    let html = normalize_html(&render(SoftBreakMode::Space));
+    assert!(html.contains("日本語の 文章です"), "{html}");
+}
+
+#[test]
+fn soft_breaks_can_be_line_breaks() {
+    let html = normalize_html(&render(SoftBreakMode::Br));
+    let br = html.find("<br>").expect(&html);
+    assert!(html[..br].ends_with("日本語の") && html[br..].contains("文章です"), "{html}");
+}
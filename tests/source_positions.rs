@@ -0,0 +1,34 @@
+use dioxus::prelude::*;
+use dioxus_markdown::*;
+
+mod common;
+
+const SRC: &str = "first\n\n*second*\n\n```\ncode\n```\n";
+
+#[test]
+fn blocks_have_their_source_position() {
+    fn app(cx: Scope) -> Element {
+        render!{
+            Markdown { src: SRC, source_positions: true }
+        }
+    }
+    let html = common::render(app);
+    assert!(html.contains(r#"data-sourcepos="0-6""#), "{html}");
+    assert!(html.contains(r#"data-sourcepos="7-16""#), "{html}");
+    let pre = &html[html.find("<pre").unwrap()..];
+    assert!(pre[..pre.find('>').unwrap()].contains("data-sourcepos"), "{html}");
+    // only blocks get one, not the emphasis
+    let em = &html[html.find("<i").unwrap()..];
+    assert!(!em[..em.find('>').unwrap()].contains("data-sourcepos"), "{html}");
+}
+
+#[test]
+fn blocks_have_no_source_position_by_default() {
+    fn app(cx: Scope) -> Element {
+        render!{
+            Markdown { src: SRC }
+        }
+    }
+    let html = common::render(app);
+    assert!(!html.contains("data-sourcepos"), "{html}");
+}
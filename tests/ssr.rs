@@ -0,0 +1,68 @@
+use std::rc::Rc;
+
+use dioxus::prelude::*;
+use dioxus::html::prelude::EvalProvider;
+use dioxus_markdown::*;
+
+mod common;
+
+use common::SilentEval;
+
+const SRC: &str = "```rust\nlet x = 1;\n```\n\n[a link](/a)\n";
+
+thread_local! {
+    static EVAL: Rc<SilentEval> = Rc::default();
+}
+
+fn scripts() -> Vec<String> {
+    EVAL.with(|eval| eval.scripts.take())
+}
+
+#[test]
+fn the_server_renders_the_static_fallbacks() {
+    fn app(cx: Scope) -> Element {
+        cx.use_hook(|| cx.provide_context(EVAL.with(|eval| eval.clone() as Rc<dyn EvalProvider>)));
+        render!{
+            Markdown {
+                src: SRC,
+                render_env: RenderEnv::Server,
+                copy_buttons: true,
+                highlight_classes: true,
+                on_click: move |_: MarkdownMouseEvent| (),
+                delegate_clicks: true,
+            }
+        }
+    }
+    scripts();
+    let html = common::render(app);
+    // there is no clipboard to copy to
+    assert!(!html.contains("md-copy"), "{html}");
+    // the stylesheet of the theme is in the html instead of mounted
+    assert!(html.contains(r#"<style id="hl-"#), "{html}");
+    // the elements have their own listeners
+    assert!(!html.contains("data-md-root"), "{html}");
+    assert_eq!(scripts(), Vec::<String>::new());
+}
+
+#[test]
+fn the_client_relies_on_javascript() {
+    fn app(cx: Scope) -> Element {
+        cx.use_hook(|| cx.provide_context(EVAL.with(|eval| eval.clone() as Rc<dyn EvalProvider>)));
+        render!{
+            Markdown {
+                src: SRC,
+                render_env: RenderEnv::Client,
+                copy_buttons: true,
+                highlight_classes: true,
+                on_click: move |_: MarkdownMouseEvent| (),
+                delegate_clicks: true,
+            }
+        }
+    }
+    scripts();
+    let html = common::render(app);
+    assert!(html.contains("md-copy"), "{html}");
+    assert!(!html.contains(r#"<style id="hl-"#), "{html}");
+    assert!(html.contains("data-md-root"), "{html}");
+    assert!(scripts().iter().any(|script| script.contains("createElement(\"style\")")));
+}
@@ -0,0 +1,15 @@
+use dioxus_markdown::*;
+
+#[test]
+fn tables_are_extracted_from_the_rewritten_source() {
+    // the table is indented in the definition, a code block without definition lists
+    let src = "Term\n: Definition\n\n    | a | b |\n    |---|---|\n    | 1 | 2 |\n";
+    let config = ParseConfig { definition_lists: true, ..ParseConfig::default() };
+    let tables = extract_tables(src, &config);
+    assert_eq!(tables.len(), 1, "{tables:?}");
+    assert_eq!(tables[0].headers, ["a", "b"]);
+    assert_eq!(tables[0].rows, [["1", "2"]]);
+    assert!(src[tables[0].range.clone()].contains("| 1 | 2 |"), "{:?}", tables[0].range);
+
+    assert!(extract_tables(src, &ParseConfig::default()).is_empty());
+}
@@ -0,0 +1,105 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use dioxus::core::{ElementId, Mutation};
+use dioxus::prelude::*;
+use dioxus_markdown::*;
+
+mod common;
+
+thread_local! {
+    static SOURCE: RefCell<String> = RefCell::new(String::new());
+    static TOGGLED: RefCell<Vec<CheckboxToggleEvent>> = RefCell::new(Vec::new());
+}
+
+/// the elements listening to clicks, in document order
+fn click_listeners(mutations: &[Mutation]) -> Vec<ElementId> {
+    mutations.iter()
+        .filter_map(|mutation| match mutation {
+            Mutation::NewEventListener { name: "click", id } => Some(*id),
+            _ => None,
+        })
+        .collect()
+}
+
+#[test]
+fn toggling_a_task_patches_a_crlf_source_exactly() {
+    fn app(cx: Scope) -> Element {
+        let src = use_state(cx, || "- [ ] one\r\n- [ ] two\r\n\r\ndone\r\n".to_string());
+        SOURCE.with(|source| source.replace(src.get().clone()));
+        render!{
+            Markdown { src: src.get(), normalize_newlines: true, task_list_sync: src.clone() }
+        }
+    }
+    let mut vdom = VirtualDom::new(app);
+    let mutations = vdom.rebuild();
+    let checkboxes = click_listeners(&mutations.edits);
+    assert_eq!(checkboxes.len(), 2);
+
+    vdom.handle_event("click", Rc::new(MouseData::default()), checkboxes[1], true);
+    common::settle(&mut vdom);
+    assert_eq!(SOURCE.with(|source| source.borrow().clone()), "- [ ] one\r\n- [x] two\r\n\r\ndone\r\n");
+}
+
+/// a task in a definition, which is only a list once the definition list is rewritten,
+/// and one after a highlighted line
+const EXTENDED: &str = "==Todo==
+
+Term
+: - [ ] inside
+
+- [ ] after
+";
+
+#[test]
+fn tasks_are_extracted_from_the_rewritten_source() {
+    let config = ParseConfig { mark: true, definition_lists: true, ..ParseConfig::default() };
+    let tasks = extract_tasks(EXTENDED, &config);
+    assert_eq!(tasks.len(), 2, "{tasks:?}");
+    assert_eq!(tasks[0].text, "inside");
+    assert_eq!(tasks[1].text, "after");
+    for task in &tasks {
+        assert_eq!(&EXTENDED[task.marker_range.clone()], "[ ]");
+    }
+}
+
+#[test]
+fn toggling_a_task_after_rewritten_lines_patches_the_clicked_one() {
+    fn app(cx: Scope) -> Element {
+        let src = use_state(cx, || EXTENDED.to_string());
+        SOURCE.with(|source| source.replace(src.get().clone()));
+        render!{
+            Markdown { src: src.get(), mark: true, definition_lists: true, task_list_sync: src.clone() }
+        }
+    }
+    let mut vdom = VirtualDom::new(app);
+    let mutations = vdom.rebuild();
+    let checkboxes = click_listeners(&mutations.edits);
+    assert_eq!(checkboxes.len(), 2);
+
+    vdom.handle_event("click", Rc::new(MouseData::default()), checkboxes[1], true);
+    common::settle(&mut vdom);
+    assert_eq!(SOURCE.with(|source| source.borrow().clone()), EXTENDED.replace("- [ ] after", "- [x] after"));
+}
+
+#[test]
+fn checkbox_toggles_have_the_source_of_the_render() {
+    fn app(cx: Scope) -> Element {
+        render!{
+            Markdown {
+                src: "- [ ] one\n- [x] two\n",
+                on_checkbox_toggle: move |e: CheckboxToggleEvent| TOGGLED.with(|t| t.borrow_mut().push(e)),
+            }
+        }
+    }
+    let mut vdom = VirtualDom::new(app);
+    let mutations = vdom.rebuild();
+    let checkboxes = click_listeners(&mutations.edits);
+    assert_eq!(checkboxes.len(), 2);
+
+    vdom.handle_event("click", Rc::new(MouseData::default()), checkboxes[1], true);
+    let toggled = TOGGLED.with(|t| t.take());
+    assert_eq!(toggled.len(), 1);
+    assert!(!toggled[0].checked);
+    assert_eq!(&toggled[0].source[toggled[0].marker_range.clone()], "[x]");
+}
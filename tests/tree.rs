@@ -0,0 +1,54 @@
+use dioxus_markdown::*;
+
+const SRC: &str = "Some ==marked== text.
+
+Term
+: Definition
+";
+
+/// the first node of `kind` in `node`, depth first
+fn find<'n>(node: &'n MdNode, kind: &NodeKind) -> Option<&'n MdNode> {
+    if &node.kind == kind {
+        return Some(node)
+    }
+    node.children.iter().find_map(|child| find(child, kind))
+}
+
+#[test]
+fn extensions_are_parsed_like_the_renderer_does() {
+    let config = ParseConfig { mark: true, definition_lists: true, ..ParseConfig::default() };
+    let tree = parse_to_tree(SRC, &config, |_| false);
+
+    let mark = find(&tree, &NodeKind::Mark).expect("a mark node");
+    assert_eq!(mark.plain_text(), "marked");
+    assert_eq!(&SRC[mark.range.clone()], "==marked==");
+
+    let list = find(&tree, &NodeKind::DefinitionList).expect("a definition list");
+    let term = find(list, &NodeKind::DefinitionTerm).expect("a term");
+    assert_eq!(term.plain_text(), "Term");
+    assert!(SRC[term.range.clone()].contains("Term"), "{:?}", term.range);
+    let details = find(list, &NodeKind::DefinitionDetails).expect("a definition");
+    assert_eq!(details.plain_text(), "Definition");
+    assert!(SRC[details.range.clone()].contains(": Definition"), "{:?}", details.range);
+
+    assert_eq!(tree.range, 0..SRC.len());
+}
+
+#[test]
+fn extensions_are_text_when_turned_off() {
+    let tree = parse_to_tree(SRC, &ParseConfig::default(), |_| false);
+    assert!(find(&tree, &NodeKind::Mark).is_none());
+    assert!(find(&tree, &NodeKind::DefinitionList).is_none());
+    assert!(tree.plain_text().contains("==marked=="), "{}", tree.plain_text());
+}
+
+#[test]
+fn ranges_are_in_the_source_before_rewrites() {
+    let src = "a ==b==\r\n\r\nc ==d==\r\n";
+    let config = ParseConfig { mark: true, normalize_newlines: true, ..ParseConfig::default() };
+    let tree = parse_to_tree(src, &config, |_| false);
+    let paragraphs: Vec<_> = tree.children.iter().filter(|node| node.kind == NodeKind::Paragraph).collect();
+    assert_eq!(paragraphs.len(), 2);
+    let second = find(paragraphs[1], &NodeKind::Mark).expect("a mark node");
+    assert_eq!(&src[second.range.clone()], "==d==");
+}